@@ -0,0 +1,129 @@
+use cosmwasm_std::{
+    BankMsg, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
+    Storage,
+};
+use cw721::Cw721Execute;
+
+use crate::query::query_royalties_info;
+use crate::state::{CONFIG, ROYALTY_TOKENS};
+use crate::{ContractError, Cw2981Contract, Extension};
+
+/// Transfers the token, paying the royalty share of any attached funds to the
+/// token's royalty recipients and the rest to the previous owner. If the
+/// collection charges a transfer fee, it comes out of the funds first.
+/// Without funds this is a plain transfer.
+pub fn transfer_nft_with_royalty(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let contract = Cw2981Contract::default();
+    if info.funds.is_empty() {
+        return contract.transfer_nft(deps, env, info, recipient, token_id);
+    }
+
+    // the transfer fee is paid by the base transfer, the sale price is what's left
+    let (fee, sale) = contract.split_transfer_fee(deps.storage, &token_id, &info.funds)?;
+
+    // work out the split before the transfer changes the owner
+    let seller = contract.load_token(deps.storage, &token_id)?.owner;
+    let mut royalty: Vec<(String, Vec<Coin>)> = vec![];
+    let mut proceeds = vec![];
+    for coin in &sale {
+        let royalty_info = query_royalties_info(deps.as_ref(), token_id.clone(), coin.amount)?;
+        let mut rest = coin.amount;
+        for payment in royalty_info.payments {
+            // a percentage over 100 can't take more than was actually paid
+            let royalty_amount = payment.royalty_amount.min(rest);
+            if royalty_amount.is_zero() {
+                continue;
+            }
+            rest -= royalty_amount;
+            let coin = Coin::new(royalty_amount.u128(), &coin.denom);
+            match royalty
+                .iter_mut()
+                .find(|(address, _)| *address == payment.address)
+            {
+                Some((_, coins)) => coins.push(coin),
+                None => royalty.push((payment.address, vec![coin])),
+            }
+        }
+        if !rest.is_zero() {
+            proceeds.push(Coin::new(rest.u128(), &coin.denom));
+        }
+    }
+
+    // the transfer only gets the fee, so the sale price isn't paid out twice
+    let info = MessageInfo {
+        funds: fee.into_iter().collect(),
+        ..info
+    };
+    let mut res = contract.transfer_nft(deps, env, info, recipient, token_id)?;
+    for (royalty_address, amount) in royalty {
+        res = res
+            .add_message(BankMsg::Send {
+                to_address: royalty_address.clone(),
+                amount,
+            })
+            .add_attribute("royalty_address", royalty_address);
+    }
+    if !proceeds.is_empty() {
+        res = res.add_message(BankMsg::Send {
+            to_address: seller.to_string(),
+            amount: proceeds,
+        });
+    }
+    Ok(res)
+}
+
+/// Brings the royalty index in line with the token's current extension.
+/// Call after anything that creates, edits or removes a token
+pub fn sync_royalty_index(storage: &mut dyn Storage, token_id: &str) -> StdResult<()> {
+    let token = Cw2981Contract::default()
+        .tokens
+        .may_load(storage, token_id)?;
+    // a burned or exempt token, or one without a royalty_percentage or split, owes nothing
+    let has_royalty = token
+        .filter(|token| !token.royalty_exempt)
+        .and_then(|token| token.extension)
+        .is_some_and(|ext| {
+            ext.royalty_percentage.is_some()
+                || ext.royalty_split.is_some_and(|split| !split.is_empty())
+        });
+    if has_royalty {
+        ROYALTY_TOKENS.save(storage, token_id, &Empty {})
+    } else {
+        ROYALTY_TOKENS.remove(storage, token_id);
+        Ok(())
+    }
+}
+
+/// Rejects an extension whose royalty_percentage, or royalty_split total, is
+/// above the configured cap. A split can never add up to more than the whole
+/// sale price, whatever the cap. Call before anything that sets a token's extension
+pub fn validate_royalty(deps: Deps, extension: &Extension) -> Result<(), ContractError> {
+    let metadata = match extension {
+        Some(metadata) => metadata,
+        None => return Ok(()),
+    };
+    let max = CONFIG.load(deps.storage)?.max_royalty_share;
+    if let Some(split) = &metadata.royalty_split {
+        let mut total = Decimal::zero();
+        for (address, share) in split {
+            deps.api.addr_validate(address.as_str())?;
+            total = total.checked_add(*share).map_err(StdError::from)?;
+        }
+        let max = max.min(Decimal::one());
+        if total > max {
+            return Err(ContractError::RoyaltyTooHigh { max });
+        }
+    }
+    if let Some(percentage) = metadata.royalty_percentage {
+        if Decimal::percent(percentage) > max {
+            return Err(ContractError::RoyaltyTooHigh { max });
+        }
+    }
+    Ok(())
+}
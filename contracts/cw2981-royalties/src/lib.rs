@@ -1,19 +1,28 @@
+pub mod execute;
 pub mod msg;
 pub mod query;
+pub mod state;
 
+pub use execute::{sync_royalty_index, transfer_nft_with_royalty, validate_royalty};
 pub use query::{check_royalties, query_royalties_info};
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{to_binary, Empty};
+use cosmwasm_std::{to_binary, Addr, Decimal, Empty};
 use cw2::set_contract_version;
 use cw721_base::Cw721Contract;
-pub use cw721_base::{ContractError, InstantiateMsg, MintMsg, MinterResponse};
+pub use cw721_base::{
+    ContractError, InstantiateMsg as Cw721BaseInstantiateMsg, MintMsg, MinterResponse,
+    SupportedStandardsResponse,
+};
 
 use crate::msg::Cw2981QueryMsg;
+pub use crate::msg::InstantiateMsg;
 
 // Version info for migration
 const CONTRACT_NAME: &str = "crates.io:cw2981-royalties";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// Identifier reported by SupportedStandards
+pub const CW2981_STANDARD: &str = "cw2981";
 
 #[cw_serde]
 pub struct Trait {
@@ -42,6 +51,11 @@ pub struct Metadata {
     /// as the minter addr
     /// question: how do we validate this?
     pub royalty_payment_address: Option<String>,
+    /// Splits the royalty between several addresses instead, each share being
+    /// a fraction of the sale price (0.05 is 5%). The shares add up to the
+    /// token's whole royalty, and royalty_percentage and
+    /// royalty_payment_address are ignored while this is set
+    pub royalty_split: Option<Vec<(Addr, Decimal)>>,
 }
 
 pub type Extension = Option<Metadata>;
@@ -55,9 +69,10 @@ pub type QueryMsg = cw721_base::QueryMsg<Cw2981QueryMsg>;
 #[cfg(not(feature = "library"))]
 pub mod entry {
     use super::*;
+    use crate::state::{Config, CONFIG};
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
 
     #[entry_point]
     pub fn instantiate(
@@ -66,7 +81,26 @@ pub mod entry {
         info: MessageInfo,
         msg: InstantiateMsg,
     ) -> Result<Response, ContractError> {
-        let res = Cw2981Contract::default().instantiate(deps.branch(), env, info, msg)?;
+        let config = Config {
+            enforce_royalty_on_transfer: msg.enforce_royalty_on_transfer,
+            max_royalty_share: msg
+                .max_royalty_share
+                .unwrap_or_else(state::default_max_royalty_share),
+        };
+        CONFIG.save(deps.storage, &config)?;
+
+        let cw721_base_instantiate_msg = Cw721BaseInstantiateMsg {
+            name: msg.name,
+            symbol: msg.symbol,
+            minter: msg.minter,
+            ..Default::default()
+        };
+        let res = Cw2981Contract::default().instantiate(
+            deps.branch(),
+            env,
+            info,
+            cw721_base_instantiate_msg,
+        )?;
         // Explicitly set contract name and version, otherwise set to cw721-base info
         set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)
             .map_err(ContractError::Std)?;
@@ -75,12 +109,64 @@ pub mod entry {
 
     #[entry_point]
     pub fn execute(
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
         msg: ExecuteMsg,
     ) -> Result<Response, ContractError> {
-        Cw2981Contract::default().execute(deps, env, info, msg)
+        let config = CONFIG.load(deps.storage)?;
+        let contract = Cw2981Contract::default();
+        // a token can't be given a royalty above the cap, whether on mint or later
+        match &msg {
+            ExecuteMsg::Mint(MintMsg { extension, .. })
+            | ExecuteMsg::MintSigned {
+                msg: MintMsg { extension, .. },
+                ..
+            }
+            | ExecuteMsg::MintAndSend {
+                mint: MintMsg { extension, .. },
+                ..
+            }
+            | ExecuteMsg::MintNext { extension, .. }
+            | ExecuteMsg::UpdateExtension { extension, .. } => {
+                validate_royalty(deps.as_ref(), extension)?
+            }
+            _ => {}
+        }
+        // these mint, burn or change whether a token carries royalties, so the index is synced
+        // afterwards for every token the response names
+        let syncs_royalty_index = matches!(
+            msg,
+            ExecuteMsg::Mint(_)
+                | ExecuteMsg::MintSigned { .. }
+                | ExecuteMsg::MintAndSend { .. }
+                | ExecuteMsg::MintNext { .. }
+                | ExecuteMsg::UpdateExtension { .. }
+                | ExecuteMsg::SetRoyaltyExempt { .. }
+                | ExecuteMsg::Burn { .. }
+                | ExecuteMsg::BurnAllOwnedBy { .. }
+                | ExecuteMsg::AdminBurn { .. }
+        );
+        let res = match msg {
+            ExecuteMsg::TransferNft {
+                recipient,
+                token_id,
+            } if config.enforce_royalty_on_transfer => {
+                transfer_nft_with_royalty(deps.branch(), env, info, recipient, token_id)
+            }
+            _ => contract.execute(deps.branch(), env, info, msg),
+        }?;
+        if syncs_royalty_index {
+            for attr in res.attributes.iter().filter(|attr| attr.key == "token_id") {
+                sync_royalty_index(deps.storage, &attr.value)?;
+            }
+        }
+        Ok(res)
+    }
+
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        Cw2981Contract::default().reply(deps, env, msg)
     }
 
     #[entry_point]
@@ -93,6 +179,13 @@ pub mod entry {
                 } => to_binary(&query_royalties_info(deps, token_id, sale_price)?),
                 Cw2981QueryMsg::CheckRoyalties {} => to_binary(&check_royalties(deps)?),
             },
+            // everything cw721-base supports, plus royalties
+            QueryMsg::SupportedStandards {} => {
+                let SupportedStandardsResponse { mut standards } =
+                    Cw2981Contract::default().supported_standards();
+                standards.push(CW2981_STANDARD.to_string());
+                to_binary(&SupportedStandardsResponse { standards })
+            }
             _ => Cw2981Contract::default().query(deps, env, msg),
         }
     }
@@ -101,9 +194,10 @@ pub mod entry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::msg::{CheckRoyaltiesResponse, RoyaltiesInfoResponse};
+    use crate::msg::{CheckRoyaltiesResponse, RoyaltiesInfoResponse, RoyaltyPayment};
+    use crate::state::ROYALTY_TOKENS;
 
-    use cosmwasm_std::{from_binary, Uint128};
+    use cosmwasm_std::{attr, coin, coins, from_binary, BankMsg, DepsMut, SubMsg, Uint128};
 
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
     use cw721::Cw721Query;
@@ -120,6 +214,7 @@ mod tests {
             name: "SpaceShips".to_string(),
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
+            ..Default::default()
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -133,6 +228,9 @@ mod tests {
                 name: Some("Starship USS Enterprise".to_string()),
                 ..Metadata::default()
             }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
         };
         let exec_msg = ExecuteMsg::Mint(mint_msg.clone());
         entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
@@ -152,9 +250,11 @@ mod tests {
             name: "SpaceShips".to_string(),
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
+            ..Default::default()
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
+        // a token without royalty config doesn't turn royalties on
         let token_id = "Enterprise";
         let mint_msg = MintMsg {
             token_id: token_id.to_string(),
@@ -165,9 +265,37 @@ mod tests {
                 name: Some("Starship USS Enterprise".to_string()),
                 ..Metadata::default()
             }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
         };
         let exec_msg = ExecuteMsg::Mint(mint_msg);
-        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+
+        let res = check_royalties(deps.as_ref()).unwrap();
+        assert_eq!(
+            res,
+            CheckRoyaltiesResponse {
+                royalty_payments: false,
+            }
+        );
+
+        // once a token carries a royalty, marketplaces should check on sale
+        let mint_msg = MintMsg {
+            token_id: "Voyager".to_string(),
+            owner: "janeway".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("janeway".to_string()),
+                royalty_percentage: Some(4),
+                ..Metadata::default()
+            }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        let exec_msg = ExecuteMsg::Mint(mint_msg);
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
 
         let expected = CheckRoyaltiesResponse {
             royalty_payments: true,
@@ -182,6 +310,48 @@ mod tests {
         let query_res: CheckRoyaltiesResponse =
             from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
         assert_eq!(query_res, expected);
+
+        // burning the only royalty token turns it back off
+        let burn_msg = ExecuteMsg::Burn {
+            token_id: "Voyager".to_string(),
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("janeway", &[]),
+            burn_msg,
+        )
+        .unwrap();
+        let res = check_royalties(deps.as_ref()).unwrap();
+        assert!(!res.royalty_payments);
+
+        // and so does clearing the royalty through an extension update
+        let mint_msg = MintMsg {
+            token_id: "Defiant".to_string(),
+            owner: "sisko".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_percentage: Some(10),
+                ..Metadata::default()
+            }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Mint(mint_msg),
+        )
+        .unwrap();
+        assert!(check_royalties(deps.as_ref()).unwrap().royalty_payments);
+        let update_msg = ExecuteMsg::UpdateExtension {
+            token_id: "Defiant".to_string(),
+            extension: Some(Metadata::default()),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, update_msg).unwrap();
+        assert!(!check_royalties(deps.as_ref()).unwrap().royalty_payments);
     }
 
     #[test]
@@ -193,6 +363,7 @@ mod tests {
             name: "SpaceShips".to_string(),
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
+            ..Default::default()
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -208,13 +379,20 @@ mod tests {
                 royalty_percentage: Some(10),
                 ..Metadata::default()
             }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
         };
         let exec_msg = ExecuteMsg::Mint(mint_msg.clone());
         entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
 
         let expected = RoyaltiesInfoResponse {
-            address: mint_msg.owner,
+            address: mint_msg.owner.clone(),
             royalty_amount: Uint128::new(10),
+            payments: vec![RoyaltyPayment {
+                address: mint_msg.owner,
+                royalty_amount: Uint128::new(10),
+            }],
         };
         let res =
             query_royalties_info(deps.as_ref(), token_id.to_string(), Uint128::new(100)).unwrap();
@@ -245,6 +423,9 @@ mod tests {
                 royalty_percentage: Some(4),
                 ..Metadata::default()
             }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
         };
         let voyager_exec_msg = ExecuteMsg::Mint(second_mint_msg.clone());
         entry::execute(deps.as_mut(), mock_env(), info, voyager_exec_msg).unwrap();
@@ -252,8 +433,12 @@ mod tests {
         // 43 x 0.04 (i.e., 4%) should be 1.72
         // we expect this to be rounded down to 1
         let voyager_expected = RoyaltiesInfoResponse {
-            address: second_mint_msg.owner,
+            address: second_mint_msg.owner.clone(),
             royalty_amount: Uint128::new(1),
+            payments: vec![RoyaltyPayment {
+                address: second_mint_msg.owner,
+                royalty_amount: Uint128::new(1),
+            }],
         };
 
         let res = query_royalties_info(
@@ -264,4 +449,627 @@ mod tests {
         .unwrap();
         assert_eq!(res, voyager_expected);
     }
+
+    #[test]
+    fn enforce_royalty_on_transfer() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            enforce_royalty_on_transfer: true,
+            ..Default::default()
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let token_id = "Defiant";
+        let mint_msg = MintMsg {
+            token_id: token_id.to_string(),
+            owner: "sisko".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("starfleet".to_string()),
+                royalty_percentage: Some(10),
+                ..Metadata::default()
+            }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Mint(mint_msg)).unwrap();
+
+        // a paid transfer splits the funds between the royalty address and the seller
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: "kira".to_string(),
+            token_id: token_id.to_string(),
+        };
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sisko", &coins(100, "uatom")),
+            transfer_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "starfleet".to_string(),
+                    amount: coins(10, "uatom"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "sisko".to_string(),
+                    amount: coins(90, "uatom"),
+                }),
+            ]
+        );
+
+        // without funds it is just a free transfer
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: "odo".to_string(),
+            token_id: token_id.to_string(),
+        };
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("kira", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+
+        let contract = Cw2981Contract::default();
+        let owner = contract
+            .owner_of(deps.as_ref(), mock_env(), token_id.to_string(), false)
+            .unwrap();
+        assert_eq!(owner.owner, "odo");
+    }
+
+    #[test]
+    fn transfer_fee_comes_out_of_a_royalty_sale() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            enforce_royalty_on_transfer: true,
+            ..Default::default()
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        // cw2981 doesn't take a transfer fee at instantiate, so set it on the base config
+        let contract = Cw2981Contract::default();
+        let mut config = contract.config(&deps.storage).unwrap();
+        config.transfer_fee = Some(coin(20, "uatom"));
+        contract.config.save(&mut deps.storage, &config).unwrap();
+
+        let token_id = "Enterprise";
+        let mint_msg = MintMsg {
+            token_id: token_id.to_string(),
+            owner: "picard".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("starfleet".to_string()),
+                royalty_percentage: Some(10),
+                ..Metadata::default()
+            }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Mint(mint_msg)).unwrap();
+
+        // of 120 paid, 20 is the fee and the royalty and seller split the other 100,
+        // so no more goes out than came in
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: "riker".to_string(),
+            token_id: token_id.to_string(),
+        };
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("picard", &coins(120, "uatom")),
+            transfer_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: CREATOR.to_string(),
+                    amount: coins(20, "uatom"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "starfleet".to_string(),
+                    amount: coins(10, "uatom"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "picard".to_string(),
+                    amount: coins(90, "uatom"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn royalty_exempt_token_pays_no_royalty() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            enforce_royalty_on_transfer: true,
+            ..Default::default()
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let token_id = "Rubicon";
+        let mint_msg = MintMsg {
+            token_id: token_id.to_string(),
+            owner: "nog".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("starfleet".to_string()),
+                royalty_percentage: Some(10),
+                ..Metadata::default()
+            }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Mint(mint_msg),
+        )
+        .unwrap();
+        let exempt_msg = ExecuteMsg::SetRoyaltyExempt {
+            token_id: token_id.to_string(),
+            exempt: true,
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, exempt_msg).unwrap();
+
+        // the token reports no royalty, whatever its metadata says
+        let res =
+            query_royalties_info(deps.as_ref(), token_id.to_string(), Uint128::new(100)).unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: String::new(),
+                royalty_amount: Uint128::zero(),
+                payments: vec![],
+            }
+        );
+
+        // so a paid transfer sends everything to the seller
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: "rom".to_string(),
+            token_id: token_id.to_string(),
+        };
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("nog", &coins(100, "uatom")),
+            transfer_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "nog".to_string(),
+                amount: coins(100, "uatom"),
+            })]
+        );
+    }
+
+    #[test]
+    fn royalty_share_is_capped() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            ..Default::default()
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let mint_with_royalty = |token_id: &str, percentage| {
+            ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: "kirk".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    royalty_payment_address: Some("kirk".to_string()),
+                    royalty_percentage: Some(percentage),
+                    ..Metadata::default()
+                }),
+                metadata_hash: None,
+                edition: None,
+                edition_of: None,
+            })
+        };
+
+        // right at the default 10% cap is fine
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            mint_with_royalty("Enterprise", 10),
+        )
+        .unwrap();
+
+        // anything above it is rejected, on mint...
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            mint_with_royalty("Excelsior", 11),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RoyaltyTooHigh {
+                max: Decimal::percent(10),
+            }
+        );
+
+        // ...and when overriding an existing token's royalty
+        let update_msg = ExecuteMsg::UpdateExtension {
+            token_id: "Enterprise".to_string(),
+            extension: Some(Metadata {
+                royalty_percentage: Some(25),
+                ..Metadata::default()
+            }),
+        };
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), update_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RoyaltyTooHigh {
+                max: Decimal::percent(10),
+            }
+        );
+
+        // lowering it is a valid override
+        let update_msg = ExecuteMsg::UpdateExtension {
+            token_id: "Enterprise".to_string(),
+            extension: Some(Metadata {
+                royalty_payment_address: Some("kirk".to_string()),
+                royalty_percentage: Some(5),
+                ..Metadata::default()
+            }),
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, update_msg).unwrap();
+        let res = query_royalties_info(deps.as_ref(), "Enterprise".to_string(), Uint128::new(100))
+            .unwrap();
+        assert_eq!(res.royalty_amount, Uint128::new(5));
+
+        // a collection can pick its own cap
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            max_royalty_share: Some(Decimal::percent(25)),
+            ..Default::default()
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            mint_with_royalty("Excelsior", 25),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn royalty_split_between_recipients() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            enforce_royalty_on_transfer: true,
+            ..Default::default()
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        // 6% to the shipyard and 4% to starfleet, the split overriding the single address
+        let token_id = "Defiant";
+        let mint_msg = MintMsg {
+            token_id: token_id.to_string(),
+            owner: "sisko".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("sisko".to_string()),
+                royalty_percentage: Some(10),
+                royalty_split: Some(vec![
+                    (Addr::unchecked("utopia"), Decimal::percent(6)),
+                    (Addr::unchecked("starfleet"), Decimal::percent(4)),
+                ]),
+                ..Metadata::default()
+            }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Mint(mint_msg)).unwrap();
+        assert!(check_royalties(deps.as_ref()).unwrap().royalty_payments);
+
+        let query_msg = QueryMsg::Extension {
+            msg: Cw2981QueryMsg::RoyaltyInfo {
+                token_id: token_id.to_string(),
+                sale_price: Uint128::new(250),
+            },
+        };
+        let res: RoyaltiesInfoResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            RoyaltiesInfoResponse {
+                address: String::new(),
+                royalty_amount: Uint128::new(25),
+                payments: vec![
+                    RoyaltyPayment {
+                        address: "utopia".to_string(),
+                        royalty_amount: Uint128::new(15),
+                    },
+                    RoyaltyPayment {
+                        address: "starfleet".to_string(),
+                        royalty_amount: Uint128::new(10),
+                    },
+                ],
+            }
+        );
+
+        // a paid transfer pays each recipient their share
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: "kira".to_string(),
+            token_id: token_id.to_string(),
+        };
+        let res = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sisko", &coins(250, "uatom")),
+            transfer_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "utopia".to_string(),
+                    amount: coins(15, "uatom"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "starfleet".to_string(),
+                    amount: coins(10, "uatom"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "sisko".to_string(),
+                    amount: coins(225, "uatom"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn royalty_split_over_cap_rejected() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            ..Default::default()
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let mint_with_split = |token_id: &str, split: Vec<(&str, u64)>| {
+            ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: "kirk".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    royalty_split: Some(
+                        split
+                            .into_iter()
+                            .map(|(addr, percent)| {
+                                (Addr::unchecked(addr), Decimal::percent(percent))
+                            })
+                            .collect(),
+                    ),
+                    ..Metadata::default()
+                }),
+                metadata_hash: None,
+                edition: None,
+                edition_of: None,
+            })
+        };
+
+        // each share is under the cap, but together they are over it
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            mint_with_split("Enterprise", vec![("kirk", 6), ("spock", 5)]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RoyaltyTooHigh {
+                max: Decimal::percent(10),
+            }
+        );
+
+        // even a collection without a meaningful cap can't give away more than the sale
+        let mut deps = mock_dependencies();
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            max_royalty_share: Some(Decimal::percent(200)),
+            ..Default::default()
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        let err = entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            mint_with_split("Enterprise", vec![("kirk", 60), ("spock", 50)]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RoyaltyTooHigh {
+                max: Decimal::one(),
+            }
+        );
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            mint_with_split("Enterprise", vec![("kirk", 60), ("spock", 40)]),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn supported_standards_include_cw2981() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            ..Default::default()
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info, init_msg).unwrap();
+
+        let query_msg = QueryMsg::SupportedStandards {};
+        let res: SupportedStandardsResponse =
+            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.standards.contains(&"cw721-1.0".to_string()));
+        assert!(res.standards.contains(&CW2981_STANDARD.to_string()));
+    }
+
+    // instantiates the collection and mints `token_ids` to janeway, each with a 4% royalty
+    fn setup_royalty_tokens(mut deps: DepsMut, token_ids: &[&str]) {
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+            ..Default::default()
+        };
+        entry::instantiate(deps.branch(), mock_env(), info.clone(), init_msg).unwrap();
+        for token_id in token_ids {
+            let mint_msg = ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: "janeway".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    royalty_payment_address: Some("janeway".to_string()),
+                    royalty_percentage: Some(4),
+                    ..Metadata::default()
+                }),
+                metadata_hash: None,
+                edition: None,
+                edition_of: None,
+            });
+            entry::execute(deps.branch(), mock_env(), info.clone(), mint_msg).unwrap();
+            assert!(ROYALTY_TOKENS.has(deps.storage, token_id));
+        }
+    }
+
+    #[test]
+    fn burn_all_owned_by_clears_royalty_index() {
+        let mut deps = mock_dependencies();
+        setup_royalty_tokens(deps.as_mut(), &["Voyager", "Defiant"]);
+
+        let burn_msg = ExecuteMsg::BurnAllOwnedBy {
+            owner: "janeway".to_string(),
+            limit: None,
+        };
+        entry::execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), burn_msg).unwrap();
+        assert!(!ROYALTY_TOKENS.has(&deps.storage, "Voyager"));
+        assert!(!ROYALTY_TOKENS.has(&deps.storage, "Defiant"));
+        assert!(!check_royalties(deps.as_ref()).unwrap().royalty_payments);
+    }
+
+    #[test]
+    fn admin_burn_clears_royalty_index() {
+        let mut deps = mock_dependencies();
+        setup_royalty_tokens(deps.as_mut(), &["Voyager", "Defiant"]);
+
+        let burn_msg = ExecuteMsg::AdminBurn {
+            token_id: "Voyager".to_string(),
+            reason: "decommissioned".to_string(),
+        };
+        entry::execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), burn_msg).unwrap();
+        assert!(!ROYALTY_TOKENS.has(&deps.storage, "Voyager"));
+        assert!(ROYALTY_TOKENS.has(&deps.storage, "Defiant"));
+    }
+
+    #[test]
+    fn royalty_exempt_tokens_leave_royalty_index() {
+        let mut deps = mock_dependencies();
+        setup_royalty_tokens(deps.as_mut(), &["Voyager"]);
+
+        let exempt_msg = |exempt| ExecuteMsg::SetRoyaltyExempt {
+            token_id: "Voyager".to_string(),
+            exempt,
+        };
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            exempt_msg(true),
+        )
+        .unwrap();
+        assert!(!ROYALTY_TOKENS.has(&deps.storage, "Voyager"));
+        assert!(!check_royalties(deps.as_ref()).unwrap().royalty_payments);
+
+        // lifting the exemption puts it back
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR, &[]),
+            exempt_msg(false),
+        )
+        .unwrap();
+        assert!(ROYALTY_TOKENS.has(&deps.storage, "Voyager"));
+    }
+
+    #[test]
+    fn mint_next_indexes_the_minted_id() {
+        let mut deps = mock_dependencies();
+        setup_royalty_tokens(deps.as_mut(), &["Voyager"]);
+
+        let mint_msg = ExecuteMsg::MintNext {
+            owner: "sisko".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                royalty_payment_address: Some("sisko".to_string()),
+                royalty_percentage: Some(5),
+                ..Metadata::default()
+            }),
+        };
+        let res =
+            entry::execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), mint_msg).unwrap();
+        assert!(res.attributes.contains(&attr("token_id", "2")));
+        assert!(ROYALTY_TOKENS.has(&deps.storage, "2"));
+    }
 }
@@ -1,5 +1,23 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{CustomMsg, Uint128};
+use cosmwasm_std::{CustomMsg, Decimal, Uint128};
+
+#[cw_serde]
+#[derive(Default)]
+pub struct InstantiateMsg {
+    /// Name of the NFT contract
+    pub name: String,
+    /// Symbol of the NFT contract
+    pub symbol: String,
+    /// The minter is the only one who can create new NFTs
+    pub minter: String,
+    /// If true, funds sent with a TransferNft are treated as a sale and the
+    /// royalty share is paid out to the token's royalty payment address
+    #[serde(default)]
+    pub enforce_royalty_on_transfer: bool,
+    /// The largest royalty share (e.g. 0.1 for 10%) a token may be minted or updated with.
+    /// Defaults to 0.1
+    pub max_royalty_share: Option<Decimal>,
+}
 
 #[cw_serde]
 pub enum Cw2981QueryMsg {
@@ -33,9 +51,19 @@ impl CustomMsg for Cw2981QueryMsg {}
 
 #[cw_serde]
 pub struct RoyaltiesInfoResponse {
+    /// The single royalty recipient, empty when the royalty is split
     pub address: String,
     // Note that this must be the same denom as that passed in to RoyaltyInfo
     // rounding up or down is at the discretion of the implementer
+    /// The total royalty owed across every recipient
+    pub royalty_amount: Uint128,
+    /// Everyone owed a royalty and how much, in the order they were set
+    pub payments: Vec<RoyaltyPayment>,
+}
+
+#[cw_serde]
+pub struct RoyaltyPayment {
+    pub address: String,
     pub royalty_amount: Uint128,
 }
 
@@ -1,8 +1,9 @@
-use crate::msg::{CheckRoyaltiesResponse, RoyaltiesInfoResponse};
+use crate::msg::{CheckRoyaltiesResponse, RoyaltiesInfoResponse, RoyaltyPayment};
+use crate::state::ROYALTY_TOKENS;
 use crate::Cw2981Contract;
-use cosmwasm_std::{Decimal, Deps, StdResult, Uint128};
+use cosmwasm_std::{Decimal, Deps, Order, StdResult, Uint128};
 
-/// NOTE: default behaviour here is to round down
+/// NOTE: default behaviour here is to round down, separately for each recipient of a split
 /// EIP2981 specifies that the rounding behaviour is at the discretion of the implementer
 pub fn query_royalties_info(
     deps: Deps,
@@ -11,37 +12,59 @@ pub fn query_royalties_info(
 ) -> StdResult<RoyaltiesInfoResponse> {
     let contract = Cw2981Contract::default();
     let token_info = contract.tokens.load(deps.storage, &token_id)?;
+    // promotional tokens carry no royalty, whatever their metadata says
+    if token_info.royalty_exempt {
+        return Ok(RoyaltiesInfoResponse {
+            address: String::new(),
+            royalty_amount: Uint128::zero(),
+            payments: vec![],
+        });
+    }
+    let metadata = token_info.extension.unwrap_or_default();
 
-    let royalty_percentage = match token_info.extension {
-        Some(ref ext) => match ext.royalty_percentage {
-            Some(percentage) => Decimal::percent(percentage),
-            None => Decimal::percent(0),
-        },
-        None => Decimal::percent(0),
-    };
+    if let Some(split) = metadata.royalty_split {
+        let payments: Vec<RoyaltyPayment> = split
+            .into_iter()
+            .map(|(address, share)| RoyaltyPayment {
+                address: address.into_string(),
+                royalty_amount: sale_price * share,
+            })
+            .collect();
+        return Ok(RoyaltiesInfoResponse {
+            address: String::new(),
+            royalty_amount: payments.iter().map(|payment| payment.royalty_amount).sum(),
+            payments,
+        });
+    }
+
+    let royalty_percentage = Decimal::percent(metadata.royalty_percentage.unwrap_or(0));
     let royalty_from_sale_price = sale_price * royalty_percentage;
+    let royalty_address = metadata.royalty_payment_address.unwrap_or_default();
 
-    let royalty_address = match token_info.extension {
-        Some(ext) => match ext.royalty_payment_address {
-            Some(addr) => addr,
-            None => String::from(""),
-        },
-        None => String::from(""),
+    // without an address there is nobody to pay
+    let payments = if royalty_address.is_empty() {
+        vec![]
+    } else {
+        vec![RoyaltyPayment {
+            address: royalty_address.clone(),
+            royalty_amount: royalty_from_sale_price,
+        }]
     };
-
     Ok(RoyaltiesInfoResponse {
         address: royalty_address,
         royalty_amount: royalty_from_sale_price,
+        payments,
     })
 }
 
-/// As our default implementation here specifies royalties at token level
-/// and not at contract level, it is therefore logically true that
-/// on sale, every token managed by this contract should be checked
-/// to see if royalties are owed, and to whom. If you are importing
-/// this logic, you may want a custom implementation here
-pub fn check_royalties(_deps: Deps) -> StdResult<CheckRoyaltiesResponse> {
-    Ok(CheckRoyaltiesResponse {
-        royalty_payments: true,
-    })
+/// Royalties are set per token, so this reports whether any token currently
+/// carries a royalty_percentage or royalty_split. Marketplaces can skip
+/// RoyaltyInfo lookups entirely while it is false. If you are importing this
+/// logic, you may want a custom implementation here
+pub fn check_royalties(deps: Deps) -> StdResult<CheckRoyaltiesResponse> {
+    let royalty_payments = ROYALTY_TOKENS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    Ok(CheckRoyaltiesResponse { royalty_payments })
 }
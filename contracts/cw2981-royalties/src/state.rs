@@ -0,0 +1,24 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Decimal, Empty};
+use cw_storage_plus::{Item, Map};
+
+#[cw_serde]
+pub struct Config {
+    /// When true, funds sent along with a TransferNft are split between
+    /// the token's royalty payment address and the previous owner
+    pub enforce_royalty_on_transfer: bool,
+    /// The largest royalty share a token may carry
+    #[serde(default = "default_max_royalty_share")]
+    pub max_royalty_share: Decimal,
+}
+
+/// 10%, which is also what configs saved before the cap existed fall back to
+pub fn default_max_royalty_share() -> Decimal {
+    Decimal::percent(10)
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Secondary index of the tokens that currently carry a royalty_percentage,
+/// so CheckRoyalties can answer without scanning every token
+pub const ROYALTY_TOKENS: Map<&str, Empty> = Map::new("royalty_tokens");
@@ -16,7 +16,7 @@ fn main() {
     create_dir_all(&out_dir).unwrap();
     remove_schemas(&out_dir).unwrap();
 
-    export_schema(&schema_for!(InstantiateMsg), &out_dir);
+    export_schema(&schema_for!(InstantiateMsg<Extension>), &out_dir);
     export_schema_with_title(
         &schema_for!(ExecuteMsg<Extension, Empty>),
         &out_dir,
@@ -1,16 +1,29 @@
 // All unit tests for the contract are here
 
 #![cfg(test)]
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_binary, to_binary, CosmosMsg, DepsMut, Empty, Response, WasmMsg};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockQuerier};
+use cosmwasm_std::{
+    attr, coin, coins, from_binary, from_slice, to_binary, to_vec, Addr, BankMsg, Binary, Coin,
+    ContractResult, CosmosMsg, Deps, DepsMut, Empty, Order, Reply, ReplyOn, Response, StdError,
+    Storage, SubMsg, SubMsgResponse, SubMsgResult, SystemError, SystemResult, Uint128, WasmMsg,
+    WasmQuery,
+};
 
 use cw721::{
-    Approval, ApprovalResponse, ContractInfoResponse, Cw721Query, Cw721ReceiveMsg, Expiration,
-    NftInfoResponse, OperatorsResponse, OwnerOfResponse,
+    Approval, ApprovalResponse, ContractInfoResponse, Cw721ExecuteMsg, Cw721Query, Cw721ReceiveMsg,
+    Cw721ReceiveWithOwnerMsg, Expiration, NftInfoResponse, OperatorsResponse, OwnerOfResponse,
 };
 
+use crate::execute::{CONTRACT_NAME as CW2_NAME, CONTRACT_VERSION as CW2_VERSION};
+use crate::state::{BurnPolicy, HistoryEntry, MintPriceCurve, Phase, TokenInfo};
 use crate::{
-    ContractError, Cw721Contract, ExecuteMsg, Extension, InstantiateMsg, MintMsg, QueryMsg,
+    safe_send_reply_id, BalanceResponse, BurnedTotalResponse, CanTransferResponse,
+    CollectionInfoResponse, ConfigResponse, ContractError, CurrentMintPriceResponse, Cw721Contract,
+    ExecuteMsg, ExistsResponse, Extension, InstantiateMsg, IsOperatorResponse, MigrateMsg,
+    MintAuthorization, MintMsg, MintPacket, MintedTotalResponse, MintersResponse,
+    NextTokenIdResponse, OwnersResponse, PrefixedKeys, QueryMsg, RedeemMsg, ReplyId,
+    SimulateSendResponse, StatsResponse, StatusResponse, SupportedStandardsResponse,
+    TokenApprovalCountResponse, TokenTagsResponse, TransferHookMsg, TransferPermit,
 };
 
 const MINTER: &str = "merlin";
@@ -23,6 +36,7 @@ fn setup_contract(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty,
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: String::from(MINTER),
+        ..Default::default()
     };
     let info = mock_info("creator", &[]);
     let res = contract.instantiate(deps, mock_env(), info, msg).unwrap();
@@ -30,6 +44,55 @@ fn setup_contract(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty,
     contract
 }
 
+// makes the mock querier treat these addresses as contracts, and everything else as accounts
+fn mock_contracts(querier: &mut MockQuerier, contracts: &'static [&'static str]) {
+    querier.update_wasm(move |query| match query {
+        WasmQuery::ContractInfo { contract_addr }
+            if contracts.contains(&contract_addr.as_str()) =>
+        {
+            let info = cosmwasm_std::ContractInfoResponse::new(1, "factory");
+            SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "not a contract".to_string(),
+        }),
+    });
+}
+
+// storage as a deployment from before migrate existed left it: a single minter under "minter",
+// no owner, no counters besides num_tokens, and each (token_id, owner) in `tokens` saved in the
+// old format
+fn legacy_deployment(
+    storage: &mut dyn Storage,
+    tokens: &[(&str, &str)],
+) -> Cw721Contract<'static, Extension, Empty, Empty, Empty> {
+    let contract = Cw721Contract::default();
+    contract
+        .contract_info
+        .save(
+            storage,
+            &ContractInfoResponse {
+                name: CONTRACT_NAME.to_string(),
+                symbol: SYMBOL.to_string(),
+            },
+        )
+        .unwrap();
+    storage.set(b"minter", br#""merlin""#);
+    for (token_id, owner) in tokens {
+        let raw = format!(
+            r#"{{"owner":"{}","approvals":[],"token_uri":null,"extension":null}}"#,
+            owner
+        );
+        let token: TokenInfo<Extension> = from_slice(raw.as_bytes()).unwrap();
+        contract.tokens.save(storage, token_id, &token).unwrap();
+    }
+    contract
+        .token_count
+        .save(storage, &(tokens.len() as u64))
+        .unwrap();
+    contract
+}
+
 #[test]
 fn proper_instantiation() {
     let mut deps = mock_dependencies();
@@ -39,6 +102,7 @@ fn proper_instantiation() {
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: String::from(MINTER),
+        ..Default::default()
     };
     let info = mock_info("creator", &[]);
 
@@ -81,6 +145,9 @@ fn minting() {
         owner: String::from("medusa"),
         token_uri: Some(token_uri.clone()),
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
 
     // random cannot mint
@@ -88,7 +155,7 @@ fn minting() {
     let err = contract
         .execute(deps.as_mut(), mock_env(), random, mint_msg.clone())
         .unwrap_err();
-    assert_eq!(err, ContractError::Unauthorized {});
+    assert_eq!(err, ContractError::NotMinter {});
 
     // minter can mint
     let allowed = mock_info(MINTER, &[]);
@@ -111,7 +178,11 @@ fn minting() {
         info,
         NftInfoResponse::<Extension> {
             token_uri: Some(token_uri),
+            mint_block: Some(mock_env().block.height),
             extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
         }
     );
 
@@ -133,6 +204,9 @@ fn minting() {
         owner: String::from("hercules"),
         token_uri: None,
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
 
     let allowed = mock_info(MINTER, &[]);
@@ -160,6 +234,9 @@ fn burning() {
         owner: MINTER.to_string(),
         token_uri: Some(token_uri),
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
 
     let burn_msg = ExecuteMsg::Burn { token_id };
@@ -176,7 +253,7 @@ fn burning() {
         .execute(deps.as_mut(), mock_env(), random, burn_msg.clone())
         .unwrap_err();
 
-    assert_eq!(err, ContractError::Unauthorized {});
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
 
     let _ = contract
         .execute(deps.as_mut(), mock_env(), allowed, burn_msg)
@@ -210,6 +287,9 @@ fn transferring_nft() {
         owner: String::from("venus"),
         token_uri: Some(token_uri),
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
 
     let minter = mock_info(MINTER, &[]);
@@ -227,7 +307,7 @@ fn transferring_nft() {
     let err = contract
         .execute(deps.as_mut(), mock_env(), random, transfer_msg)
         .unwrap_err();
-    assert_eq!(err, ContractError::Unauthorized {});
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
 
     // owner can
     let random = mock_info("venus", &[]);
@@ -245,6 +325,7 @@ fn transferring_nft() {
         Response::new()
             .add_attribute("action", "transfer_nft")
             .add_attribute("sender", "venus")
+            .add_attribute("previous_owner", "venus")
             .add_attribute("recipient", "random")
             .add_attribute("token_id", token_id)
     );
@@ -264,6 +345,9 @@ fn sending_nft() {
         owner: String::from("venus"),
         token_uri: Some(token_uri),
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
 
     let minter = mock_info(MINTER, &[]);
@@ -277,13 +361,16 @@ fn sending_nft() {
         contract: target.clone(),
         token_id: token_id.clone(),
         msg: msg.clone(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
     };
 
     let random = mock_info("random", &[]);
     let err = contract
         .execute(deps.as_mut(), mock_env(), random, send_msg.clone())
         .unwrap_err();
-    assert_eq!(err, ContractError::Unauthorized {});
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
 
     // but owner can
     let random = mock_info("venus", &[]);
@@ -308,9 +395,10 @@ fn sending_nft() {
     assert_eq!(
         res,
         Response::new()
-            .add_message(expected)
+            .add_submessage(SubMsg::reply_always(expected, ReplyId::SendNft.id()))
             .add_attribute("action", "send_nft")
             .add_attribute("sender", "venus")
+            .add_attribute("previous_owner", "venus")
             .add_attribute("recipient", "another_contract")
             .add_attribute("token_id", token_id)
     );
@@ -330,6 +418,9 @@ fn approving_revoking() {
         owner: String::from("demeter"),
         token_uri: Some(token_uri),
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
 
     let minter = mock_info(MINTER, &[]);
@@ -374,6 +465,7 @@ fn approving_revoking() {
             .add_attribute("sender", "demeter")
             .add_attribute("spender", "random")
             .add_attribute("token_id", token_id.clone())
+            .add_attribute("approvals", "random:never")
     );
 
     // test approval query
@@ -477,6 +569,9 @@ fn approving_all_revoking_all() {
         owner: String::from("demeter"),
         token_uri: Some(token_uri1),
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
 
     let minter = mock_info(MINTER, &[]);
@@ -489,6 +584,9 @@ fn approving_all_revoking_all() {
         owner: String::from("demeter"),
         token_uri: Some(token_uri2),
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
 
     contract
@@ -509,6 +607,7 @@ fn approving_all_revoking_all() {
     let approve_all_msg = ExecuteMsg::ApproveAll {
         operator: String::from("random"),
         expires: None,
+        min_token_height: None,
     };
     let owner = mock_info("demeter", &[]);
     let res = contract
@@ -544,6 +643,9 @@ fn approving_all_revoking_all() {
         contract: String::from("another_contract"),
         token_id: token_id2,
         msg: to_binary(&msg).unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
     };
     contract
         .execute(deps.as_mut(), mock_env(), random, send_msg)
@@ -553,6 +655,7 @@ fn approving_all_revoking_all() {
     let approve_all_msg = ExecuteMsg::ApproveAll {
         operator: String::from("operator"),
         expires: None,
+        min_token_height: None,
     };
     // person is now the owner of the tokens
     let owner = mock_info("person", &[]);
@@ -585,6 +688,7 @@ fn approving_all_revoking_all() {
     let approve_all_msg = ExecuteMsg::ApproveAll {
         operator: String::from("buddy"),
         expires: Some(buddy_expires),
+        min_token_height: None,
     };
     let owner = mock_info("person", &[]);
     contract
@@ -693,6 +797,9 @@ fn query_tokens_by_owner() {
         owner: demeter.clone(),
         token_uri: None,
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
     contract
         .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
@@ -703,6 +810,9 @@ fn query_tokens_by_owner() {
         owner: ceres.clone(),
         token_uri: None,
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
     contract
         .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
@@ -713,6 +823,9 @@ fn query_tokens_by_owner() {
         owner: demeter.clone(),
         token_uri: None,
         extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
     contract
         .execute(deps.as_mut(), mock_env(), minter, mint_msg)
@@ -751,3 +864,8233 @@ fn query_tokens_by_owner() {
         .unwrap();
     assert_eq!(&by_demeter[1..], &tokens.tokens[..]);
 }
+
+#[test]
+fn mint_records_block_height() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let token_id = "timed".to_string();
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: token_id.clone(),
+        owner: String::from("chronos"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+
+    // mint at a height different from the default mock env
+    let mut env = mock_env();
+    env.block.height = 54321;
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(deps.as_mut(), env, minter, mint_msg)
+        .unwrap();
+
+    // the recorded mint height matches the env used
+    let info = contract.nft_info(deps.as_ref(), token_id.clone()).unwrap();
+    assert_eq!(Some(54321), info.mint_block);
+
+    // and is also part of all_nft_info
+    let info = contract
+        .all_nft_info(deps.as_ref(), mock_env(), token_id, false)
+        .unwrap();
+    assert_eq!(Some(54321), info.info.mint_block);
+}
+
+#[test]
+fn transfer_updates_last_transfer_height() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let token_id = "active".to_string();
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: token_id.clone(),
+        owner: String::from("hermes"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+
+    // initialized to the mint height
+    let info = contract
+        .all_nft_info(deps.as_ref(), mock_env(), token_id.clone(), false)
+        .unwrap();
+    assert_eq!(Some(mock_env().block.height), info.last_transfer);
+
+    // approving does not count as activity
+    let mut env = mock_env();
+    env.block.height += 10;
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("random"),
+        token_id: token_id.clone(),
+        expires: None,
+    };
+    let owner = mock_info("hermes", &[]);
+    contract
+        .execute(deps.as_mut(), env.clone(), owner.clone(), approve_msg)
+        .unwrap();
+    let info = contract
+        .all_nft_info(deps.as_ref(), env.clone(), token_id.clone(), false)
+        .unwrap();
+    assert_eq!(Some(mock_env().block.height), info.last_transfer);
+
+    // transferring updates it
+    env.block.height += 10;
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("apollo"),
+        token_id: token_id.clone(),
+    };
+    contract
+        .execute(deps.as_mut(), env.clone(), owner, transfer_msg)
+        .unwrap();
+    let info = contract
+        .all_nft_info(deps.as_ref(), env.clone(), token_id, false)
+        .unwrap();
+    assert_eq!(Some(env.block.height), info.last_transfer);
+    // mint height is untouched
+    assert_eq!(Some(mock_env().block.height), info.info.mint_block);
+}
+
+#[test]
+fn approving_self_is_rejected() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let token_id = "mirror".to_string();
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: token_id.clone(),
+        owner: String::from("narcissus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+
+    // owner cannot approve themselves on a token
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("narcissus"),
+        token_id: token_id.clone(),
+        expires: None,
+    };
+    let owner = mock_info("narcissus", &[]);
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::ApprovalToSelf {});
+
+    // nor register themselves as their own operator
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("narcissus"),
+        expires: None,
+        min_token_height: None,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner, approve_all_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::ApprovalToSelf {});
+
+    // nothing was stored
+    let res = contract
+        .approvals(deps.as_ref(), mock_env(), token_id, true)
+        .unwrap();
+    assert!(res.approvals.is_empty());
+    let res = contract
+        .operators(
+            deps.as_ref(),
+            mock_env(),
+            String::from("narcissus"),
+            true,
+            None,
+            None,
+        )
+        .unwrap();
+    assert!(res.operators.is_empty());
+}
+
+#[test]
+fn transferring_to_self_is_rejected() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let token_id = "boomerang".to_string();
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: token_id.clone(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("venus"),
+        token_id: token_id.clone(),
+    };
+
+    // permission is checked first
+    let random = mock_info("random", &[]);
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), random, transfer_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
+
+    // the owner gets the self-transfer error
+    let owner = mock_info("venus", &[]);
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner, transfer_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::CannotTransferToSelf {});
+
+    // same for send
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("venus"),
+        token_id,
+        msg: to_binary("loop").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    let owner = mock_info("venus", &[]);
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner, send_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::CannotTransferToSelf {});
+}
+
+#[test]
+fn operations_on_missing_token() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let sender = mock_info("venus", &[]);
+    let expected = ContractError::TokenNotFound {
+        token_id: String::from("ghost"),
+    };
+
+    let msgs = vec![
+        ExecuteMsg::TransferNft {
+            recipient: String::from("random"),
+            token_id: String::from("ghost"),
+        },
+        ExecuteMsg::SendNft {
+            contract: String::from("another_contract"),
+            token_id: String::from("ghost"),
+            msg: to_binary("boo").unwrap(),
+            safe_send: false,
+            wrap: true,
+            with_owner: false,
+        },
+        ExecuteMsg::Approve {
+            spender: String::from("random"),
+            token_id: String::from("ghost"),
+            expires: None,
+        },
+        ExecuteMsg::Revoke {
+            spender: String::from("random"),
+            token_id: String::from("ghost"),
+        },
+        ExecuteMsg::Burn {
+            token_id: String::from("ghost"),
+        },
+    ];
+    for msg in msgs {
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), sender.clone(), msg)
+            .unwrap_err();
+        assert_eq!(err, expected);
+    }
+    // the message names the missing token
+    assert_eq!(expected.to_string(), "Token not found: ghost");
+}
+
+#[test]
+fn sending_nft_with_funds() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let token_id = "consign".to_string();
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: token_id.clone(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+
+    let msg = to_binary("list it").unwrap();
+    let target = String::from("auction_contract");
+    let send_msg = ExecuteMsg::SendNftWithFunds {
+        contract: target.clone(),
+        token_id: token_id.clone(),
+        msg: msg.clone(),
+    };
+    let funds = coins(500, "ustars");
+    let owner = mock_info("venus", &funds);
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), owner, send_msg)
+        .unwrap();
+
+    // the attached funds are forwarded to the receiver
+    let payload = Cw721ReceiveMsg {
+        sender: String::from("venus"),
+        token_id: token_id.clone(),
+        msg,
+    };
+    let expected = payload
+        .clone()
+        .into_cosmos_msg_with_funds(target.clone(), funds.clone())
+        .unwrap();
+    match &expected {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            funds: sent,
+            ..
+        }) => {
+            assert_eq!(contract_addr, &target);
+            assert_eq!(sent, &funds);
+        }
+        m => panic!("Unexpected message type: {:?}", m),
+    }
+    assert_eq!(
+        res,
+        Response::new()
+            .add_submessage(SubMsg::reply_always(expected, ReplyId::SendNft.id()))
+            .add_attribute("action", "send_nft_with_funds")
+            .add_attribute("sender", "venus")
+            .add_attribute("recipient", "auction_contract")
+            .add_attribute("token_id", token_id.clone())
+            .add_attribute("previous_owner", "venus")
+    );
+
+    // the plain path still sends no funds
+    let plain: CosmosMsg = payload.into_cosmos_msg(target).unwrap();
+    match plain {
+        CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => assert!(funds.is_empty()),
+        m => panic!("Unexpected message type: {:?}", m),
+    }
+
+    // and the token changed hands
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), token_id, false)
+        .unwrap();
+    assert_eq!("auction_contract", owner.owner);
+}
+
+#[test]
+fn sending_nft_reports_previous_owner() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let token_id = "escrowed".to_string();
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: token_id.clone(),
+        owner: String::from("demeter"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+
+    // demeter makes random her operator
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("random"),
+        expires: None,
+        min_token_height: None,
+    };
+    let owner = mock_info("demeter", &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), owner, approve_all_msg)
+        .unwrap();
+
+    // the operator sends demeter's token to an escrow contract
+    let msg = to_binary("hold this").unwrap();
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("escrow"),
+        token_id: token_id.clone(),
+        msg: msg.clone(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    let operator = mock_info("random", &[]);
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), operator, send_msg)
+        .unwrap();
+
+    // the receiver gets the standard message, with the caller as sender
+    let payload = Cw721ReceiveMsg {
+        sender: String::from("random"),
+        token_id,
+        msg: msg.clone(),
+    };
+    let expected: CosmosMsg = payload.into_cosmos_msg("escrow").unwrap();
+    assert_eq!(res.messages[0].msg, expected);
+    // while the real previous owner is in the response, for indexers and escrow frontends
+    assert!(res.attributes.contains(&attr("previous_owner", "demeter")));
+
+    // a receiver that needs the owner asks for the extended message
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "deposit".to_string(),
+        owner: String::from("demeter"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("escrow"),
+        token_id: "deposit".to_string(),
+        msg: msg.clone(),
+        safe_send: false,
+        wrap: true,
+        with_owner: true,
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            send_msg,
+        )
+        .unwrap();
+    let payload = Cw721ReceiveWithOwnerMsg {
+        sender: String::from("random"),
+        token_id: "deposit".to_string(),
+        msg,
+        previous_owner: String::from("demeter"),
+    };
+    let expected: CosmosMsg = payload.into_cosmos_msg("escrow").unwrap();
+    assert_eq!(res.messages[0].msg, expected);
+    // which is what a receiver's ReceiveNftWithOwner variant parses
+    match expected {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => assert_eq!(
+            msg,
+            Binary::from(
+                br#"{"receive_nft_with_owner":{"sender":"random","token_id":"deposit","msg":"ImhvbGQgdGhpcyI=","previous_owner":"demeter"}}"#
+                    .as_slice()
+            )
+        ),
+        m => panic!("Unexpected message type: {:?}", m),
+    }
+}
+
+#[test]
+fn revoking_batch() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MINTER, &[]);
+    let owner = mock_info("demeter", &[]);
+
+    let token_ids = vec!["wheat".to_string(), "barley".to_string(), "rye".to_string()];
+    for token_id in &token_ids {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.clone(),
+            owner: String::from("demeter"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    // random is approved on the first two tokens only, buddy on the first
+    for (spender, token_id) in [
+        ("random", "wheat"),
+        ("random", "barley"),
+        ("buddy", "wheat"),
+    ] {
+        let approve_msg = ExecuteMsg::Approve {
+            spender: String::from(spender),
+            token_id: String::from(token_id),
+            expires: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+            .unwrap();
+    }
+
+    // someone else cannot revoke on demeter's tokens
+    let revoke_msg = ExecuteMsg::RevokeBatch {
+        token_ids: token_ids.clone(),
+        spender: String::from("random"),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            revoke_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
+
+    // the owner revokes in one go, rye is skipped
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), owner, revoke_msg)
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "revoke_batch")
+            .add_attribute("sender", "demeter")
+            .add_attribute("spender", "random")
+            .add_attribute("revoked_count", "2")
+            .add_attribute("token_id", "wheat")
+            .add_attribute("approvals", "buddy:never")
+            .add_attribute("token_id", "barley")
+            .add_attribute("approvals", "")
+    );
+
+    // random's approvals are gone, buddy's is untouched
+    let res = contract
+        .approvals(deps.as_ref(), mock_env(), String::from("wheat"), true)
+        .unwrap();
+    assert_eq!(
+        res.approvals,
+        vec![Approval {
+            spender: String::from("buddy"),
+            expires: Expiration::Never {}
+        }]
+    );
+    let res = contract
+        .approvals(deps.as_ref(), mock_env(), String::from("barley"), true)
+        .unwrap();
+    assert!(res.approvals.is_empty());
+}
+
+#[test]
+fn approving_batch() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MINTER, &[]);
+    let owner = mock_info("demeter", &[]);
+
+    for (token_id, token_owner) in [
+        ("wheat", "demeter"),
+        ("barley", "demeter"),
+        ("rye", "hades"),
+    ] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: token_owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    // the owner lists both tokens at once
+    let approve_msg = ExecuteMsg::ApproveBatch {
+        spender: String::from("market"),
+        token_ids: vec!["wheat".to_string(), "barley".to_string()],
+        expires: None,
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "approve_batch")
+            .add_attribute("sender", "demeter")
+            .add_attribute("spender", "market")
+            .add_attribute("approved_count", "2")
+            .add_attribute("token_id", "wheat")
+            .add_attribute("approvals", "market:never")
+            .add_attribute("token_id", "barley")
+            .add_attribute("approvals", "market:never")
+    );
+    for token_id in ["wheat", "barley"] {
+        let res = contract
+            .approval(
+                deps.as_ref(),
+                mock_env(),
+                token_id.to_string(),
+                String::from("market"),
+                false,
+            )
+            .unwrap();
+        assert_eq!(res.approval.expires, Expiration::Never {});
+    }
+
+    // with someone else's token in the batch, the whole batch fails
+    let approve_msg = ExecuteMsg::ApproveBatch {
+        spender: String::from("broker"),
+        token_ids: vec!["wheat".to_string(), "rye".to_string()],
+        expires: None,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner, approve_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
+}
+
+#[test]
+fn scoped_operator_only_covers_newer_tokens() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // mint one token early and one late, both to the same owner
+    let minter = mock_info(MINTER, &[]);
+    for (token_id, height) in [("vintage", 100), ("fresh", 200)] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("collector"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        let mut env = mock_env();
+        env.block.height = height;
+        contract
+            .execute(deps.as_mut(), env, minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    // collector grants an operator scoped to tokens minted from height 150
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("curator"),
+        expires: None,
+        min_token_height: Some(150),
+    };
+    let owner = mock_info("collector", &[]);
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), owner, approve_all_msg)
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "approve_all")
+            .add_attribute("sender", "collector")
+            .add_attribute("operator", "curator")
+            .add_attribute("min_token_height", "150")
+    );
+
+    // the operator cannot touch the older token
+    let curator = mock_info("curator", &[]);
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("gallery"),
+        token_id: String::from("vintage"),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), curator.clone(), transfer_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
+
+    // nor approve someone else for it
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("gallery"),
+        token_id: String::from("vintage"),
+        expires: None,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), curator.clone(), approve_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
+
+    // but can move the newer one
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("gallery"),
+        token_id: String::from("fresh"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), curator, transfer_msg)
+        .unwrap();
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "fresh".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "gallery");
+}
+
+#[test]
+fn query_token_exists() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "1".to_string(),
+        owner: String::from("medusa"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+
+    // a minted id exists
+    let res: ExistsResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Exists {
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(res.exists);
+
+    // the next sequential id is still free
+    let res = contract.exists(deps.as_ref(), "2".to_string()).unwrap();
+    assert!(!res.exists);
+}
+
+#[test]
+fn balance_tracks_ownership_changes() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let balance = |deps: cosmwasm_std::Deps, owner: &str| {
+        contract.balance_of(deps, owner.to_string()).unwrap().count
+    };
+
+    // nobody holds anything yet
+    assert_eq!(0, balance(deps.as_ref(), "demeter"));
+
+    let minter = mock_info(MINTER, &[]);
+    for token_id in ["wheat", "barley"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("demeter"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+    assert_eq!(2, balance(deps.as_ref(), "demeter"));
+
+    // transfers move balance out of one account and into another
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("persephone"),
+        token_id: String::from("wheat"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("demeter", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(1, balance(deps.as_ref(), "demeter"));
+    assert_eq!(1, balance(deps.as_ref(), "persephone"));
+
+    // burning reduces the holder's balance
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("wheat"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("persephone", &[]),
+            burn_msg,
+        )
+        .unwrap();
+    assert_eq!(0, balance(deps.as_ref(), "persephone"));
+
+    // also available through the query entry point
+    let res: BalanceResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::BalanceOf {
+                    owner: String::from("demeter"),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(1, res.count);
+}
+
+#[test]
+fn sending_respects_receiver_allowlist() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let minter = mock_info(MINTER, &[]);
+    for token_id in ["first", "second"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("odysseus"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    // only the owner (the instantiating account) manages the allowlist
+    let allow_msg = ExecuteMsg::SetReceiverAllowed {
+        receiver: String::from("ithaca"),
+        allowed: true,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), minter, allow_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            allow_msg,
+        )
+        .unwrap();
+
+    // receivers missing from a non-empty allowlist are refused
+    let sender = mock_info("odysseus", &[]);
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("troy"),
+        token_id: String::from("first"),
+        msg: to_binary("voyage").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), sender.clone(), send_msg)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ReceiverNotAllowed {
+            receiver: String::from("troy")
+        }
+    );
+
+    // listed receivers are fine
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("ithaca"),
+        token_id: String::from("first"),
+        msg: to_binary("voyage").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), sender.clone(), send_msg)
+        .unwrap();
+
+    // removing the last entry allows any receiver again
+    let disallow_msg = ExecuteMsg::SetReceiverAllowed {
+        receiver: String::from("ithaca"),
+        allowed: false,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            disallow_msg,
+        )
+        .unwrap();
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("troy"),
+        token_id: String::from("second"),
+        msg: to_binary("voyage").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), sender, send_msg)
+        .unwrap();
+}
+
+#[test]
+fn operators_cannot_grant_operators_for_owner() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "crown".to_string(),
+        owner: String::from("zeus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // zeus makes hermes an operator
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("hermes"),
+        expires: None,
+        min_token_height: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("zeus", &[]),
+            approve_all_msg,
+        )
+        .unwrap();
+
+    // hermes tries to pass that on to loki; the grant is recorded against hermes' own account
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("loki"),
+        expires: None,
+        min_token_height: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hermes", &[]),
+            approve_all_msg,
+        )
+        .unwrap();
+    let res = contract
+        .operators(
+            deps.as_ref(),
+            mock_env(),
+            String::from("zeus"),
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        res.operators,
+        vec![cw721::Approval {
+            spender: String::from("hermes"),
+            expires: Expiration::Never {}
+        }]
+    );
+
+    // so loki gains nothing over zeus' tokens
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("loki"),
+        token_id: String::from("crown"),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("loki", &[]),
+            transfer_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
+}
+
+#[test]
+fn minted_total_survives_burns() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let minter = mock_info(MINTER, &[]);
+    for token_id in ["phoenix", "ember"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("hestia"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("ember"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hestia", &[]),
+            burn_msg,
+        )
+        .unwrap();
+
+    // current supply drops, but the total ever minted does not
+    let count = contract.num_tokens(deps.as_ref()).unwrap();
+    assert_eq!(1, count.count);
+    let res: MintedTotalResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::MintedTotal {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(2, res.count);
+}
+
+#[test]
+fn minting_next_token_id() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let minter = mock_info(MINTER, &[]);
+    let mint_next_msg = ExecuteMsg::MintNext {
+        owner: String::from("athena"),
+        token_uri: None,
+        extension: None,
+    };
+
+    // only the minter can mint
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("athena", &[]),
+            mint_next_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+
+    // ids are handed out in sequence and reported back
+    for expected in ["1", "2"] {
+        let res = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                minter.clone(),
+                mint_next_msg.clone(),
+            )
+            .unwrap();
+        let token_id = res
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "token_id")
+            .unwrap();
+        assert_eq!(expected, token_id.value);
+    }
+    let tokens = contract.all_tokens(deps.as_ref(), None, None).unwrap();
+    assert_eq!(vec!["1", "2"], tokens.tokens);
+
+    // a manually minted token can take the next id, which is then rejected rather than overwritten
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "4".to_string(),
+        owner: String::from("ares"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+        .unwrap();
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_next_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::Claimed {});
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "4".to_string(), false)
+        .unwrap();
+    assert_eq!("ares", owner.owner);
+}
+
+#[test]
+fn query_tokens_approved_to_spender() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let minter = mock_info(MINTER, &[]);
+    for token_id in ["amphora", "lyre", "trident"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("poseidon"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    let owner = mock_info("poseidon", &[]);
+    for token_id in ["amphora", "lyre", "trident"] {
+        let approve_msg = ExecuteMsg::Approve {
+            spender: String::from("agora"),
+            token_id: token_id.to_string(),
+            expires: None,
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+            .unwrap();
+    }
+    let approved_to = |deps: cosmwasm_std::Deps, start_after: Option<String>, limit| {
+        contract
+            .tokens_approved_to(deps, mock_env(), String::from("agora"), start_after, limit)
+            .unwrap()
+            .tokens
+    };
+    assert_eq!(
+        vec!["amphora", "lyre", "trident"],
+        approved_to(deps.as_ref(), None, None)
+    );
+    // paginates like the other token queries
+    assert_eq!(vec!["amphora"], approved_to(deps.as_ref(), None, Some(1)));
+    assert_eq!(
+        vec!["lyre", "trident"],
+        approved_to(deps.as_ref(), Some("amphora".to_string()), None)
+    );
+
+    // revoked, transferred and burned tokens drop out
+    let revoke_msg = ExecuteMsg::Revoke {
+        spender: String::from("agora"),
+        token_id: String::from("amphora"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), revoke_msg)
+        .unwrap();
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("triton"),
+        token_id: String::from("lyre"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), transfer_msg)
+        .unwrap();
+    assert_eq!(vec!["trident"], approved_to(deps.as_ref(), None, None));
+
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("trident"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner, burn_msg)
+        .unwrap();
+    assert!(approved_to(deps.as_ref(), None, None).is_empty());
+}
+
+#[test]
+fn burning_follows_burn_policy() {
+    let setup = |deps: DepsMut, burn_policy| {
+        let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            minter: String::from(MINTER),
+            burn_policy,
+            ..Default::default()
+        };
+        contract
+            .instantiate(deps, mock_env(), mock_info("creator", &[]), msg)
+            .unwrap();
+        contract
+    };
+    let mint = |contract: &Cw721Contract<Extension, Empty, Empty, Empty>, deps: DepsMut| {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: "relic".to_string(),
+            owner: String::from("hades"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps, mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    };
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: "relic".to_string(),
+    };
+
+    // by default the owner may burn
+    let mut deps = mock_dependencies();
+    let contract = setup(deps.as_mut(), None);
+    mint(&contract, deps.as_mut());
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hades", &[]),
+            burn_msg.clone(),
+        )
+        .unwrap();
+
+    // minter-only collections refuse the owner but accept the minter
+    let mut deps = mock_dependencies();
+    let contract = setup(deps.as_mut(), Some(BurnPolicy::MinterOnly));
+    mint(&contract, deps.as_mut());
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hades", &[]),
+            burn_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            burn_msg.clone(),
+        )
+        .unwrap();
+
+    // and nobody can burn when it is disabled
+    let mut deps = mock_dependencies();
+    let contract = setup(deps.as_mut(), Some(BurnPolicy::Disabled));
+    mint(&contract, deps.as_mut());
+    for sender in ["hades", MINTER] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                burn_msg.clone(),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::BurnNotAllowed {});
+    }
+    let count = contract.num_tokens(deps.as_ref()).unwrap();
+    assert_eq!(1, count.count);
+}
+
+#[test]
+fn approvals_default_to_configured_duration() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        default_approval_duration: Some(100),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let minter = mock_info(MINTER, &[]);
+    for token_id in ["short", "long"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("cronus"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+            .unwrap();
+    }
+
+    // omitting expires gives a grant that ends 100 blocks from now
+    let owner = mock_info("cronus", &[]);
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("rhea"),
+        token_id: String::from("short"),
+        expires: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+        .unwrap();
+    let res = contract
+        .approval(
+            deps.as_ref(),
+            mock_env(),
+            String::from("short"),
+            String::from("rhea"),
+            true,
+        )
+        .unwrap();
+    let expected = Expiration::AtHeight(mock_env().block.height + 100);
+    assert_eq!(res.approval.expires, expected);
+
+    // the same goes for operators
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("gaia"),
+        expires: None,
+        min_token_height: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_all_msg)
+        .unwrap();
+    let res = contract
+        .operators(
+            deps.as_ref(),
+            mock_env(),
+            String::from("cronus"),
+            true,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(res.operators[0].expires, expected);
+
+    // but an explicit Never is still honoured
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("rhea"),
+        token_id: String::from("long"),
+        expires: Some(Expiration::Never {}),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner, approve_msg)
+        .unwrap();
+
+    // once the default window has passed, only the permanent grant still works
+    let mut later = mock_env();
+    later.block.height += 100;
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("rhea"),
+        token_id: String::from("short"),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            later.clone(),
+            mock_info("rhea", &[]),
+            transfer_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::ApprovalExpired {});
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("rhea"),
+        token_id: String::from("long"),
+    };
+    contract
+        .execute(deps.as_mut(), later, mock_info("rhea", &[]), transfer_msg)
+        .unwrap();
+}
+
+#[test]
+fn query_config() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        owner: Some(String::from("olympus")),
+        burn_policy: Some(BurnPolicy::MinterOnly),
+        default_approval_duration: Some(50),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    // the minter appoints a metadata updater
+    let set_msg = ExecuteMsg::SetMetadataUpdater {
+        address: Some(String::from("muse")),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), set_msg)
+        .unwrap();
+
+    let res: ConfigResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::Config {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        ConfigResponse {
+            minters: vec![String::from(MINTER)],
+            owner: String::from("olympus"),
+            metadata_updater: Some(String::from("muse")),
+            burn_policy: BurnPolicy::MinterOnly,
+            default_approval_duration: Some(50),
+            reject_contract_owners: false,
+            transfer_cooldown_blocks: None,
+            mint_prices: vec![],
+            allowlist_mint_prices: vec![],
+            phase: Phase::MinterOnly,
+            transfer_fee: None,
+            max_token_id_length: 128,
+            reserved_prefix: None,
+            redemption_contract: None,
+            minimal_events: false,
+            signer_pubkey: None,
+            transfers_enabled: true,
+            send_enabled: true,
+            keep_approvals_on_send: false,
+            max_supply: None,
+            burns_free_supply: false,
+            allowlist_free_mints: None,
+            max_per_owner: None,
+            mint_price_curve: None,
+            track_history: false,
+            approval_delay_blocks: 0,
+            operators_enabled: true,
+            min_deposit: None,
+        }
+    );
+}
+
+#[test]
+fn owner_can_set_metadata_updater() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let set_msg = |address: &str| ExecuteMsg::SetMetadataUpdater {
+        address: Some(address.to_string()),
+    };
+
+    // the collection owner isn't a minter, but manages the updater too
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            set_msg("muse"),
+        )
+        .unwrap();
+    assert_eq!(
+        contract.metadata_updater.load(&deps.storage).unwrap(),
+        Some(Addr::unchecked("muse"))
+    );
+
+    // anyone else, the updater included, can't
+    for sender in ["random", "muse"] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                set_msg(sender),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotMinterOrOwner {});
+    }
+}
+
+#[test]
+fn minting_to_contracts_can_be_rejected() {
+    let mut deps = mock_dependencies();
+    // "vault" is a contract, everything else is a plain account
+    mock_contracts(&mut deps.querier, &["vault"]);
+
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        reject_contract_owners: true,
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let minter = mock_info(MINTER, &[]);
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "stranded".to_string(),
+        owner: String::from("vault"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::CannotMintToContract {});
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "held".to_string(),
+        owner: String::from("prometheus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+}
+
+#[test]
+fn transfers_respect_cooldown() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        transfer_cooldown_blocks: Some(10),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "hourglass".to_string(),
+        owner: String::from("kairos"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let minted_at = mock_env().block.height;
+
+    let transfer_to = |recipient: &str| ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: "hourglass".to_string(),
+    };
+    let at_height = |height| {
+        let mut env = mock_env();
+        env.block.height = height;
+        env
+    };
+
+    // still inside the window that started at mint
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at_height(minted_at + 9),
+            mock_info("kairos", &[]),
+            transfer_to("aion"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TransferCooldown {
+            available_at: minted_at + 10
+        }
+    );
+
+    // once it has passed the transfer goes through and starts a new window
+    contract
+        .execute(
+            deps.as_mut(),
+            at_height(minted_at + 10),
+            mock_info("kairos", &[]),
+            transfer_to("aion"),
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            at_height(minted_at + 15),
+            mock_info("aion", &[]),
+            transfer_to("kairos"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TransferCooldown {
+            available_at: minted_at + 20
+        }
+    );
+    contract
+        .execute(
+            deps.as_mut(),
+            at_height(minted_at + 20),
+            mock_info("aion", &[]),
+            transfer_to("kairos"),
+        )
+        .unwrap();
+}
+
+#[test]
+fn managing_multiple_minters() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let owner = mock_info("creator", &[]);
+    let mint = |token_id: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("pandora"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+
+    // only the owner can add minters
+    let add_msg = ExecuteMsg::AddMinter {
+        minter: String::from("hephaestus"),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            add_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), add_msg)
+        .unwrap();
+    let res: MintersResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::Minters {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.minters, vec!["hephaestus", MINTER]);
+
+    // both minters can mint
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint("box"),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hephaestus", &[]),
+            mint("jar"),
+        )
+        .unwrap();
+
+    // removing a minter revokes their access
+    let remove_msg = ExecuteMsg::RemoveMinter {
+        minter: String::from(MINTER),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), remove_msg)
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint("lid"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+
+    // but the last one can't be removed
+    let remove_msg = ExecuteMsg::RemoveMinter {
+        minter: String::from("hephaestus"),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), remove_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::CannotRemoveLastMinter {});
+
+    // and removing someone who isn't a minter is an error
+    let remove_msg = ExecuteMsg::RemoveMinter {
+        minter: String::from(MINTER),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner, remove_msg)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MinterNotFound {
+            minter: String::from(MINTER)
+        }
+    );
+    let res = contract.minter(deps.as_ref()).unwrap();
+    assert_eq!(res.minter, "hephaestus");
+}
+
+#[test]
+fn simulating_send() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "scroll".to_string(),
+        owner: String::from("hermes"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let query_msg = QueryMsg::SimulateSend {
+        contract: String::from("library"),
+        token_id: String::from("scroll"),
+        msg: to_binary("shelve").unwrap(),
+        wrap: true,
+        with_owner: false,
+    };
+    let res: SimulateSendResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), query_msg)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.contract, "library");
+
+    // the receiver would get a ReceiveNft from the owner
+    #[cosmwasm_schema::cw_serde]
+    enum ReceiverMsg {
+        ReceiveNft(Cw721ReceiveMsg),
+    }
+    let ReceiverMsg::ReceiveNft(received) = from_binary(&res.msg).unwrap();
+    assert_eq!(
+        received,
+        Cw721ReceiveMsg {
+            sender: String::from("hermes"),
+            token_id: String::from("scroll"),
+            msg: to_binary("shelve").unwrap(),
+        }
+    );
+
+    // nothing actually moved
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "scroll".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "hermes");
+
+    // however SendNft is asked to encode it, the preview is what would be sent
+    for (wrap, with_owner) in [(true, false), (false, false), (true, true), (false, true)] {
+        let query_msg = QueryMsg::SimulateSend {
+            contract: String::from("library"),
+            token_id: String::from("scroll"),
+            msg: to_binary("shelve").unwrap(),
+            wrap,
+            with_owner,
+        };
+        let preview: SimulateSendResponse = from_binary(
+            &contract
+                .query(deps.as_ref(), mock_env(), query_msg)
+                .unwrap(),
+        )
+        .unwrap();
+        let send_msg = ExecuteMsg::SendNft {
+            contract: String::from("library"),
+            token_id: String::from("scroll"),
+            msg: to_binary("shelve").unwrap(),
+            safe_send: false,
+            wrap,
+            with_owner,
+        };
+        let res = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("hermes", &[]),
+                send_msg,
+            )
+            .unwrap();
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => assert_eq!(msg, &preview.msg),
+            m => panic!("Unexpected message type: {:?}", m),
+        }
+        let return_msg = ExecuteMsg::TransferNft {
+            recipient: String::from("hermes"),
+            token_id: String::from("scroll"),
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("library", &[]),
+                return_msg,
+            )
+            .unwrap();
+    }
+}
+
+#[test]
+fn approval_events_carry_full_approval_list() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "torch".to_string(),
+        owner: String::from("hecate"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let owner = mock_info("hecate", &[]);
+    let approvals_attr = |res: Response| {
+        res.attributes
+            .into_iter()
+            .find(|attr| attr.key == "approvals")
+            .unwrap()
+            .value
+    };
+
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("selene"),
+        token_id: String::from("torch"),
+        expires: Some(Expiration::AtHeight(50000)),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+        .unwrap();
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("eos"),
+        token_id: String::from("torch"),
+        expires: None,
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+        .unwrap();
+    assert_eq!(approvals_attr(res), "selene:height:50000,eos:never");
+
+    // after a revoke only the remaining approval is listed
+    let revoke_msg = ExecuteMsg::Revoke {
+        spender: String::from("selene"),
+        token_id: String::from("torch"),
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), owner, revoke_msg)
+        .unwrap();
+    let approvals = approvals_attr(res);
+    let parsed: Vec<(&str, &str)> = approvals
+        .split(',')
+        .map(|pair| pair.split_once(':').unwrap())
+        .collect();
+    assert_eq!(parsed, vec![("eos", "never")]);
+}
+
+#[test]
+fn instantiate_with_initial_mints() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let genesis = |token_id: &str, owner: &str| MintMsg::<Extension> {
+        token_id: token_id.to_string(),
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    };
+
+    // without initial mints the collection starts empty
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        ..Default::default()
+    };
+    let res = contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    assert_eq!(res.attributes, vec![("initial_mint_count", "0")]);
+    assert_eq!(0, contract.num_tokens(deps.as_ref()).unwrap().count);
+
+    // with them, every token exists right after instantiate
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        initial_mints: Some(vec![genesis("alpha", "adam"), genesis("omega", "eve")]),
+        ..Default::default()
+    };
+    let res = contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    assert_eq!(res.attributes, vec![("initial_mint_count", "2")]);
+    assert_eq!(2, contract.num_tokens(deps.as_ref()).unwrap().count);
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "omega".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "eve");
+
+    // a duplicate id in the initial set fails the whole instantiate
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        initial_mints: Some(vec![genesis("alpha", "adam"), genesis("alpha", "eve")]),
+        ..Default::default()
+    };
+    let err = contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::Claimed {});
+
+    // and so does going over the batch cap
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        initial_mints: Some((0..51).map(|i| genesis(&i.to_string(), "adam")).collect()),
+        ..Default::default()
+    };
+    let err = contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::BatchTooLarge { max: 50 });
+}
+
+#[test]
+fn batch_nft_info_mixes_found_and_missing() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    for token_id in ["sun", "moon"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("helios"),
+            token_uri: Some(format!("https://sky.example.com/{}.json", token_id)),
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    // ids keep the requested order, and unknown ones map to None
+    let query_msg = QueryMsg::BatchNftInfo {
+        token_ids: vec!["moon".to_string(), "comet".to_string(), "sun".to_string()],
+    };
+    let res: Vec<(String, Option<NftInfoResponse<Extension>>)> = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), query_msg)
+            .unwrap(),
+    )
+    .unwrap();
+    let info = |token_id: &str| NftInfoResponse::<Extension> {
+        token_uri: Some(format!("https://sky.example.com/{}.json", token_id)),
+        mint_block: Some(mock_env().block.height),
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    };
+    assert_eq!(
+        res,
+        vec![
+            ("moon".to_string(), Some(info("moon"))),
+            ("comet".to_string(), None),
+            ("sun".to_string(), Some(info("sun"))),
+        ]
+    );
+
+    // asking for too many at once is refused
+    let token_ids = (0..31).map(|i| i.to_string()).collect();
+    contract
+        .batch_nft_info(deps.as_ref(), token_ids)
+        .unwrap_err();
+}
+
+#[test]
+fn batch_owner_of_mixes_found_and_missing() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    for (token_id, owner) in [("sun", "helios"), ("moon", "selene")] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    // ids keep the requested order, and unknown ones map to None
+    let query_msg = QueryMsg::BatchOwnerOf {
+        token_ids: vec!["moon".to_string(), "comet".to_string(), "sun".to_string()],
+    };
+    let res: Vec<(String, Option<String>)> = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), query_msg)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        vec![
+            ("moon".to_string(), Some("selene".to_string())),
+            ("comet".to_string(), None),
+            ("sun".to_string(), Some("helios".to_string())),
+        ]
+    );
+
+    // 30 is fine, more is refused
+    let token_ids: Vec<String> = (0..30).map(|i| i.to_string()).collect();
+    let res = contract
+        .batch_owner_of(deps.as_ref(), token_ids.clone())
+        .unwrap();
+    assert!(res.iter().all(|(_, owner)| owner.is_none()));
+    let token_ids = token_ids.into_iter().chain(["30".to_string()]).collect();
+    contract
+        .batch_owner_of(deps.as_ref(), token_ids)
+        .unwrap_err();
+}
+
+#[test]
+fn tokens_by_owners_groups_and_caps() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    for (token_id, owner) in [
+        ("sun", "helios"),
+        ("dawn", "helios"),
+        ("moon", "selene"),
+        ("star", "asteria"),
+    ] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    let tokens_by_owners = |owners: &[&str], limit| -> Vec<(String, Vec<String>)> {
+        let query_msg: QueryMsg<Empty> = QueryMsg::TokensByOwners {
+            owners: owners.iter().map(|owner| owner.to_string()).collect(),
+            limit,
+        };
+        from_binary(
+            &contract
+                .query(deps.as_ref(), mock_env(), query_msg)
+                .unwrap(),
+        )
+        .unwrap()
+    };
+    let group = |owner: &str, tokens: &[&str]| {
+        (
+            owner.to_string(),
+            tokens
+                .iter()
+                .map(|token| token.to_string())
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    // grouped by owner in the order asked, owners without tokens included
+    assert_eq!(
+        tokens_by_owners(&["selene", "eos", "helios"], None),
+        vec![
+            group("selene", &["moon"]),
+            group("eos", &[]),
+            group("helios", &["dawn", "sun"]),
+        ]
+    );
+
+    // the limit caps the total, cutting the last owner short and leaving out the rest
+    assert_eq!(
+        tokens_by_owners(&["selene", "helios", "asteria"], Some(2)),
+        vec![group("selene", &["moon"]), group("helios", &["dawn"])]
+    );
+
+    // and no more than 30 owners at once
+    let owners = (0..31).map(|i| format!("owner{}", i)).collect();
+    contract
+        .tokens_by_owners(deps.as_ref(), owners, None)
+        .unwrap_err();
+}
+
+#[test]
+fn persistent_approvals_survive_transfer() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "vault".to_string(),
+        owner: String::from("midas"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    for spender in ["custodian", "broker"] {
+        let approve_msg = ExecuteMsg::Approve {
+            spender: spender.to_string(),
+            token_id: "vault".to_string(),
+            expires: None,
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("midas", &[]),
+                approve_msg,
+            )
+            .unwrap();
+    }
+
+    // only the owner can make an approval persistent
+    let persist_msg = ExecuteMsg::SetPersistentApproval {
+        token_id: "vault".to_string(),
+        spender: "custodian".to_string(),
+        persistent: true,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("broker", &[]),
+            persist_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotTokenOwner {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("midas", &[]),
+            persist_msg,
+        )
+        .unwrap();
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "croesus".to_string(),
+        token_id: "vault".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("midas", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+
+    // the custodian's approval carried over, the broker's was cleared
+    let res = contract
+        .approvals(deps.as_ref(), mock_env(), "vault".to_string(), false)
+        .unwrap();
+    assert_eq!(
+        res.approvals,
+        vec![Approval {
+            spender: String::from("custodian"),
+            expires: Expiration::Never {},
+        }]
+    );
+
+    // so the custodian can still move the token for the new owner
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "midas".to_string(),
+        token_id: "vault".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("custodian", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+}
+
+#[test]
+fn burn_all_owned_by_only_burns_target() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // three tokens for the target, one for a bystander
+    for (token_id, owner) in [
+        ("a", "icarus"),
+        ("b", "icarus"),
+        ("c", "daedalus"),
+        ("d", "icarus"),
+    ] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    // only the minter may do this
+    let burn_msg = ExecuteMsg::BurnAllOwnedBy {
+        owner: "icarus".to_string(),
+        limit: Some(2),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("icarus", &[]),
+            burn_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+
+    // the first page leaves one behind
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            burn_msg.clone(),
+        )
+        .unwrap();
+    assert!(res.attributes.contains(&("burned_count", "2").into()));
+    assert!(res.attributes.contains(&("has_more", "true").into()));
+    assert!(res.attributes.contains(&("token_id", "a").into()));
+    assert!(res.attributes.contains(&("token_id", "b").into()));
+    assert_eq!(2, contract.num_tokens(deps.as_ref()).unwrap().count);
+
+    // the second finishes the job
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), burn_msg)
+        .unwrap();
+    assert!(res.attributes.contains(&("burned_count", "1").into()));
+    assert!(res.attributes.contains(&("has_more", "false").into()));
+
+    let tokens = contract
+        .tokens(deps.as_ref(), "icarus".to_string(), None, None)
+        .unwrap();
+    assert!(tokens.tokens.is_empty());
+    assert_eq!(
+        0,
+        contract
+            .balance_of(deps.as_ref(), "icarus".to_string())
+            .unwrap()
+            .count
+    );
+
+    // the bystander keeps theirs
+    assert_eq!(1, contract.num_tokens(deps.as_ref()).unwrap().count);
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "c".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "daedalus");
+}
+
+#[test]
+fn update_contract_info() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let owner = mock_info("creator", &[]);
+
+    // only the owner can rebrand, not even the minter
+    let update_msg = ExecuteMsg::UpdateContractInfo {
+        name: Some("Dark Arts".to_string()),
+        symbol: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            update_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+
+    // name only keeps the symbol
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), update_msg)
+        .unwrap();
+    let info = contract.contract_info(deps.as_ref()).unwrap();
+    assert_eq!(
+        info,
+        ContractInfoResponse {
+            name: "Dark Arts".to_string(),
+            symbol: SYMBOL.to_string(),
+        }
+    );
+
+    // symbol only keeps the new name
+    let update_msg = ExecuteMsg::UpdateContractInfo {
+        name: None,
+        symbol: Some("DRK".to_string()),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), update_msg)
+        .unwrap();
+    let info = contract.contract_info(deps.as_ref()).unwrap();
+    assert_eq!(
+        info,
+        ContractInfoResponse {
+            name: "Dark Arts".to_string(),
+            symbol: "DRK".to_string(),
+        }
+    );
+
+    // empty or overly long values are rejected
+    let update_msg = ExecuteMsg::UpdateContractInfo {
+        name: Some(" ".to_string()),
+        symbol: None,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), update_msg)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidContractInfo {
+            field: "name".to_string(),
+            max: 64,
+        }
+    );
+    let update_msg = ExecuteMsg::UpdateContractInfo {
+        name: None,
+        symbol: Some("X".repeat(17)),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner, update_msg)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidContractInfo {
+            field: "symbol".to_string(),
+            max: 16,
+        }
+    );
+}
+
+#[test]
+fn all_owners_lists_distinct_holders() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // castor holds two tokens but should only be listed once
+    for (token_id, owner) in [("one", "castor"), ("two", "castor"), ("three", "pollux")] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+    let all_owners = |deps: cosmwasm_std::Deps<'_>| -> Vec<String> {
+        let query_msg = QueryMsg::AllOwners {
+            start_after: None,
+            limit: None,
+        };
+        let res: OwnersResponse =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.owners
+    };
+    assert_eq!(all_owners(deps.as_ref()), vec!["castor", "pollux"]);
+
+    // paginates by address
+    let res = contract
+        .all_owners(deps.as_ref(), Some("castor".to_string()), Some(1))
+        .unwrap();
+    assert_eq!(res.owners, vec!["pollux"]);
+
+    // pollux drops off once their only token is transferred away
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "castor".to_string(),
+        token_id: "three".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("pollux", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(all_owners(deps.as_ref()), vec!["castor"]);
+
+    // and castor once everything they hold is burned
+    for token_id in ["one", "two", "three"] {
+        let burn_msg = ExecuteMsg::Burn {
+            token_id: token_id.to_string(),
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("castor", &[]),
+                burn_msg,
+            )
+            .unwrap();
+    }
+    assert!(all_owners(deps.as_ref()).is_empty());
+}
+
+#[test]
+fn token_count_tracks_stored_tokens() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // a small deterministic generator, so any failure replays the same way
+    let mut seed: u64 = 0x5eed;
+    let mut next = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        seed >> 33
+    };
+
+    let mut live: Vec<String> = vec![];
+    let mut minted = 0u64;
+    for _ in 0..200 {
+        if live.is_empty() || next() % 3 != 0 {
+            minted += 1;
+            let token_id = format!("t{}", minted);
+            let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+                token_id: token_id.clone(),
+                owner: format!("holder{}", next() % 4),
+                token_uri: None,
+                extension: None,
+                metadata_hash: None,
+                edition: None,
+                edition_of: None,
+            });
+            contract
+                .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+                .unwrap();
+            live.push(token_id);
+        } else {
+            let token_id = live.swap_remove((next() as usize) % live.len());
+            let owner = contract
+                .owner_of(deps.as_ref(), mock_env(), token_id.clone(), false)
+                .unwrap()
+                .owner;
+            let burn_msg = ExecuteMsg::Burn { token_id };
+            contract
+                .execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), burn_msg)
+                .unwrap();
+        }
+
+        let count = contract.num_tokens(deps.as_ref()).unwrap().count;
+        let stored = contract
+            .tokens
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .count();
+        assert_eq!(count, live.len() as u64);
+        assert_eq!(count, stored as u64);
+    }
+}
+
+#[test]
+fn token_count_overflow_is_an_error() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    contract
+        .token_count
+        .save(deps.as_mut().storage, &u64::MAX)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "infinity".to_string(),
+        owner: String::from("ouroboros"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+}
+
+#[test]
+fn send_to_non_receiver_names_the_receiver() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "lyre".to_string(),
+        owner: String::from("orpheus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("underworld"),
+        token_id: String::from("lyre"),
+        msg: to_binary("play").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &[]),
+            send_msg,
+        )
+        .unwrap();
+    assert_eq!(res.messages[0].id, ReplyId::SendNft.id());
+
+    // the receiver doesn't know ReceiveNft, so its execute fails with whatever wasm says
+    let failure = Reply {
+        id: ReplyId::SendNft.id(),
+        result: SubMsgResult::Err("Error parsing into type underworld::msg::ExecuteMsg".into()),
+    };
+    let err = contract
+        .reply(deps.as_mut(), mock_env(), failure)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ReceiverRejected {
+            contract: String::from("underworld"),
+        }
+    );
+}
+
+#[test]
+fn safe_send_rolls_back_when_the_receiver_fails() {
+    let mut deps = mock_dependencies();
+    mock_contracts(&mut deps.querier, &["underworld"]);
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "lyre".to_string(),
+        owner: String::from("orpheus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("hermes"),
+        token_id: String::from("lyre"),
+        expires: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    let before = contract.tokens.load(&deps.storage, "lyre").unwrap();
+
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("underworld"),
+        token_id: String::from("lyre"),
+        msg: to_binary("play").unwrap(),
+        safe_send: true,
+        wrap: true,
+        with_owner: false,
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &[]),
+            send_msg,
+        )
+        .unwrap();
+    assert_eq!(res.messages[0].id, ReplyId::SafeSendNft.id());
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "lyre".into(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "underworld");
+
+    // the receiver rejects the token, so the transfer is undone instead of failing
+    let failure = Reply {
+        id: ReplyId::SafeSendNft.id(),
+        result: SubMsgResult::Err("Error parsing into type underworld::msg::ExecuteMsg".into()),
+    };
+    let res = contract.reply(deps.as_mut(), mock_env(), failure).unwrap();
+    assert_eq!(res.attributes[0], attr("action", "send_nft_rolled_back"));
+    assert_eq!(contract.tokens.load(&deps.storage, "lyre").unwrap(), before);
+    assert_eq!(
+        contract
+            .balance(&deps.storage, &Addr::unchecked("orpheus"))
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        contract
+            .balance(&deps.storage, &Addr::unchecked("underworld"))
+            .unwrap(),
+        0
+    );
+    assert!(contract
+        .spender_approvals
+        .has(&deps.storage, (&Addr::unchecked("hermes"), "lyre")));
+
+    // without safe_send the same failure reverts the transaction
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("underworld"),
+        token_id: String::from("lyre"),
+        msg: to_binary("play").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &[]),
+            send_msg,
+        )
+        .unwrap();
+    assert_eq!(res.messages[0].id, ReplyId::SendNft.id());
+    let failure = Reply {
+        id: ReplyId::SendNft.id(),
+        result: SubMsgResult::Err("Error parsing into type underworld::msg::ExecuteMsg".into()),
+    };
+    let err = contract
+        .reply(deps.as_mut(), mock_env(), failure)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ReceiverRejected {
+            contract: String::from("underworld"),
+        }
+    );
+}
+
+#[test]
+fn mint_accepts_any_listed_price() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        mint_prices: Some(vec![coin(100, "ujuno"), coin(5, "uusdc")]),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    // minters don't pay, so the buyers here mint in the public phase
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetPhase {
+                phase: Phase::Public,
+            },
+        )
+        .unwrap();
+
+    let mint_msg = |token_id: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("midas"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+
+    // paying in either accepted denom works, and the payment goes to the owner
+    for (token_id, funds) in [("gold", coins(100, "ujuno")), ("silver", coins(5, "uusdc"))] {
+        let res = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("midas", &funds),
+                mint_msg(token_id),
+            )
+            .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: String::from("creator"),
+                amount: funds,
+            })]
+        );
+    }
+
+    // too little of an accepted denom doesn't count
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("midas", &coins(99, "ujuno")),
+            mint_msg("bronze"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoAcceptedPaymentProvided {});
+
+    // neither does a denom that isn't listed, or no funds at all
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("midas", &coins(1000, "uatom")),
+            mint_msg("bronze"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoAcceptedPaymentProvided {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("midas", &[]),
+            mint_msg("bronze"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoAcceptedPaymentProvided {});
+
+    // minters mint for free, in any phase
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_msg("bronze"),
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+
+    let count = contract.num_tokens(deps.as_ref()).unwrap();
+    assert_eq!(3, count.count);
+}
+
+#[test]
+fn can_transfer_matches_transfer_permissions() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "compass".to_string(),
+        owner: String::from("magellan"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("elcano"),
+        token_id: String::from("compass"),
+        expires: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("magellan", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("pigafetta"),
+        expires: None,
+        min_token_height: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("magellan", &[]),
+            approve_all_msg,
+        )
+        .unwrap();
+
+    let can_transfer = |deps: Deps, address: &str| {
+        let query_msg: QueryMsg<Empty> = QueryMsg::CanTransfer {
+            token_id: String::from("compass"),
+            address: address.to_string(),
+            recipient: None,
+        };
+        let res: CanTransferResponse =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.can_transfer
+    };
+
+    // owner, approved spender and operator all may, a stranger may not
+    assert!(can_transfer(deps.as_ref(), "magellan"));
+    assert!(can_transfer(deps.as_ref(), "elcano"));
+    assert!(can_transfer(deps.as_ref(), "pigafetta"));
+    assert!(!can_transfer(deps.as_ref(), "drake"));
+
+    // an unknown token is an error, like NftInfo
+    let query_msg: QueryMsg<Empty> = QueryMsg::CanTransfer {
+        token_id: String::from("astrolabe"),
+        address: String::from("magellan"),
+        recipient: None,
+    };
+    contract
+        .query(deps.as_ref(), mock_env(), query_msg)
+        .unwrap_err();
+}
+
+#[test]
+fn mint_phases() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        mint_prices: Some(coins(100, "ujuno")),
+        allowlist_mint_prices: Some(coins(60, "ujuno")),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = |token_id: &str, owner: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+
+    // only the owner can change the phase or the allowlist
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("vendor", &[]),
+            ExecuteMsg::SetPhase {
+                phase: Phase::Public,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetMintAllowed {
+                address: String::from("early"),
+                allowed: true,
+            },
+        )
+        .unwrap();
+
+    // before any phase is set, only minters mint
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("early", &coins(100, "ujuno")),
+            mint_msg("first", "early"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetPhase {
+                phase: Phase::Allowlist,
+            },
+        )
+        .unwrap();
+
+    // a listed address mints at the discounted price
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("early", &coins(60, "ujuno")),
+            mint_msg("first", "early"),
+        )
+        .unwrap();
+    // an unlisted one can't, whatever it pays
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("late", &coins(100, "ujuno")),
+            mint_msg("second", "late"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotOnMintAllowlist {});
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetPhase {
+                phase: Phase::Public,
+            },
+        )
+        .unwrap();
+
+    // in the public phase anyone mints, but at full price
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("late", &coins(60, "ujuno")),
+            mint_msg("second", "late"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoAcceptedPaymentProvided {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("late", &coins(100, "ujuno")),
+            mint_msg("second", "late"),
+        )
+        .unwrap();
+
+    let config = contract.collection_config(deps.as_ref()).unwrap();
+    assert_eq!(config.phase, Phase::Public);
+    let count = contract.num_tokens(deps.as_ref()).unwrap();
+    assert_eq!(2, count.count);
+}
+
+#[test]
+fn metadata_hash_is_validated_and_stored() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // sha256 of the token's JSON, hex-encoded
+    let hash = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "scroll".to_string(),
+        owner: String::from("scribe"),
+        token_uri: Some("ipfs://scroll.json".to_string()),
+        extension: None,
+        metadata_hash: Some(hash.to_string()),
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let info = contract
+        .nft_info(deps.as_ref(), "scroll".to_string())
+        .unwrap();
+    assert_eq!(info.metadata_hash, Some(hash.to_string()));
+
+    // too short, and the right length but not hex
+    for bad_hash in ["9f86d081", &"z".repeat(64)] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: "forgery".to_string(),
+            owner: String::from("scribe"),
+            token_uri: Some("ipfs://forgery.json".to_string()),
+            extension: None,
+            metadata_hash: Some(bad_hash.to_string()),
+            edition: None,
+            edition_of: None,
+        });
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidMetadataHash {});
+    }
+    assert!(!contract.tokens.has(&deps.storage, "forgery"));
+}
+
+#[test]
+fn transfer_with_permit() {
+    use k256::ecdsa::signature::Signer;
+    use k256::ecdsa::{Signature, SigningKey};
+
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let env = mock_env();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "letter".to_string(),
+        owner: String::from("sender"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // the owner registers the key they'll sign permits with
+    let key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+    let register_msg = ExecuteMsg::SetPermitPubkey {
+        pubkey: Some(Binary::from(key.verifying_key().to_bytes().as_slice())),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("sender", &[]),
+            register_msg,
+        )
+        .unwrap();
+
+    // and signs a permit off-chain, which a relayer then submits
+    let permit = TransferPermit {
+        contract: env.contract.address.to_string(),
+        token_id: "letter".to_string(),
+        recipient: String::from("receiver"),
+        nonce: 0,
+        expires: Expiration::AtHeight(env.block.height + 100),
+    };
+    let signature: Signature = key.sign(&to_vec(&permit).unwrap());
+    let permit_msg = ExecuteMsg::TransferWithPermit {
+        permit: permit.clone(),
+        signature: Binary::from(signature.as_ref()),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("relayer", &[]),
+            permit_msg.clone(),
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "transfer_with_permit")
+            .add_attribute("sender", "sender")
+            .add_attribute("relayer", "relayer")
+            .add_attribute("recipient", "receiver")
+            .add_attribute("token_id", "letter")
+    );
+    let owner = contract
+        .owner_of(deps.as_ref(), env.clone(), "letter".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "receiver");
+
+    // once the token comes back, the same permit can't be replayed to take it again
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("sender"),
+        token_id: "letter".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("receiver", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("relayer", &[]),
+            permit_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidPermitNonce { expected: 1 });
+
+    // a permit past its expiry is refused
+    let permit = TransferPermit {
+        nonce: 1,
+        expires: Expiration::AtHeight(env.block.height),
+        ..permit
+    };
+    let signature: Signature = key.sign(&to_vec(&permit).unwrap());
+    let permit_msg = ExecuteMsg::TransferWithPermit {
+        permit: permit.clone(),
+        signature: Binary::from(signature.as_ref()),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("relayer", &[]),
+            permit_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::PermitExpired {});
+
+    // and so is one signed by anyone but the owner
+    let permit = TransferPermit {
+        expires: Expiration::Never {},
+        ..permit
+    };
+    let forger = SigningKey::from_bytes(&[9u8; 32]).unwrap();
+    let signature: Signature = forger.sign(&to_vec(&permit).unwrap());
+    let permit_msg = ExecuteMsg::TransferWithPermit {
+        permit,
+        signature: Binary::from(signature.as_ref()),
+    };
+    let err = contract
+        .execute(deps.as_mut(), env, mock_info("relayer", &[]), permit_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidPermitSignature {});
+}
+
+#[test]
+fn transfers_pay_the_transfer_fee() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        transfer_fee: Some(coin(10, "ujuno")),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "toll".to_string(),
+        owner: String::from("charon"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // underpaying, or paying in the wrong denom, is rejected
+    let transfer_msg = |recipient: &str| ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: "toll".to_string(),
+    };
+    for funds in [coins(9, "ujuno"), coins(10, "uatom")] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("charon", &funds),
+                transfer_msg("orpheus"),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientTransferFee {
+                fee: coin(10, "ujuno"),
+            }
+        );
+    }
+
+    // the fee is only asked for once the transfer is allowed, so a caller without the
+    // token, or a self-transfer, gets the real reason even without funds
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("underworld"),
+        token_id: "toll".to_string(),
+        msg: to_binary("cross").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    for msg in [transfer_msg("orpheus"), send_msg] {
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), mock_info("orpheus", &[]), msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotOwnerOrApproved {});
+    }
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("charon", &[]),
+            transfer_msg("charon"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::CannotTransferToSelf {});
+
+    // paying the fee moves the token and sends the fee to the owner
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("charon", &coins(10, "ujuno")),
+            transfer_msg("orpheus"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("creator"),
+            amount: coins(10, "ujuno"),
+        })]
+    );
+
+    // with SendNftWithFunds, the fee is taken off and the receiver gets the rest
+    let send_msg = ExecuteMsg::SendNftWithFunds {
+        contract: String::from("underworld"),
+        token_id: "toll".to_string(),
+        msg: to_binary("cross").unwrap(),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &coins(25, "ujuno")),
+            send_msg,
+        )
+        .unwrap();
+    let expected = Cw721ReceiveMsg {
+        sender: String::from("orpheus"),
+        token_id: "toll".to_string(),
+        msg: to_binary("cross").unwrap(),
+    };
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_always(
+                expected
+                    .into_cosmos_msg_with_funds("underworld", coins(15, "ujuno"))
+                    .unwrap(),
+                ReplyId::SendNft.id(),
+            ),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("creator"),
+                amount: coins(10, "ujuno"),
+            }),
+        ]
+    );
+
+    // paying more than the fee only sends the fee to the owner, the rest goes back
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("underworld", &[coin(25, "ujuno"), coin(5, "uatom")]),
+            transfer_msg("charon"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("creator"),
+                amount: coins(10, "ujuno"),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("underworld"),
+                amount: vec![coin(15, "ujuno"), coin(5, "uatom")],
+            }),
+        ]
+    );
+
+    // funds listing a denom twice pay the fee from the first one, never underflowing
+    let send_msg = ExecuteMsg::SendNftWithFunds {
+        contract: String::from("underworld"),
+        token_id: "toll".to_string(),
+        msg: to_binary("cross").unwrap(),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("charon", &[coin(20, "ujuno"), coin(5, "ujuno")]),
+            send_msg,
+        )
+        .unwrap();
+    let expected = Cw721ReceiveMsg {
+        sender: String::from("charon"),
+        token_id: "toll".to_string(),
+        msg: to_binary("cross").unwrap(),
+    };
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_always(
+            expected
+                .into_cosmos_msg_with_funds("underworld", vec![coin(10, "ujuno"), coin(5, "ujuno")])
+                .unwrap(),
+            ReplyId::SendNft.id(),
+        )
+    );
+
+    // without a fee, transfers stay free
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "toll".to_string(),
+        owner: String::from("charon"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("charon", &[]),
+            transfer_msg("orpheus"),
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn query_operator_expiration() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let approve_all = |operator: &str, expires| ExecuteMsg::ApproveAll {
+        operator: operator.to_string(),
+        expires: Some(expires),
+        min_token_height: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("demeter", &[]),
+            approve_all("persephone", Expiration::AtHeight(50_000)),
+        )
+        .unwrap();
+    let expires = mock_env().block.height + 1;
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("demeter", &[]),
+            approve_all("hades", Expiration::AtHeight(expires)),
+        )
+        .unwrap();
+
+    let operator_expiration = |env, operator: &str| -> Option<Expiration> {
+        let query_msg: QueryMsg<Empty> = QueryMsg::OperatorExpiration {
+            owner: String::from("demeter"),
+            operator: operator.to_string(),
+        };
+        from_binary(&contract.query(deps.as_ref(), env, query_msg).unwrap()).unwrap()
+    };
+
+    assert_eq!(
+        operator_expiration(mock_env(), "persephone"),
+        Some(Expiration::AtHeight(50_000))
+    );
+
+    // an expired grant is still reported, with when it expired
+    let mut later = mock_env();
+    later.block.height = expires + 10;
+    assert_eq!(
+        operator_expiration(later, "hades"),
+        Some(Expiration::AtHeight(expires))
+    );
+
+    // no grant at all is None
+    assert_eq!(operator_expiration(mock_env(), "zeus"), None);
+}
+
+#[test]
+fn query_is_operator() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let expires = mock_env().block.height + 1;
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("demeter", &[]),
+            ExecuteMsg::ApproveAll {
+                operator: String::from("hades"),
+                expires: Some(Expiration::AtHeight(expires)),
+                min_token_height: None,
+            },
+        )
+        .unwrap();
+
+    let is_operator = |env, operator: &str| -> bool {
+        let query_msg: QueryMsg<Empty> = QueryMsg::IsOperator {
+            owner: String::from("demeter"),
+            operator: operator.to_string(),
+        };
+        let res: IsOperatorResponse =
+            from_binary(&contract.query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+        res.is_operator
+    };
+
+    // an active grant
+    assert!(is_operator(mock_env(), "hades"));
+
+    // the same grant once it has expired
+    let mut later = mock_env();
+    later.block.height = expires;
+    assert!(!is_operator(later, "hades"));
+
+    // no grant at all
+    assert!(!is_operator(mock_env(), "zeus"));
+}
+
+#[test]
+fn mints_without_extension_get_the_default() {
+    let mut deps = mock_dependencies();
+    // a string extension, so the default and an explicit one can be told apart
+    let contract = Cw721Contract::<Option<String>, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        default_extension: Some(Some("common card".to_string())),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = |token_id: &str, extension: Option<String>| {
+        ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.to_string(),
+            owner: String::from("collector"),
+            token_uri: None,
+            extension,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_msg("plain", None),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_msg("shiny", Some("holo card".to_string())),
+        )
+        .unwrap();
+
+    // omitted inherits the default, explicit overrides it
+    let plain = contract
+        .nft_info(deps.as_ref(), "plain".to_string())
+        .unwrap();
+    assert_eq!(plain.extension, Some("common card".to_string()));
+    let shiny = contract
+        .nft_info(deps.as_ref(), "shiny".to_string())
+        .unwrap();
+    assert_eq!(shiny.extension, Some("holo card".to_string()));
+}
+
+// an extension that serializes to `{}` when nothing is set
+#[cosmwasm_schema::cw_serde]
+#[derive(Default)]
+struct Card {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rarity: Option<String>,
+}
+
+#[test]
+fn mints_with_empty_extension_get_the_default() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Card, Empty, Empty, Empty>::default();
+    let default_extension = Card {
+        rarity: Some("common".to_string()),
+    };
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        default_extension: Some(default_extension.clone()),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg {
+        token_id: "blank".to_string(),
+        owner: String::from("collector"),
+        token_uri: None,
+        extension: Card { rarity: None },
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // the mint treats `{}` as empty, the same as NftInfo with_defaults does
+    let info = contract
+        .nft_info(deps.as_ref(), "blank".to_string())
+        .unwrap();
+    assert_eq!(info.extension, default_extension);
+}
+
+#[test]
+fn mint_validates_token_id() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = |token_id: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("indexer"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+
+    // alphanumerics, '-', '_' and ':' are fine, up to 128 characters
+    let long_id = "a".repeat(128);
+    for token_id in ["season-1:card_042", long_id.as_str()] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MINTER, &[]),
+                mint_msg(token_id),
+            )
+            .unwrap();
+    }
+
+    let too_long = "a".repeat(129);
+    for token_id in [too_long.as_str(), "card 42", "card/42", "card\u{1F0CF}", ""] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MINTER, &[]),
+                mint_msg(token_id),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidTokenId {
+                token_id: token_id.to_string(),
+            }
+        );
+    }
+
+    // a collection can choose a tighter limit
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        max_token_id_length: Some(8),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_msg("card_0042"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidTokenId {
+            token_id: "card_0042".to_string(),
+        }
+    );
+}
+
+#[test]
+fn reserved_token_ids_are_for_minters() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        reserved_prefix: Some("team-".to_string()),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetPhase {
+                phase: Phase::Public,
+            },
+        )
+        .unwrap();
+
+    let mint_msg = |token_id: &str, owner: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+
+    // the minter can mint reserved ids
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_msg("team-1", "founder"),
+        )
+        .unwrap();
+
+    // the public can mint anything else, but not a reserved id
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("fan", &[]),
+            mint_msg("1", "fan"),
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("fan", &[]),
+            mint_msg("team-2", "fan"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ReservedTokenId {
+            token_id: "team-2".to_string(),
+        }
+    );
+}
+
+#[test]
+fn burn_notifies_the_redemption_contract() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        redemption_contract: Some(String::from("warehouse")),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "sneakers".to_string(),
+        owner: String::from("hermes"),
+        token_uri: Some("ipfs://sneakers.json".to_string()),
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: "sneakers".to_string(),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hermes", &[]),
+            burn_msg,
+        )
+        .unwrap();
+    let redeem = RedeemMsg {
+        token_id: "sneakers".to_string(),
+        redeemer: String::from("hermes"),
+        token_uri: Some("ipfs://sneakers.json".to_string()),
+    };
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(redeem.into_cosmos_msg("warehouse").unwrap())]
+    );
+    // which the fulfillment contract receives under a "redeem" variant
+    let msg = concat!(
+        r#"{"redeem":{"token_id":"sneakers","redeemer":"hermes","#,
+        r#""token_uri":"ipfs://sneakers.json"}}"#,
+    );
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("warehouse"),
+            msg: Binary::from(msg.as_bytes()),
+            funds: vec![],
+        })
+    );
+
+    // without a redemption contract, burning sends nothing
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "sneakers".to_string(),
+        owner: String::from("hermes"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: "sneakers".to_string(),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hermes", &[]),
+            burn_msg,
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn minimal_events_trim_attributes() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        minimal_events: true,
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "haiku".to_string(),
+        owner: String::from("basho"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let minimal = Response::<Empty>::new()
+        .add_attribute("action", "approve")
+        .add_attribute("token_id", "haiku");
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("buson"),
+        token_id: "haiku".to_string(),
+        expires: None,
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("basho", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    assert_eq!(res, minimal);
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("issa"),
+        token_id: "haiku".to_string(),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("basho", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "transfer_nft")
+            .add_attribute("token_id", "haiku")
+    );
+
+    // sends keep their ReceiveNft message, just not the extra attributes
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("anthology"),
+        token_id: "haiku".to_string(),
+        msg: to_binary("publish").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("issa", &[]), send_msg)
+        .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "send_nft"), attr("token_id", "haiku"),]
+    );
+
+    // by default, everything is still reported
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "haiku".to_string(),
+        owner: String::from("basho"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("issa"),
+        token_id: "haiku".to_string(),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("basho", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(res.attributes.len(), 5);
+}
+
+#[test]
+fn contract_cannot_be_its_own_minter() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let env = mock_env();
+
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: env.contract.address.to_string(),
+        ..Default::default()
+    };
+    let err = contract
+        .instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMinter {});
+
+    // a regular minter is fine, but the contract can't be added later either
+    let contract = setup_contract(deps.as_mut());
+    assert!(contract
+        .is_minter(&deps.storage, &Addr::unchecked(MINTER))
+        .unwrap());
+    let add_msg = ExecuteMsg::AddMinter {
+        minter: env.contract.address.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), env, mock_info("creator", &[]), add_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMinter {});
+}
+
+#[test]
+fn raw_token_info_returns_the_stored_token() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "astrolabe".to_string(),
+        owner: String::from("hipparchus"),
+        token_uri: Some("https://example.com/astrolabe.json".into()),
+        extension: Some(Empty {}),
+        metadata_hash: Some("ab".repeat(32)),
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("ptolemy"),
+        token_id: String::from("astrolabe"),
+        expires: Some(Expiration::AtHeight(100_000)),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hipparchus", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    let persist_msg = ExecuteMsg::SetPersistentApproval {
+        token_id: String::from("astrolabe"),
+        spender: String::from("ptolemy"),
+        persistent: true,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hipparchus", &[]),
+            persist_msg,
+        )
+        .unwrap();
+
+    let query_msg: QueryMsg<Empty> = QueryMsg::RawTokenInfo {
+        token_id: String::from("astrolabe"),
+    };
+    let raw: TokenInfo<Extension> = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), query_msg)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        raw,
+        contract.tokens.load(&deps.storage, "astrolabe").unwrap()
+    );
+    assert_eq!(raw.owner, "hipparchus");
+    assert_eq!(raw.approvals.len(), 1);
+    assert_eq!(raw.persistent_approvals, vec![Addr::unchecked("ptolemy")]);
+    assert_eq!(raw.mint_block, mock_env().block.height);
+    assert_eq!(raw.last_transfer, mock_env().block.height);
+    assert_eq!(raw.extension, Some(Empty {}));
+    assert_eq!(raw.metadata_hash, Some("ab".repeat(32)));
+
+    // unknown tokens are an error, like NftInfo
+    let query_msg: QueryMsg<Empty> = QueryMsg::RawTokenInfo {
+        token_id: String::from("armillary"),
+    };
+    contract
+        .query(deps.as_ref(), mock_env(), query_msg)
+        .unwrap_err();
+}
+
+#[test]
+fn token_approval_count_skips_expired_approvals() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "sextant".to_string(),
+        owner: String::from("cook"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let approval_count = |deps: Deps, env| {
+        let query_msg: QueryMsg<Empty> = QueryMsg::TokenApprovalCount {
+            token_id: String::from("sextant"),
+        };
+        let res: TokenApprovalCountResponse =
+            from_binary(&contract.query(deps, env, query_msg).unwrap()).unwrap();
+        res.count
+    };
+    assert_eq!(approval_count(deps.as_ref(), mock_env()), 0);
+
+    // one approval that never expires, one that expires in ten blocks
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("banks"),
+        token_id: String::from("sextant"),
+        expires: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("cook", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    assert_eq!(approval_count(deps.as_ref(), mock_env()), 1);
+
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("solander"),
+        token_id: String::from("sextant"),
+        expires: Some(Expiration::AtHeight(mock_env().block.height + 10)),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("cook", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    assert_eq!(approval_count(deps.as_ref(), mock_env()), 2);
+
+    // once the second one expires, only the first counts
+    let mut later = mock_env();
+    later.block.height += 10;
+    assert_eq!(approval_count(deps.as_ref(), later), 1);
+}
+
+#[test]
+fn mint_signed_by_the_collection_signer() {
+    use k256::ecdsa::signature::Signer;
+    use k256::ecdsa::{Signature, SigningKey};
+
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let env = mock_env();
+    let signer = SigningKey::from_bytes(&[5u8; 32]).unwrap();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        signer_pubkey: Some(Binary::from(signer.verifying_key().to_bytes().as_slice())),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    // the signer authorizes this exact mint off-chain, the recipient submits it
+    let mint_msg = MintMsg::<Extension> {
+        token_id: "ticket".to_string(),
+        owner: String::from("guest"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    };
+    let authorization = MintAuthorization {
+        contract: env.contract.address.to_string(),
+        msg: mint_msg.clone(),
+    };
+    let signature: Signature = signer.sign(&to_vec(&authorization).unwrap());
+    let signed_msg = ExecuteMsg::MintSigned {
+        msg: mint_msg.clone(),
+        signature: Binary::from(signature.as_ref()),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("guest", &[]),
+            signed_msg.clone(),
+        )
+        .unwrap();
+    let owner = contract
+        .owner_of(deps.as_ref(), env.clone(), "ticket".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "guest");
+
+    // burning the token doesn't make the signature usable again
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: "ticket".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("guest", &[]),
+            burn_msg,
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("guest", &[]),
+            signed_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Claimed {});
+
+    // a signature from anyone else is refused, as is a changed msg under a valid signature
+    let forger = SigningKey::from_bytes(&[9u8; 32]).unwrap();
+    let mint_msg = MintMsg {
+        token_id: "forged".to_string(),
+        ..mint_msg
+    };
+    let authorization = MintAuthorization {
+        contract: env.contract.address.to_string(),
+        msg: mint_msg.clone(),
+    };
+    let forged: Signature = forger.sign(&to_vec(&authorization).unwrap());
+    let signed_msg = ExecuteMsg::MintSigned {
+        msg: mint_msg.clone(),
+        signature: Binary::from(forged.as_ref()),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("guest", &[]),
+            signed_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMintSignature {});
+
+    let signature: Signature = signer.sign(&to_vec(&authorization).unwrap());
+    let signed_msg = ExecuteMsg::MintSigned {
+        msg: MintMsg {
+            owner: String::from("gatecrasher"),
+            ..mint_msg
+        },
+        signature: Binary::from(signature.as_ref()),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env,
+            mock_info("gatecrasher", &[]),
+            signed_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMintSignature {});
+}
+
+#[test]
+fn owner_toggles_transfers() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    for token_id in ["vested", "cliff"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("founder"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    // only the owner may turn transfers off
+    let disable_msg = ExecuteMsg::SetTransfersEnabled { enabled: false };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("founder", &[]),
+            disable_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            disable_msg,
+        )
+        .unwrap();
+
+    // while off, neither transfers nor sends go through
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("investor"),
+        token_id: String::from("vested"),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("founder", &[]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TransfersDisabled {});
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("exchange"),
+        token_id: String::from("vested"),
+        msg: to_binary("sell").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("founder", &[]),
+            send_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TransfersDisabled {});
+
+    // burning still works
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("cliff"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("founder", &[]),
+            burn_msg,
+        )
+        .unwrap();
+
+    // and once back on, the transfer goes through
+    let enable_msg = ExecuteMsg::SetTransfersEnabled { enabled: true };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            enable_msg,
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("founder", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "vested".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "investor");
+}
+
+#[test]
+fn mint_packet_describes_the_mint() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let env = mock_env();
+
+    let mint_msg = MintMsg::<Extension> {
+        token_id: "beacon".to_string(),
+        owner: String::from("keeper"),
+        token_uri: Some("https://example.com/beacon.json".into()),
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    };
+    let packet = MintPacket::new(&env, &mint_msg);
+    assert_eq!(
+        packet.to_binary().unwrap(),
+        Binary::from(
+            concat!(
+                r#"{"contract":"cosmos2contract","token_id":"beacon","owner":"keeper","#,
+                r#""token_uri":"https://example.com/beacon.json","block_height":12345}"#
+            )
+            .as_bytes()
+        )
+    );
+
+    // without an open channel picked for them, mints send no packet
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint(mint_msg),
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+    let channel_msg = ExecuteMsg::SetMintChannel {
+        channel_id: Some("channel-7".to_string()),
+    };
+    let err = contract
+        .execute(deps.as_mut(), env, mock_info("creator", &[]), channel_msg)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownChannel {
+            channel_id: "channel-7".to_string()
+        }
+    );
+}
+
+#[cfg(feature = "ibc")]
+#[test]
+fn mints_send_packets_over_the_mint_channel() {
+    use crate::ibc::IBC_VERSION;
+    use cosmwasm_std::testing::{
+        mock_ibc_channel_close_init, mock_ibc_channel_connect_ack, mock_ibc_channel_open_init,
+    };
+    use cosmwasm_std::{IbcMsg, IbcOrder, IbcTimeout};
+
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let env = mock_env();
+
+    // the handshake only goes through with the mint packet version
+    let open_msg = mock_ibc_channel_open_init("channel-7", IbcOrder::Unordered, "ics20-1");
+    let err = contract
+        .ibc_channel_open(deps.as_mut(), env.clone(), open_msg)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidIbcVersion {
+            version: "ics20-1".to_string()
+        }
+    );
+    let open_msg = mock_ibc_channel_open_init("channel-7", IbcOrder::Unordered, IBC_VERSION);
+    contract
+        .ibc_channel_open(deps.as_mut(), env.clone(), open_msg)
+        .unwrap();
+    let connect_msg = mock_ibc_channel_connect_ack("channel-7", IbcOrder::Unordered, IBC_VERSION);
+    contract
+        .ibc_channel_connect(deps.as_mut(), env.clone(), connect_msg)
+        .unwrap();
+    let channel_msg = ExecuteMsg::SetMintChannel {
+        channel_id: Some("channel-7".to_string()),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            channel_msg,
+        )
+        .unwrap();
+
+    let mint_msg = MintMsg::<Extension> {
+        token_id: "beacon".to_string(),
+        owner: String::from("keeper"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    };
+    let packet = MintPacket::new(&env, &mint_msg);
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint(mint_msg),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(IbcMsg::SendPacket {
+            channel_id: "channel-7".to_string(),
+            data: packet.to_binary().unwrap(),
+            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(3600)),
+        })]
+    );
+
+    // once the channel closes, mints go back to sending nothing
+    let close_msg = mock_ibc_channel_close_init("channel-7", IbcOrder::Unordered, IBC_VERSION);
+    contract
+        .ibc_channel_close(deps.as_mut(), env.clone(), close_msg)
+        .unwrap();
+    let mint_msg = MintMsg::<Extension> {
+        token_id: "lantern".to_string(),
+        owner: String::from("keeper"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env,
+            mock_info(MINTER, &[]),
+            ExecuteMsg::Mint(mint_msg),
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn stats_track_mints_transfers_and_burns() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let stats = |deps: Deps| -> StatsResponse {
+        let query_msg: QueryMsg<Empty> = QueryMsg::Stats {};
+        from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap()
+    };
+    assert_eq!(
+        stats(deps.as_ref()),
+        StatsResponse {
+            supply: 0,
+            minted_total: 0,
+            burned_total: 0,
+            owner_count: 0,
+        }
+    );
+
+    for (token_id, owner) in [
+        ("acorn", "squirrel"),
+        ("hazel", "squirrel"),
+        ("walnut", "jay"),
+    ] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+    assert_eq!(
+        stats(deps.as_ref()),
+        StatsResponse {
+            supply: 3,
+            minted_total: 3,
+            burned_total: 0,
+            owner_count: 2,
+        }
+    );
+
+    // a transfer to a new holder adds an owner, but leaves the totals alone
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("dormouse"),
+        token_id: String::from("hazel"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("squirrel", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(stats(deps.as_ref()).owner_count, 3);
+
+    // burning jay's only token drops the supply and the owner
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("walnut"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("jay", &[]), burn_msg)
+        .unwrap();
+    assert_eq!(
+        stats(deps.as_ref()),
+        StatsResponse {
+            supply: 2,
+            minted_total: 3,
+            burned_total: 1,
+            owner_count: 2,
+        }
+    );
+}
+
+#[test]
+fn burned_total_keeps_totals_in_step_with_supply() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let check_invariant = |deps: Deps| {
+        let minted: MintedTotalResponse = from_binary(
+            &contract
+                .query(deps, mock_env(), QueryMsg::MintedTotal {})
+                .unwrap(),
+        )
+        .unwrap();
+        let burned: BurnedTotalResponse = from_binary(
+            &contract
+                .query(deps, mock_env(), QueryMsg::BurnedTotal {})
+                .unwrap(),
+        )
+        .unwrap();
+        let supply = contract.num_tokens(deps).unwrap().count;
+        assert_eq!(minted.count - burned.count, supply);
+        burned.count
+    };
+
+    for token_id in ["1", "2", "3", "4", "5"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("potter"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+        assert_eq!(check_invariant(deps.as_ref()), 0);
+    }
+
+    // a single burn
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("3"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("potter", &[]),
+            burn_msg,
+        )
+        .unwrap();
+    assert_eq!(check_invariant(deps.as_ref()), 1);
+
+    // a transfer changes neither total
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("glazier"),
+        token_id: String::from("5"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("potter", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(check_invariant(deps.as_ref()), 1);
+
+    // a batch burn counts every token it burns
+    let burn_all_msg = ExecuteMsg::BurnAllOwnedBy {
+        owner: String::from("potter"),
+        limit: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            burn_all_msg,
+        )
+        .unwrap();
+    assert_eq!(check_invariant(deps.as_ref()), 4);
+
+    // and minting again keeps them in step
+    let mint_msg = ExecuteMsg::MintNext {
+        owner: String::from("potter"),
+        token_uri: None,
+        extension: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    assert_eq!(check_invariant(deps.as_ref()), 4);
+}
+
+#[test]
+fn expired_grants_are_told_apart_from_no_grant() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let env = mock_env();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "hourglass".to_string(),
+        owner: String::from("chronos"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), env.clone(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // kairos gets a token approval and aion an operator grant, both for ten blocks
+    let expires = Some(Expiration::AtHeight(env.block.height + 10));
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("kairos"),
+        token_id: String::from("hourglass"),
+        expires,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("chronos", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("aion"),
+        expires,
+        min_token_height: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("chronos", &[]),
+            approve_all_msg,
+        )
+        .unwrap();
+
+    let mut later = env;
+    later.block.height += 10;
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("eris"),
+        token_id: String::from("hourglass"),
+    };
+    let mut transfer_as = |sender: &str| {
+        contract
+            .execute(
+                deps.as_mut(),
+                later.clone(),
+                mock_info(sender, &[]),
+                transfer_msg.clone(),
+            )
+            .unwrap_err()
+    };
+    assert_eq!(transfer_as("kairos"), ContractError::ApprovalExpired {});
+    assert_eq!(transfer_as("aion"), ContractError::OperatorExpired {});
+    assert_eq!(transfer_as("eris"), ContractError::NotOwnerOrApproved {});
+}
+
+#[test]
+fn owner_sets_collection_uri() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        collection_uri: Some("ipfs://banner/v1.json".into()),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let collection_info = |deps: Deps| -> CollectionInfoResponse {
+        let query_msg: QueryMsg<Empty> = QueryMsg::CollectionInfo {};
+        from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap()
+    };
+    assert_eq!(
+        collection_info(deps.as_ref()),
+        CollectionInfoResponse {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            collection_uri: Some("ipfs://banner/v1.json".into()),
+        }
+    );
+
+    // only the owner may change it
+    let set_msg = ExecuteMsg::SetCollectionUri {
+        collection_uri: Some("ipfs://banner/v2.json".into()),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            set_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            set_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        collection_info(deps.as_ref()).collection_uri,
+        Some("ipfs://banner/v2.json".into())
+    );
+
+    // and clear it
+    let clear_msg = ExecuteMsg::SetCollectionUri {
+        collection_uri: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            clear_msg,
+        )
+        .unwrap();
+    assert_eq!(collection_info(deps.as_ref()).collection_uri, None);
+}
+
+#[test]
+fn transfer_reports_previous_owner() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "fresco".to_string(),
+        owner: String::from("medici"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("dealer"),
+        token_id: String::from("fresco"),
+        expires: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medici", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    let owner_before = contract
+        .owner_of(deps.as_ref(), mock_env(), "fresco".to_string(), false)
+        .unwrap()
+        .owner;
+
+    // the approved dealer moves it, the event still names the owner it was taken from
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("borghese"),
+        token_id: String::from("fresco"),
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("dealer", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert!(res.attributes.contains(&attr("sender", "dealer")));
+    assert!(res
+        .attributes
+        .contains(&attr("previous_owner", owner_before.as_str())));
+
+    // same for a send
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("gallery"),
+        token_id: String::from("fresco"),
+        msg: to_binary("hang").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("borghese", &[]),
+            send_msg,
+        )
+        .unwrap();
+    assert!(res.attributes.contains(&attr("previous_owner", "borghese")));
+}
+
+#[test]
+fn query_supported_standards() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let query_msg: QueryMsg<Empty> = QueryMsg::SupportedStandards {};
+    let res: SupportedStandardsResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), query_msg)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(res.standards.contains(&"cw721-1.0".to_string()));
+    // royalties are only reported by cw2981-royalties
+    assert!(!res.standards.contains(&"cw2981".to_string()));
+    #[cfg(feature = "ibc")]
+    assert!(res.standards.contains(&crate::ibc::IBC_VERSION.to_string()));
+}
+
+#[test]
+fn nft_info_with_defaults_fills_empty_extension() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Option<String>, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    for (token_id, extension) in [("plain", None), ("shiny", Some("holo card".to_string()))] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.to_string(),
+            owner: String::from("collector"),
+            token_uri: None,
+            extension,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    let nft_info = |deps: Deps, token_id: &str, with_defaults: Option<bool>| {
+        let query_msg: QueryMsg<Empty> = QueryMsg::NftInfo {
+            token_id: token_id.to_string(),
+            with_defaults,
+        };
+        let res: NftInfoResponse<Option<String>> =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.extension
+    };
+
+    // without a configured default there is nothing to merge
+    assert_eq!(nft_info(deps.as_ref(), "plain", Some(true)), None);
+
+    // the default arrives after "plain" was minted, e.g. through a migration
+    contract
+        .default_extension
+        .save(deps.as_mut().storage, &Some("common card".to_string()))
+        .unwrap();
+
+    // only asking for defaults merges, and the stored token keeps its empty extension
+    assert_eq!(nft_info(deps.as_ref(), "plain", None), None);
+    assert_eq!(nft_info(deps.as_ref(), "plain", Some(false)), None);
+    assert_eq!(
+        nft_info(deps.as_ref(), "plain", Some(true)),
+        Some("common card".to_string())
+    );
+    assert_eq!(
+        contract
+            .tokens
+            .load(&deps.storage, "plain")
+            .unwrap()
+            .extension,
+        None
+    );
+
+    // a token with its own extension is never overridden
+    assert_eq!(
+        nft_info(deps.as_ref(), "shiny", Some(true)),
+        Some("holo card".to_string())
+    );
+}
+
+#[test]
+fn owner_rescues_tokens_held_by_the_contract() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let contract_addr = mock_env().contract.address.to_string();
+
+    // one token ended up on the contract itself, the other is owned normally
+    for (token_id, owner) in [("stuck", contract_addr.as_str()), ("held", "venus")] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    let rescue_msg = |collection: Option<&str>, token_id: &str| ExecuteMsg::RescueNft {
+        collection: collection.map(String::from),
+        token_id: token_id.to_string(),
+        recipient: String::from("venus"),
+    };
+
+    // only the owner can rescue
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            rescue_msg(None, "stuck"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+
+    // and only tokens the contract holds
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            rescue_msg(None, "held"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotHeldByContract {});
+
+    // naming this contract as the collection is the same internal rescue
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            rescue_msg(Some(&contract_addr), "stuck"),
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("collection", contract_addr.as_str()),
+            attr("action", "rescue_nft"),
+            attr("sender", "creator"),
+            attr("recipient", "venus"),
+            attr("token_id", "stuck"),
+        ]
+    );
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "stuck".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "venus");
+    assert_eq!(
+        contract
+            .balance_of(deps.as_ref(), contract_addr.clone())
+            .unwrap()
+            .count,
+        0
+    );
+
+    // another collection gets a TransferNft from the contract
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            rescue_msg(Some("other-collection"), "punk-7"),
+        )
+        .unwrap();
+    let transfer = WasmMsg::Execute {
+        contract_addr: String::from("other-collection"),
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: String::from("venus"),
+            token_id: String::from("punk-7"),
+        })
+        .unwrap(),
+        funds: vec![],
+    };
+    assert_eq!(res.messages, vec![SubMsg::new(transfer)]);
+    assert!(res
+        .attributes
+        .contains(&attr("collection", "other-collection")));
+}
+
+#[test]
+fn send_clears_approvals_unless_configured() {
+    for keep_approvals_on_send in [false, true] {
+        let mut deps = mock_dependencies();
+        let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            minter: String::from(MINTER),
+            keep_approvals_on_send,
+            ..Default::default()
+        };
+        contract
+            .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .unwrap();
+
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: String::from("deed"),
+            owner: String::from("demeter"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+        let approve_msg = ExecuteMsg::Approve {
+            spender: String::from("escrow-agent"),
+            token_id: String::from("deed"),
+            expires: None,
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("demeter", &[]),
+                approve_msg,
+            )
+            .unwrap();
+
+        let send_msg = ExecuteMsg::SendNft {
+            contract: String::from("escrow"),
+            token_id: String::from("deed"),
+            msg: to_binary("lock").unwrap(),
+            safe_send: false,
+            wrap: true,
+            with_owner: false,
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("demeter", &[]),
+                send_msg,
+            )
+            .unwrap();
+
+        let token = contract.tokens.load(&deps.storage, "deed").unwrap();
+        assert_eq!(token.owner, "escrow");
+        let approved = contract
+            .tokens_approved_to(
+                deps.as_ref(),
+                mock_env(),
+                String::from("escrow-agent"),
+                None,
+                None,
+            )
+            .unwrap()
+            .tokens;
+        if keep_approvals_on_send {
+            // the agent can still move the token back out of escrow
+            assert_eq!(token.approvals.len(), 1);
+            assert_eq!(approved, vec!["deed"]);
+            let transfer_msg = ExecuteMsg::TransferNft {
+                recipient: String::from("demeter"),
+                token_id: String::from("deed"),
+            };
+            contract
+                .execute(
+                    deps.as_mut(),
+                    mock_env(),
+                    mock_info("escrow-agent", &[]),
+                    transfer_msg,
+                )
+                .unwrap();
+        } else {
+            // the default, like any transfer
+            assert!(token.approvals.is_empty());
+            assert!(approved.is_empty());
+        }
+    }
+}
+
+#[test]
+fn limited_editions_carry_their_serial() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = |token_id: &str, edition: u32, edition_of: u32| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("collector"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: Some(edition),
+            edition_of: Some(edition_of),
+        })
+    };
+
+    // 3 of 50 is stored and shows up in NftInfo
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_msg("print-3", 3, 50),
+        )
+        .unwrap();
+    let info = contract
+        .nft_info(deps.as_ref(), "print-3".to_string())
+        .unwrap();
+    assert_eq!(info.edition, Some(3));
+    assert_eq!(info.edition_of, Some(50));
+
+    // serials past the edition size, or 0, are refused
+    for (token_id, edition, edition_of) in [("print-51", 51, 50), ("print-0", 0, 50)] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MINTER, &[]),
+                mint_msg(token_id, edition, edition_of),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidEdition {});
+        assert!(!contract.tokens.has(&deps.storage, token_id));
+    }
+}
+
+#[test]
+fn query_owner_tokens_approved_to_spender() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let tokens = [
+        ("amphora", "poseidon"),
+        ("helm", "hades"),
+        ("lyre", "poseidon"),
+        ("net", "poseidon"),
+        ("trident", "poseidon"),
+    ];
+    for (token_id, owner) in tokens {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    // "lyre" goes to another spender, "net" only until height 12400
+    let approvals = [
+        ("amphora", "poseidon", "agora", None),
+        ("helm", "hades", "agora", None),
+        ("lyre", "poseidon", "delphi", None),
+        (
+            "net",
+            "poseidon",
+            "agora",
+            Some(Expiration::AtHeight(12400)),
+        ),
+        ("trident", "poseidon", "agora", None),
+    ];
+    for (token_id, owner, spender, expires) in approvals {
+        let approve_msg = ExecuteMsg::Approve {
+            spender: spender.to_string(),
+            token_id: token_id.to_string(),
+            expires,
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(owner, &[]),
+                approve_msg,
+            )
+            .unwrap();
+    }
+
+    let approved_to = |deps: Deps, env, start_after: Option<&str>, limit| {
+        let query_msg: QueryMsg<Empty> = QueryMsg::OwnerTokensApprovedTo {
+            owner: String::from("poseidon"),
+            spender: String::from("agora"),
+            start_after: start_after.map(String::from),
+            limit,
+        };
+        let res: cw721::TokensResponse =
+            from_binary(&contract.query(deps, env, query_msg).unwrap()).unwrap();
+        res.tokens
+    };
+
+    // only poseidon's tokens, and only those approved to agora
+    assert_eq!(
+        approved_to(deps.as_ref(), mock_env(), None, None),
+        vec!["amphora", "net", "trident"]
+    );
+    // pages count matches, not scanned tokens
+    assert_eq!(
+        approved_to(deps.as_ref(), mock_env(), Some("amphora"), Some(1)),
+        vec!["net"]
+    );
+
+    // expired approvals are filtered against the current block
+    let mut env = mock_env();
+    env.block.height = 12400;
+    assert_eq!(
+        approved_to(deps.as_ref(), env, None, None),
+        vec!["amphora", "trident"]
+    );
+}
+
+#[test]
+fn max_supply_counts_burns_as_configured() {
+    for burns_free_supply in [false, true] {
+        let mut deps = mock_dependencies();
+        let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        let msg = InstantiateMsg {
+            name: CONTRACT_NAME.to_string(),
+            symbol: SYMBOL.to_string(),
+            minter: String::from(MINTER),
+            max_supply: Some(2),
+            burns_free_supply,
+            ..Default::default()
+        };
+        contract
+            .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .unwrap();
+
+        let mint = |deps: DepsMut, token_id: &str| {
+            let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+                token_id: token_id.to_string(),
+                owner: String::from("collector"),
+                token_uri: None,
+                extension: None,
+                metadata_hash: None,
+                edition: None,
+                edition_of: None,
+            });
+            contract.execute(deps, mock_env(), mock_info(MINTER, &[]), mint_msg)
+        };
+        mint(deps.as_mut(), "first").unwrap();
+        mint(deps.as_mut(), "second").unwrap();
+        let err = mint(deps.as_mut(), "third").unwrap_err();
+        assert_eq!(err, ContractError::MaxSupplyReached { max_supply: 2 });
+
+        let burn_msg = ExecuteMsg::Burn {
+            token_id: String::from("first"),
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("collector", &[]),
+                burn_msg,
+            )
+            .unwrap();
+
+        // a burn only makes room when the cap counts live tokens
+        let res = mint(deps.as_mut(), "third");
+        if burns_free_supply {
+            res.unwrap();
+        } else {
+            assert_eq!(
+                res.unwrap_err(),
+                ContractError::MaxSupplyReached { max_supply: 2 }
+            );
+        }
+    }
+}
+
+#[test]
+fn send_nft_can_skip_the_receive_nft_wrapper() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    for token_id in ["wrapped", "raw"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    // leaving wrap out keeps the standard encoding
+    let send_msg: ExecuteMsg<Extension, Empty> = cosmwasm_std::from_slice(
+        br#"{"send_nft":{"contract":"vault","token_id":"wrapped","msg":"aGk="}}"#,
+    )
+    .unwrap();
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("venus", &[]), send_msg)
+        .unwrap();
+    let payload = |token_id: &str| Cw721ReceiveMsg {
+        sender: String::from("venus"),
+        token_id: token_id.to_string(),
+        msg: Binary::from(b"hi"),
+    };
+    let sent_msg = |res: &Response| match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => msg.clone(),
+        m => panic!("Unexpected message type: {:?}", m),
+    };
+    assert_eq!(sent_msg(&res), payload("wrapped").into_binary().unwrap());
+    let json = String::from_utf8(sent_msg(&res).to_vec()).unwrap();
+    assert!(json.starts_with(r#"{"receive_nft":{"sender":"venus""#));
+
+    // wrap: false sends the bare struct
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("vault"),
+        token_id: String::from("raw"),
+        msg: Binary::from(b"hi"),
+        safe_send: false,
+        wrap: false,
+        with_owner: false,
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("venus", &[]), send_msg)
+        .unwrap();
+    assert_eq!(
+        sent_msg(&res),
+        payload("raw").into_binary_unwrapped().unwrap()
+    );
+    let json = String::from_utf8(sent_msg(&res).to_vec()).unwrap();
+    assert!(json.starts_with(r#"{"sender":"venus""#));
+    let raw: Cw721ReceiveMsg = from_binary(&sent_msg(&res)).unwrap();
+    assert_eq!(raw, payload("raw"));
+    assert_eq!(
+        res.messages[0].msg,
+        payload("raw").into_cosmos_msg_raw("vault").unwrap()
+    );
+}
+
+#[test]
+fn allowlisted_addresses_mint_free_while_public_pays() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        mint_prices: Some(coins(100, "ujuno")),
+        allowlist_free_mints: Some(1),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    for execute_msg in [
+        ExecuteMsg::SetMintAllowed {
+            address: String::from("early"),
+            allowed: true,
+        },
+        ExecuteMsg::SetPhase {
+            phase: Phase::Public,
+        },
+    ] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", &[]),
+                execute_msg,
+            )
+            .unwrap();
+    }
+
+    let mint_msg = |token_id: &str, owner: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+
+    // a listed address mints without paying, and nothing is sent on to the owner
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("early", &[]),
+            mint_msg("first", "early"),
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+
+    // the public has to pay
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("late", &[]),
+            mint_msg("second", "late"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoAcceptedPaymentProvided {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("late", &coins(100, "ujuno")),
+            mint_msg("second", "late"),
+        )
+        .unwrap();
+
+    // and so does a listed address once its free mints are used up
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("early", &[]),
+            mint_msg("third", "early"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoAcceptedPaymentProvided {});
+}
+
+#[test]
+fn query_version() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let query_msg: QueryMsg<Empty> = QueryMsg::Version {};
+    let res: cw2::ContractVersion = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), query_msg)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.contract, CW2_NAME);
+    assert_eq!(res.version, CW2_VERSION);
+}
+
+#[test]
+fn max_per_owner_limits_mints_and_transfers() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        max_per_owner: Some(2),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint = |deps: DepsMut, token_id: &str, owner: &str| {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract.execute(deps, mock_env(), mock_info(MINTER, &[]), mint_msg)
+    };
+
+    // up to the limit is fine
+    mint(deps.as_mut(), "whale-1", "whale").unwrap();
+    mint(deps.as_mut(), "whale-2", "whale").unwrap();
+    mint(deps.as_mut(), "minnow-1", "minnow").unwrap();
+
+    // one more for the same owner is not
+    let err = mint(deps.as_mut(), "whale-3", "whale").unwrap_err();
+    assert_eq!(err, ContractError::OwnerLimitReached { max_per_owner: 2 });
+
+    // nor is a transfer that would push them over
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("whale"),
+        token_id: String::from("minnow-1"),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minnow", &[]),
+            transfer_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::OwnerLimitReached { max_per_owner: 2 });
+
+    // once they pass one on, there is room again
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("minnow"),
+        token_id: String::from("whale-1"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("whale", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    mint(deps.as_mut(), "whale-3", "whale").unwrap();
+}
+
+#[test]
+fn recent_tokens_are_newest_first() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // token_ids that sort differently from the order they are minted in
+    for token_id in ["zeta", "alpha", "mid", "beta"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("curator"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    let recent = |deps: Deps, start_after: Option<u64>, limit: Option<u32>| {
+        let query_msg: QueryMsg<Empty> = QueryMsg::RecentTokens { start_after, limit };
+        let res: Vec<(u64, String)> =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res
+    };
+    let entry = |seq: u64, token_id: &str| (seq, token_id.to_string());
+
+    assert_eq!(
+        recent(deps.as_ref(), None, None),
+        vec![
+            entry(4, "beta"),
+            entry(3, "mid"),
+            entry(2, "alpha"),
+            entry(1, "zeta")
+        ]
+    );
+    // pages continue from the last position seen
+    assert_eq!(
+        recent(deps.as_ref(), None, Some(2)),
+        vec![entry(4, "beta"), entry(3, "mid")]
+    );
+    assert_eq!(
+        recent(deps.as_ref(), Some(3), Some(2)),
+        vec![entry(2, "alpha"), entry(1, "zeta")]
+    );
+
+    // burned tokens drop out of the index
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("mid"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("curator", &[]),
+            burn_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        recent(deps.as_ref(), None, Some(2)),
+        vec![entry(4, "beta"), entry(2, "alpha")]
+    );
+}
+
+#[test]
+fn owner_admin_burns_with_a_reason() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: String::from("contraband"),
+        owner: String::from("smuggler"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let admin_burn = |reason: &str| ExecuteMsg::AdminBurn {
+        token_id: String::from("contraband"),
+        reason: reason.to_string(),
+    };
+
+    // only the owner, not even the minter or the token's holder
+    for sender in [MINTER, "smuggler"] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                admin_burn("court order 42"),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotContractOwner {});
+    }
+
+    // and never without a reason
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            admin_burn(" "),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingBurnReason {});
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            admin_burn("court order 42"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "admin_burn"),
+            attr("sender", "creator"),
+            attr("previous_owner", "smuggler"),
+            attr("token_id", "contraband"),
+            attr("reason", "court order 42"),
+        ]
+    );
+    assert!(!contract.tokens.has(&deps.storage, "contraband"));
+    let stats = contract.stats(deps.as_ref()).unwrap();
+    assert_eq!((stats.supply, stats.burned_total), (0, 1));
+}
+
+#[test]
+fn query_status_follows_toggles() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let status = |deps: Deps| -> StatusResponse {
+        let query_msg: QueryMsg<Empty> = QueryMsg::Status {};
+        from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap()
+    };
+    assert_eq!(
+        status(deps.as_ref()),
+        StatusResponse {
+            transfers_enabled: true,
+            send_enabled: true,
+        }
+    );
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetTransfersEnabled { enabled: false },
+        )
+        .unwrap();
+    assert_eq!(
+        status(deps.as_ref()),
+        StatusResponse {
+            transfers_enabled: false,
+            send_enabled: true,
+        }
+    );
+}
+
+#[test]
+fn transfer_hook_can_be_repointed_and_removed() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: String::from("horn"),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let set_hook = |hook: Option<&str>, required| ExecuteMsg::SetTransferHook {
+        hook: hook.map(String::from),
+        required,
+    };
+    let transfer = |recipient: &str| ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: String::from("horn"),
+    };
+    let hook_msg = |previous_owner: &str, recipient: &str, contract: &str| {
+        TransferHookMsg {
+            token_id: String::from("horn"),
+            previous_owner: previous_owner.to_string(),
+            recipient: recipient.to_string(),
+        }
+        .into_cosmos_msg(contract)
+        .unwrap()
+    };
+
+    // only the owner can set it
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            set_hook(Some("ledger"), true),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+
+    // a required hook is a plain submessage, so its failure reverts the transfer
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            set_hook(Some("ledger"), true),
+        )
+        .unwrap();
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer("mars"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(hook_msg("venus", "mars", "ledger"))]
+    );
+
+    // repointing it sends the next transfer's hook to the new contract, failures ignored
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            set_hook(Some("archive"), false),
+        )
+        .unwrap();
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("mars", &[]),
+            transfer("venus"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_error(
+            hook_msg("mars", "venus", "archive"),
+            ReplyId::TransferHook.id()
+        )]
+    );
+    let reply = Reply {
+        id: ReplyId::TransferHook.id(),
+        result: SubMsgResult::Err("archive is full".into()),
+    };
+    let res = contract.reply(deps.as_mut(), mock_env(), reply).unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "transfer_hook_failed")]);
+
+    // and removing it stops the messages
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            set_hook(None, false),
+        )
+        .unwrap();
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer("mars"),
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn token_tags_set_overwrite_remove_and_cap() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: String::from("lyre"),
+        owner: String::from("orpheus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let set_tag = |key: &str, value: &str| ExecuteMsg::SetTokenTag {
+        token_id: String::from("lyre"),
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let tags = |deps: Deps| -> Vec<(String, String)> {
+        let query_msg: QueryMsg<Empty> = QueryMsg::TokenTags {
+            token_id: String::from("lyre"),
+        };
+        let res: TokenTagsResponse =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.tags
+    };
+
+    // not even the holder can tag
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &[]),
+            set_tag("tier", "gold"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinterOrOwner {});
+
+    // minters and the contract owner can
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            set_tag("tier", "gold"),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            set_tag("origin", "thrace"),
+        )
+        .unwrap();
+    assert_eq!(
+        tags(deps.as_ref()),
+        vec![
+            (String::from("origin"), String::from("thrace")),
+            (String::from("tier"), String::from("gold")),
+        ]
+    );
+
+    // setting a key again overwrites it
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            set_tag("tier", "platinum"),
+        )
+        .unwrap();
+    assert_eq!(
+        tags(deps.as_ref())[1],
+        (String::from("tier"), String::from("platinum"))
+    );
+
+    let remove_msg = ExecuteMsg::RemoveTokenTag {
+        token_id: String::from("lyre"),
+        key: String::from("origin"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            remove_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        tags(deps.as_ref()),
+        vec![(String::from("tier"), String::from("platinum"))]
+    );
+
+    // fill up to the cap, after which only new keys are refused
+    for i in 1..32 {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MINTER, &[]),
+                set_tag(&format!("verse{}", i), "sung"),
+            )
+            .unwrap();
+    }
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            set_tag("encore", "sung"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TooManyTags { max: 32 });
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            set_tag("tier", "gold"),
+        )
+        .unwrap();
+    assert_eq!(tags(deps.as_ref()).len(), 32);
+}
+
+#[test]
+fn mint_price_follows_curve() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        mint_price_curve: Some(MintPriceCurve {
+            base_price: coin(100, "ujuno"),
+            step: Uint128::new(10),
+        }),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    // minters don't pay, so the buyers here mint in the public phase
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetPhase {
+                phase: Phase::Public,
+            },
+        )
+        .unwrap();
+
+    let mint_msg = |token_id: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("icarus"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+    let current_price = |deps: Deps| -> Vec<Coin> {
+        let query_msg: QueryMsg<Empty> = QueryMsg::CurrentMintPrice {};
+        let res: CurrentMintPriceResponse =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.prices
+    };
+
+    // each mint pays what the query said, and the next one costs a step more
+    for (token_id, price) in [("wax", 100), ("feather", 110), ("sun", 120)] {
+        assert_eq!(current_price(deps.as_ref()), coins(price, "ujuno"));
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("icarus", &coins(price - 1, "ujuno")),
+                mint_msg(token_id),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::NoAcceptedPaymentProvided {});
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("icarus", &coins(price, "ujuno")),
+                mint_msg(token_id),
+            )
+            .unwrap();
+    }
+
+    // the price follows the supply, so a burn brings it back down
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("icarus", &[]),
+            ExecuteMsg::Burn {
+                token_id: String::from("sun"),
+            },
+        )
+        .unwrap();
+    assert_eq!(current_price(deps.as_ref()), coins(120, "ujuno"));
+}
+
+#[test]
+fn overpaid_mint_is_refunded() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        mint_prices: Some(coins(100, "ujuno")),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    // minters don't pay, so the buyers here mint in the public phase
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetPhase {
+                phase: Phase::Public,
+            },
+        )
+        .unwrap();
+
+    let mint_msg = |token_id: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("midas"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+
+    // the excess of the payment denom comes back, other denoms still go to the owner
+    let funds = vec![coin(130, "ujuno"), coin(5, "uatom")];
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("midas", &funds),
+            mint_msg("gold"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("creator"),
+                amount: vec![coin(100, "ujuno"), coin(5, "uatom")],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("midas"),
+                amount: coins(30, "ujuno"),
+            }),
+        ]
+    );
+
+    // the price denom attached more than once is added up, and refunded in one coin
+    let funds = vec![coin(120, "ujuno"), coin(30, "ujuno")];
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("midas", &funds),
+            mint_msg("bronze"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("creator"),
+                amount: coins(100, "ujuno"),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("midas"),
+                amount: coins(50, "ujuno"),
+            }),
+        ]
+    );
+
+    // paying exactly the price refunds nothing
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("midas", &coins(100, "ujuno")),
+            mint_msg("silver"),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("creator"),
+            amount: coins(100, "ujuno"),
+        })]
+    );
+}
+
+#[test]
+fn token_history_records_ownership_changes() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        track_history: true,
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: String::from("fleece"),
+        owner: String::from("jason"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let mut env = mock_env();
+    env.block.height += 5;
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("medea"),
+        token_id: String::from("fleece"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("jason", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    env.block.height += 5;
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("fleece"),
+    };
+    contract
+        .execute(deps.as_mut(), env, mock_info("medea", &[]), burn_msg)
+        .unwrap();
+
+    let history = |start_after, limit| -> Vec<(u64, HistoryEntry)> {
+        let query_msg: QueryMsg<Empty> = QueryMsg::TokenHistory {
+            token_id: String::from("fleece"),
+            start_after,
+            limit,
+        };
+        from_binary(
+            &contract
+                .query(deps.as_ref(), mock_env(), query_msg)
+                .unwrap(),
+        )
+        .unwrap()
+    };
+    let entry = |action: &str, from: Option<&str>, to: Option<&str>, height| HistoryEntry {
+        action: action.to_string(),
+        from: from.map(Addr::unchecked),
+        to: to.map(Addr::unchecked),
+        height,
+    };
+    let height = mock_env().block.height;
+
+    // in order, and still there after the burn
+    assert_eq!(
+        history(None, None),
+        vec![
+            (1, entry("mint", None, Some("jason"), height)),
+            (
+                2,
+                entry("transfer", Some("jason"), Some("medea"), height + 5)
+            ),
+            (3, entry("burn", Some("medea"), None, height + 10)),
+        ]
+    );
+    assert_eq!(
+        history(Some(1), Some(1)),
+        vec![(
+            2,
+            entry("transfer", Some("jason"), Some("medea"), height + 5)
+        )]
+    );
+
+    // collections that don't opt in keep nothing
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: String::from("fleece"),
+        owner: String::from("jason"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    assert!(contract
+        .token_history(deps.as_ref(), String::from("fleece"), None, None)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn approvals_wait_out_the_delay() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        approval_delay_blocks: 10,
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: String::from("apple"),
+        owner: String::from("paris"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("eris"),
+        token_id: String::from("apple"),
+        expires: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("paris", &[]),
+            approve_msg,
+        )
+        .unwrap();
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("eris"),
+        token_id: String::from("apple"),
+    };
+    let active_from = mock_env().block.height + 10;
+
+    // a freshly-created approval can't be used yet, up to the block before it activates,
+    // and the approval queries don't count it either
+    for height in [mock_env().block.height, active_from - 1] {
+        let mut env = mock_env();
+        env.block.height = height;
+        let approved = contract
+            .tokens_approved_to(deps.as_ref(), env.clone(), "eris".to_string(), None, None)
+            .unwrap();
+        assert!(approved.tokens.is_empty());
+        let approved = contract
+            .owner_tokens_approved_to(
+                deps.as_ref(),
+                env.clone(),
+                "paris".to_string(),
+                "eris".to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(approved.tokens.is_empty());
+        let count = contract
+            .token_approval_count(deps.as_ref(), env.clone(), "apple".to_string())
+            .unwrap();
+        assert_eq!(count.count, 0);
+        contract
+            .approval(
+                deps.as_ref(),
+                env.clone(),
+                "apple".to_string(),
+                "eris".to_string(),
+                false,
+            )
+            .unwrap_err();
+        let approvals = contract
+            .approvals(deps.as_ref(), env.clone(), "apple".to_string(), false)
+            .unwrap();
+        assert!(approvals.approvals.is_empty());
+        let owner = contract
+            .owner_of(deps.as_ref(), env.clone(), "apple".to_string(), false)
+            .unwrap();
+        assert!(owner.approvals.is_empty());
+        let info = contract
+            .all_nft_info(deps.as_ref(), env.clone(), "apple".to_string(), false)
+            .unwrap();
+        assert!(info.access.approvals.is_empty());
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                env,
+                mock_info("eris", &[]),
+                transfer_msg.clone(),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::ApprovalNotYetActive { active_from });
+    }
+
+    // once the delay has passed it works as usual
+    let mut env = mock_env();
+    env.block.height = active_from;
+    let approved = contract
+        .tokens_approved_to(deps.as_ref(), env.clone(), "eris".to_string(), None, None)
+        .unwrap();
+    assert_eq!(approved.tokens, vec!["apple".to_string()]);
+    let count = contract
+        .token_approval_count(deps.as_ref(), env.clone(), "apple".to_string())
+        .unwrap();
+    assert_eq!(count.count, 1);
+    let eris_approval = Approval {
+        spender: String::from("eris"),
+        expires: Expiration::Never {},
+    };
+    let approval = contract
+        .approval(
+            deps.as_ref(),
+            env.clone(),
+            "apple".to_string(),
+            "eris".to_string(),
+            false,
+        )
+        .unwrap();
+    assert_eq!(approval.approval, eris_approval);
+    let owner = contract
+        .owner_of(deps.as_ref(), env.clone(), "apple".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.approvals, vec![eris_approval]);
+    contract
+        .execute(deps.as_mut(), env, mock_info("eris", &[]), transfer_msg)
+        .unwrap();
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), String::from("apple"), false)
+        .unwrap();
+    assert_eq!(owner.owner, "eris");
+}
+
+#[test]
+fn send_can_be_disabled_while_transfers_still_work() {
+    let mut deps = mock_dependencies();
+    mock_contracts(&mut deps.querier, &["exchange"]);
+    let contract = setup_contract(deps.as_mut());
+
+    for token_id in ["kept", "moved"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("venus"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    // only the owner may turn sending off
+    let disable_msg = ExecuteMsg::SetSendEnabled { enabled: false };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            disable_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            disable_msg,
+        )
+        .unwrap();
+
+    let status: StatusResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::Status {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(!status.send_enabled);
+    assert!(status.transfers_enabled);
+
+    // sends are refused, safe or not
+    for safe_send in [false, true] {
+        let send_msg = ExecuteMsg::SendNft {
+            contract: String::from("exchange"),
+            token_id: String::from("kept"),
+            msg: to_binary("sell").unwrap(),
+            safe_send,
+            wrap: true,
+            with_owner: false,
+        };
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), mock_info("venus", &[]), send_msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::SendDisabled {});
+    }
+
+    // but a plain transfer goes through
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("mars"),
+        token_id: String::from("moved"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "moved".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "mars");
+
+    // turning it back on lets sends through again
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetSendEnabled { enabled: true },
+        )
+        .unwrap();
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("exchange"),
+        token_id: String::from("kept"),
+        msg: to_binary("sell").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("venus", &[]), send_msg)
+        .unwrap();
+}
+
+#[test]
+fn mint_msg_validate_rejects_each_bad_field() {
+    let valid = MintMsg::<Extension> {
+        token_id: String::from("nebula-7"),
+        owner: String::from("anyone"),
+        token_uri: Some(String::from("ipfs://nebula/7.json")),
+        extension: None,
+        metadata_hash: Some("ab".repeat(32)),
+        edition: Some(7),
+        edition_of: Some(10),
+    };
+    valid.validate(16, 64).unwrap();
+    // the owner isn't the validator's business, that needs the api
+    MintMsg {
+        owner: String::new(),
+        ..valid.clone()
+    }
+    .validate(16, 64)
+    .unwrap();
+
+    // token_id: empty, too long, or with disallowed characters
+    for token_id in ["", "nebula-7-and-then-some", "nebula 7", "nebula/7"] {
+        let err = MintMsg {
+            token_id: token_id.to_string(),
+            ..valid.clone()
+        }
+        .validate(16, 64)
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidTokenId {
+                token_id: token_id.to_string()
+            }
+        );
+    }
+
+    // token_uri: blank, or longer than allowed
+    for token_uri in [" ".to_string(), format!("ipfs://{}", "n".repeat(64))] {
+        let err = MintMsg {
+            token_uri: Some(token_uri),
+            ..valid.clone()
+        }
+        .validate(16, 64)
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidTokenUri { max: 64 });
+    }
+    // but leaving it out is fine
+    MintMsg {
+        token_uri: None,
+        ..valid.clone()
+    }
+    .validate(16, 64)
+    .unwrap();
+
+    // metadata_hash: too short, or not hex
+    for hash in ["ab".repeat(31), "zz".repeat(32)] {
+        let err = MintMsg {
+            metadata_hash: Some(hash),
+            ..valid.clone()
+        }
+        .validate(16, 64)
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidMetadataHash {});
+    }
+
+    // edition: zero, or past the size of the edition
+    for (edition, edition_of) in [(Some(0), None), (None, Some(0)), (Some(11), Some(10))] {
+        let err = MintMsg {
+            edition,
+            edition_of,
+            ..valid.clone()
+        }
+        .validate(16, 64)
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidEdition {});
+    }
+}
+
+#[test]
+fn royalty_exempt_tokens_skip_the_transfer_fee() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        transfer_fee: Some(coin(10, "ujuno")),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    for token_id in ["giveaway", "regular"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("promoter"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+
+    // only a minter may exempt a token, not even its owner
+    let exempt_msg = ExecuteMsg::SetRoyaltyExempt {
+        token_id: "giveaway".to_string(),
+        exempt: true,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("promoter", &[]),
+            exempt_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            exempt_msg,
+        )
+        .unwrap();
+
+    // the exempt token moves for free, with no fee payment
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("promoter", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("winner"),
+                token_id: "giveaway".to_string(),
+            },
+        )
+        .unwrap();
+    assert!(res.messages.is_empty());
+
+    // the other one still has to pay
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("buyer"),
+        token_id: "regular".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("promoter", &[]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientTransferFee {
+            fee: coin(10, "ujuno"),
+        }
+    );
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("promoter", &coins(10, "ujuno")),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("creator"),
+            amount: coins(10, "ujuno"),
+        })]
+    );
+
+    // lifting the exemption brings the fee back
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::SetRoyaltyExempt {
+                token_id: "giveaway".to_string(),
+                exempt: false,
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("winner", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("promoter"),
+                token_id: "giveaway".to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientTransferFee {
+            fee: coin(10, "ujuno"),
+        }
+    );
+}
+
+#[test]
+fn reply_router_handles_success_and_failure_per_id() {
+    let mut deps = mock_dependencies();
+    mock_contracts(&mut deps.querier, &["underworld"]);
+    let contract = setup_contract(deps.as_mut());
+    let success = |id: ReplyId| Reply {
+        id: id.id(),
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let failure = |id: ReplyId| Reply {
+        id: id.id(),
+        result: SubMsgResult::Err("receiver said no".into()),
+    };
+
+    for token_id in ["plain", "rejected", "safe"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("orpheus"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+    let send = |token_id: &str, safe_send: bool| ExecuteMsg::SendNft {
+        contract: String::from("underworld"),
+        token_id: token_id.to_string(),
+        msg: to_binary("descend").unwrap(),
+        safe_send,
+        wrap: true,
+        with_owner: false,
+    };
+
+    // SendNft: a success only clears the pending send, a failure names the receiver
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &[]),
+            send("plain", false),
+        )
+        .unwrap();
+    let res = contract
+        .reply(deps.as_mut(), mock_env(), success(ReplyId::SendNft))
+        .unwrap();
+    assert_eq!(res, Response::new());
+    assert!(contract.pending_sends.is_empty(&deps.storage));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &[]),
+            send("rejected", false),
+        )
+        .unwrap();
+    let err = contract
+        .reply(deps.as_mut(), mock_env(), failure(ReplyId::SendNft))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ReceiverRejected {
+            contract: String::from("underworld"),
+        }
+    );
+
+    // SafeSendNft: a success keeps the transfer and drops the backup and pending send
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &[]),
+            send("safe", true),
+        )
+        .unwrap();
+    let res = contract
+        .reply(deps.as_mut(), mock_env(), success(ReplyId::SafeSendNft))
+        .unwrap();
+    assert_eq!(res, Response::new());
+    assert!(contract.safe_send_backups.is_empty(&deps.storage));
+    assert!(contract.pending_sends.is_empty(&deps.storage));
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "safe".into(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "underworld");
+
+    // while a failure hands the token back
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("underworld", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("orpheus"),
+                token_id: "safe".to_string(),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("orpheus", &[]),
+            send("safe", true),
+        )
+        .unwrap();
+    let res = contract
+        .reply(deps.as_mut(), mock_env(), failure(ReplyId::SafeSendNft))
+        .unwrap();
+    assert_eq!(res.attributes[0], attr("action", "send_nft_rolled_back"));
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "safe".into(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "orpheus");
+
+    // TransferHook: neither outcome fails the transaction, a failure is just noted
+    let res = contract
+        .reply(deps.as_mut(), mock_env(), success(ReplyId::TransferHook))
+        .unwrap();
+    assert_eq!(res, Response::new());
+    let res = contract
+        .reply(deps.as_mut(), mock_env(), failure(ReplyId::TransferHook))
+        .unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "transfer_hook_failed")]);
+
+    // ids are stable, and anything else is refused
+    for id in [
+        ReplyId::SendNft,
+        ReplyId::SafeSendNft,
+        ReplyId::TransferHook,
+    ] {
+        assert_eq!(ReplyId::try_from(id.id()).unwrap(), id);
+    }
+    let err = contract
+        .reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: 99,
+                result: SubMsgResult::Err("who?".into()),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::UnknownReplyId { id: 99 });
+}
+
+#[test]
+fn next_token_id_matches_mint_next() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let next_token_id = |deps: Deps| -> String {
+        let query_msg: QueryMsg<Empty> = QueryMsg::NextTokenId {};
+        let res: NextTokenIdResponse =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.token_id
+    };
+    let mint_next = |deps: DepsMut| -> String {
+        let mint_next_msg = ExecuteMsg::MintNext {
+            owner: String::from("athena"),
+            token_uri: None,
+            extension: None,
+        };
+        let res = contract
+            .execute(deps, mock_env(), mock_info(MINTER, &[]), mint_next_msg)
+            .unwrap();
+        res.attributes
+            .iter()
+            .find(|attr| attr.key == "token_id")
+            .unwrap()
+            .value
+            .clone()
+    };
+
+    assert_eq!(next_token_id(deps.as_ref()), "1");
+    for _ in 0..2 {
+        let predicted = next_token_id(deps.as_ref());
+        assert_eq!(mint_next(deps.as_mut()), predicted);
+    }
+
+    // burns don't hand an id out again, and neither do manual mints
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("athena", &[]),
+            ExecuteMsg::Burn {
+                token_id: "2".to_string(),
+            },
+        )
+        .unwrap();
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "owl".to_string(),
+        owner: String::from("athena"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let predicted = next_token_id(deps.as_ref());
+    assert_eq!(predicted, "4");
+    assert_eq!(mint_next(deps.as_mut()), predicted);
+}
+
+#[test]
+fn tokens_retire_after_max_transfers() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "relic".to_string(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // only a minter sets the limit
+    let limit_msg = ExecuteMsg::SetMaxTransfers {
+        token_id: "relic".to_string(),
+        max_transfers: Some(2),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            limit_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), limit_msg)
+        .unwrap();
+
+    // two transfers are fine
+    let transfer = |recipient: &str| ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: "relic".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer("mars"),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("mars", &[]),
+            transfer("jupiter"),
+        )
+        .unwrap();
+    assert_eq!(
+        contract
+            .tokens
+            .load(&deps.storage, "relic")
+            .unwrap()
+            .transfer_count,
+        2
+    );
+
+    // the third, or a send, is refused
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jupiter", &[]),
+            transfer("saturn"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TransferLimitReached { max: 2 });
+    let send_msg = ExecuteMsg::SendNft {
+        contract: String::from("museum"),
+        token_id: "relic".to_string(),
+        msg: to_binary("display").unwrap(),
+        safe_send: false,
+        wrap: true,
+        with_owner: false,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jupiter", &[]),
+            send_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TransferLimitReached { max: 2 });
+
+    // but the retired token can still be burned
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jupiter", &[]),
+            ExecuteMsg::Burn {
+                token_id: "relic".to_string(),
+            },
+        )
+        .unwrap();
+}
+
+#[test]
+fn operators_can_be_disabled_at_instantiate() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        operators_enabled: Some(false),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "vault".to_string(),
+        owner: String::from("demeter"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // no blanket operator grants
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: String::from("hermes"),
+        expires: None,
+        min_token_height: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("demeter", &[]),
+            approve_all_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::OperatorsDisabled {});
+
+    // a per-token approval still works, and lets the spender move the token
+    let approve_msg = ExecuteMsg::Approve {
+        spender: String::from("hermes"),
+        token_id: "vault".to_string(),
+        expires: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("demeter", &[]),
+            approve_msg,
+        )
+        .unwrap();
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("persephone"),
+        token_id: "vault".to_string(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hermes", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "vault".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "persephone");
+}
+
+#[test]
+fn mint_and_send_mints_into_the_contract() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_and_send = ExecuteMsg::MintAndSend {
+        mint: MintMsg::<Extension> {
+            token_id: "sword".to_string(),
+            // replaced by the receiving contract
+            owner: String::from("player"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        },
+        contract: String::from("vault"),
+        msg: to_binary("deposit for player").unwrap(),
+    };
+
+    // minting rules still apply
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("player", &[]),
+            mint_and_send.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_and_send,
+        )
+        .unwrap();
+
+    // the contract owns the new token
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "sword".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "vault");
+    assert_eq!(contract.token_count(&deps.storage).unwrap(), 1);
+
+    // and is told about it in the same transaction
+    let expected = Cw721ReceiveMsg {
+        sender: String::from(MINTER),
+        token_id: "sword".to_string(),
+        msg: to_binary("deposit for player").unwrap(),
+    };
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_always(
+            expected.into_cosmos_msg("vault").unwrap(),
+            ReplyId::SendNft.id()
+        )]
+    );
+    assert!(res.attributes.contains(&attr("recipient", "vault")));
+
+    // once the vault took it, the reply leaves nothing pending
+    let accepted = Reply {
+        id: ReplyId::SendNft.id(),
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    assert!(!contract.pending_sends.is_empty(&deps.storage));
+    contract.reply(deps.as_mut(), mock_env(), accepted).unwrap();
+    assert!(contract.pending_sends.is_empty(&deps.storage));
+}
+
+#[test]
+fn prefixed_instances_keep_separate_storage() {
+    let mut deps = mock_dependencies();
+    let (swords_keys, shields_keys) = (PrefixedKeys::new("swords_"), PrefixedKeys::new("shields_"));
+    let swords = Cw721Contract::<Extension, Empty, Empty, Empty>::with_prefix(&swords_keys);
+    let shields = Cw721Contract::<Extension, Empty, Empty, Empty>::with_prefix(&shields_keys);
+
+    for (contract, name, minter) in [
+        (&swords, "Swords", "smith"),
+        (&shields, "Shields", "armorer"),
+    ] {
+        let msg = InstantiateMsg {
+            name: name.to_string(),
+            symbol: name.to_uppercase(),
+            minter: minter.to_string(),
+            ..Default::default()
+        };
+        contract
+            .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .unwrap();
+    }
+
+    // the same token_id can exist in both, with different owners
+    let mint = |owner: &str| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: "1".to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        })
+    };
+    swords
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("smith", &[]),
+            mint("arthur"),
+        )
+        .unwrap();
+    // each instance only knows its own minter
+    let err = shields
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("smith", &[]),
+            mint("lancelot"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+    shields
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("armorer", &[]),
+            mint("lancelot"),
+        )
+        .unwrap();
+
+    let owner_of = |contract: &Cw721Contract<Extension, Empty, Empty, Empty>| {
+        contract
+            .owner_of(deps.as_ref(), mock_env(), "1".to_string(), false)
+            .unwrap()
+            .owner
+    };
+    assert_eq!(owner_of(&swords), "arthur");
+    assert_eq!(owner_of(&shields), "lancelot");
+    assert_eq!(swords.contract_info(deps.as_ref()).unwrap().name, "Swords");
+    assert_eq!(
+        shields.contract_info(deps.as_ref()).unwrap().name,
+        "Shields"
+    );
+
+    // the owner indexes are namespaced too
+    let tokens = swords
+        .tokens(deps.as_ref(), "lancelot".to_string(), None, None)
+        .unwrap();
+    assert!(tokens.tokens.is_empty());
+
+    // and an unprefixed instance sees neither
+    let unprefixed = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    assert_eq!(unprefixed.token_count(&deps.storage).unwrap(), 0);
+    assert_eq!(swords.token_count(&deps.storage).unwrap(), 1);
+    assert_eq!(shields.token_count(&deps.storage).unwrap(), 1);
+}
+
+#[test]
+fn unauthorized_errors_name_the_missing_role() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // minting needs a minter
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "crown".to_string(),
+        owner: String::from("queen"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("queen", &[]),
+            mint_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinter {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // collection settings need the contract owner, a minter isn't enough
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::SetTransfersEnabled { enabled: false },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+
+    // moving or approving a token needs its owner, or an approval
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jester", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("jester"),
+                token_id: "crown".to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jester", &[]),
+            ExecuteMsg::Approve {
+                spender: String::from("jester"),
+                token_id: "crown".to_string(),
+                expires: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotOwnerOrApproved {});
+
+    // checks that accept more than one role keep the general error
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("jester", &[]),
+            ExecuteMsg::SetTokenTag {
+                token_id: "crown".to_string(),
+                key: "stolen".to_string(),
+                value: "yes".to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMinterOrOwner {});
+}
+
+#[test]
+fn signer_pubkey_can_be_rotated() {
+    use k256::ecdsa::signature::Signer;
+    use k256::ecdsa::{Signature, SigningKey};
+
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let env = mock_env();
+    let old_signer = SigningKey::from_bytes(&[5u8; 32]).unwrap();
+    let new_signer = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+    let pubkey = |signer: &SigningKey| Binary::from(signer.verifying_key().to_bytes().as_slice());
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        signer_pubkey: Some(pubkey(&old_signer)),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let signed_mint = |signer: &SigningKey, token_id: &str| {
+        let mint_msg = MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("guest"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        let authorization = MintAuthorization {
+            contract: env.contract.address.to_string(),
+            msg: mint_msg.clone(),
+        };
+        let signature: Signature = signer.sign(&to_vec(&authorization).unwrap());
+        ExecuteMsg::MintSigned {
+            msg: mint_msg,
+            signature: Binary::from(signature.as_ref()),
+        }
+    };
+
+    // only the owner rotates, and only to something that can be a secp256k1 key
+    let rotate = |pubkey: Option<Binary>| ExecuteMsg::SetSignerPubkey { pubkey };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MINTER, &[]),
+            rotate(Some(pubkey(&new_signer))),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            rotate(Some(Binary::from(vec![2u8; 20]))),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSignerPubkey {});
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            rotate(Some(pubkey(&new_signer))),
+        )
+        .unwrap();
+
+    // the old key's signatures no longer work, the new key's do
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("guest", &[]),
+            signed_mint(&old_signer, "old"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMintSignature {});
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("guest", &[]),
+            signed_mint(&new_signer, "new"),
+        )
+        .unwrap();
+    let config: ConfigResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), env.clone(), QueryMsg::Config {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.signer_pubkey, Some(pubkey(&new_signer)));
+
+    // and clearing it turns signed mints off
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            rotate(None),
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("guest", &[]),
+            signed_mint(&new_signer, "newer"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMintSignature {});
+}
+
+#[test]
+fn min_deposit_is_required_and_kept_on_withdraw() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        min_deposit: Some(coin(100, "ustars")),
+        ..Default::default()
+    };
+
+    // too little, or the wrong denom, doesn't cover the deposit
+    for funds in [coins(99, "ustars"), coins(100, "uatom")] {
+        let err = contract
+            .instantiate(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("creator", &funds),
+                msg.clone(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientDeposit {
+                min_deposit: coin(100, "ustars")
+            }
+        );
+    }
+    contract
+        .instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &coins(100, "ustars")),
+            msg,
+        )
+        .unwrap();
+    let config = contract.collection_config(deps.as_ref()).unwrap();
+    assert_eq!(config.min_deposit, Some(coin(100, "ustars")));
+
+    // the contract picked up some more funds on top of the deposit
+    deps.querier
+        .update_balance(env.contract.address.as_str(), coins(130, "ustars"));
+
+    // only the owner can withdraw
+    let withdraw_msg = ExecuteMsg::Withdraw {
+        amount: coin(500, "ustars"),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MINTER, &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotContractOwner {});
+
+    // asking for more than is spare is capped at the balance above the deposit
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("creator"),
+            amount: coins(30, "ustars"),
+        })]
+    );
+
+    // once only the deposit is left, there is nothing to withdraw
+    deps.querier
+        .update_balance(env.contract.address.as_str(), coins(100, "ustars"));
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            withdraw_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingToWithdraw {});
+
+    // other denoms aren't held back
+    deps.querier.update_balance(
+        env.contract.address.as_str(),
+        vec![coin(100, "ustars"), coin(40, "uatom")],
+    );
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env,
+            mock_info("creator", &[]),
+            ExecuteMsg::Withdraw {
+                amount: coin(40, "uatom"),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("creator"),
+            amount: coins(40, "uatom"),
+        })]
+    );
+}
+
+#[test]
+fn tokens_saved_before_upgrade_still_load() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // a token as stored by a deployment from before mint and transfer heights and persistent
+    // approvals were recorded
+    deps.storage.set(
+        &contract.tokens.key("relic"),
+        br#"{"owner":"hades","approvals":[],"token_uri":null,"extension":null}"#,
+    );
+
+    let token = contract.tokens.load(&deps.storage, "relic").unwrap();
+    assert_eq!(token.owner, "hades");
+    assert_eq!(token.mint_block, 0);
+    assert_eq!(token.last_transfer, 0);
+    assert!(token.persistent_approvals.is_empty());
+
+    // the heights it was minted and last moved at are unknown
+    let info = contract
+        .all_nft_info(deps.as_ref(), mock_env(), "relic".to_string(), false)
+        .unwrap();
+    assert_eq!(info.info.mint_block, None);
+    assert_eq!(info.last_transfer, None);
+}
+
+#[test]
+fn migrate_moves_the_legacy_minter_into_the_minters_map() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+
+    // a deployment from before multiple minters: one minter under "minter" and no owner
+    contract
+        .contract_info
+        .save(
+            &mut deps.storage,
+            &ContractInfoResponse {
+                name: CONTRACT_NAME.to_string(),
+                symbol: SYMBOL.to_string(),
+            },
+        )
+        .unwrap();
+    deps.storage.set(b"minter", br#""merlin""#);
+
+    let res = contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "migrate"), attr("migrated_minter", MINTER)]
+    );
+    assert_eq!(deps.storage.get(b"minter"), None);
+    let minters: MintersResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::Minters {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(minters.minters, vec![MINTER.to_string()]);
+    assert_eq!(contract.owner.load(&deps.storage).unwrap(), MINTER);
+
+    // the migrated minter can mint again
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "phoenix".to_string(),
+        owner: String::from("medusa"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // migrating again leaves everything as it is
+    let res = contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "migrate"), attr("migrated_minter", "")]
+    );
+    assert_eq!(contract.owner.load(&deps.storage).unwrap(), MINTER);
+}
+
+#[test]
+fn migrate_counts_balances_of_legacy_tokens() {
+    let mut deps = mock_dependencies();
+    let contract = legacy_deployment(
+        &mut deps.storage,
+        &[("relic", "hades"), ("helm", "hades"), ("lyre", "orpheus")],
+    );
+
+    contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+    let hades = Addr::unchecked("hades");
+    assert_eq!(contract.balance(&deps.storage, &hades).unwrap(), 2);
+    assert_eq!(
+        contract
+            .balance(&deps.storage, &Addr::unchecked("orpheus"))
+            .unwrap(),
+        1
+    );
+    // and Stats counts their owners
+    assert_eq!(contract.owner_count(&deps.storage).unwrap(), 2);
+
+    // tokens minted before the upgrade move like any other
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("persephone"),
+        token_id: String::from("relic"),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("hades", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(contract.balance(&deps.storage, &hades).unwrap(), 1);
+    assert_eq!(
+        contract
+            .balance(&deps.storage, &Addr::unchecked("persephone"))
+            .unwrap(),
+        1
+    );
+    assert_eq!(contract.owner_count(&deps.storage).unwrap(), 3);
+}
+
+#[test]
+fn migrate_counts_legacy_tokens_as_minted() {
+    let mut deps = mock_dependencies();
+    let contract = legacy_deployment(&mut deps.storage, &[("1", "hades"), ("2", "orpheus")]);
+
+    contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+    assert_eq!(contract.minted_total(&deps.storage).unwrap(), 2);
+
+    // the next id in sequence follows on from the legacy tokens instead of restarting at 1
+    let res: NextTokenIdResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::NextTokenId {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.token_id, "3");
+    let mint_msg = ExecuteMsg::MintNext {
+        owner: String::from("eurydice"),
+        token_uri: None,
+        extension: None,
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    assert!(res.attributes.contains(&attr("token_id", "3")));
+}
+
+#[test]
+fn migrate_keeps_supply_totals_in_line() {
+    let mut deps = mock_dependencies();
+    let contract = legacy_deployment(
+        &mut deps.storage,
+        &[("relic", "hades"), ("helm", "hades"), ("lyre", "orpheus")],
+    );
+    let totals_add_up = |storage: &dyn Storage| {
+        contract.minted_total(storage).unwrap() - contract.burned_total(storage).unwrap()
+            == contract.token_count(storage).unwrap()
+    };
+
+    contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+    assert_eq!(contract.burned_total.load(&deps.storage).unwrap(), 0);
+    assert!(totals_add_up(&deps.storage));
+
+    // and stay that way once legacy tokens are burned
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: String::from("helm"),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("hades", &[]), burn_msg)
+        .unwrap();
+    assert_eq!(contract.burned_total(&deps.storage).unwrap(), 1);
+    assert!(totals_add_up(&deps.storage));
+    assert_eq!(
+        contract
+            .balance(&deps.storage, &Addr::unchecked("hades"))
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+fn counters_fail_instead_of_wrapping() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // a balance that is already out of sync can't go below zero
+    let nobody = Addr::unchecked("nobody");
+    let err = contract
+        .decrement_balance(&mut deps.storage, &nobody)
+        .unwrap_err();
+    assert!(matches!(err, StdError::Overflow { .. }));
+    contract
+        .balances
+        .save(&mut deps.storage, &nobody, &u64::MAX)
+        .unwrap();
+    let err = contract
+        .increment_balance(&mut deps.storage, &nobody)
+        .unwrap_err();
+    assert!(matches!(err, StdError::Overflow { .. }));
+
+    // nor can the mint sequence run past the largest id
+    contract
+        .minted_total
+        .save(&mut deps.storage, &u64::MAX)
+        .unwrap();
+    let err = contract.next_token_id(&deps.storage).unwrap_err();
+    assert!(matches!(err, StdError::Overflow { .. }));
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "omega".to_string(),
+        owner: String::from("medusa"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+
+    // and a token's transfer count stops at its limit, before anything moves
+    contract.minted_total.save(&mut deps.storage, &0).unwrap();
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        token_id: "worn".to_string(),
+        owner: String::from("medusa"),
+        token_uri: None,
+        extension: None,
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+    let mut worn = contract.tokens.load(&deps.storage, "worn").unwrap();
+    worn.transfer_count = u32::MAX;
+    contract
+        .tokens
+        .save(&mut deps.storage, "worn", &worn)
+        .unwrap();
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("perseus"),
+        token_id: "worn".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &[]),
+            transfer_msg,
+        )
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+    assert_eq!(
+        contract
+            .balances
+            .load(&deps.storage, &Addr::unchecked("medusa"))
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+fn safe_send_pays_fee_and_calls_hook_only_once_the_receiver_accepts() {
+    let mut deps = mock_dependencies();
+    mock_contracts(&mut deps.querier, &["museum"]);
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        transfer_fee: Some(coin(10, "ujuno")),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::SetTransferHook {
+                hook: Some(String::from("ledger")),
+                required: true,
+            },
+        )
+        .unwrap();
+    for token_id in ["vase", "urn"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("collector"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+    let send = |token_id: &str, receiver: &str| ExecuteMsg::SendNft {
+        contract: receiver.to_string(),
+        token_id: token_id.to_string(),
+        msg: to_binary("exhibit").unwrap(),
+        safe_send: true,
+        wrap: true,
+        with_owner: false,
+    };
+    let reply = |result| Reply {
+        id: ReplyId::SafeSendNft.id(),
+        result,
+    };
+    let hook_msg = |token_id: &str| {
+        TransferHookMsg {
+            token_id: token_id.to_string(),
+            previous_owner: String::from("collector"),
+            recipient: String::from("museum"),
+        }
+        .into_cosmos_msg("ledger")
+        .unwrap()
+    };
+
+    // an account can't take the token, so it isn't even tried
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("collector", &coins(10, "ujuno")),
+            send("vase", "curator"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ReceiverNotContract {
+            contract: String::from("curator"),
+        }
+    );
+
+    // only the ReceiveNft goes out with the send, and its reply always runs
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("collector", &coins(10, "ujuno")),
+            send("vase", "museum"),
+        )
+        .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, ReplyId::SafeSendNft.id());
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Always);
+
+    // once the receiver took it, the reply pays the fee and calls the hook
+    let res = contract
+        .reply(
+            deps.as_mut(),
+            mock_env(),
+            reply(SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            })),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(hook_msg("vase")),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("creator"),
+                amount: coins(10, "ujuno"),
+            }),
+        ]
+    );
+
+    // if the receiver fails, the sender gets their funds back and the hook isn't called
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("collector", &coins(10, "ujuno")),
+            send("urn", "museum"),
+        )
+        .unwrap();
+    let res = contract
+        .reply(
+            deps.as_mut(),
+            mock_env(),
+            reply(SubMsgResult::Err("museum is closed".into())),
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("collector"),
+            amount: coins(10, "ujuno"),
+        })]
+    );
+    assert_eq!(res.attributes[0], attr("action", "send_nft_rolled_back"));
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "urn".into(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "collector");
+
+    // either way nothing is left behind in storage
+    assert!(contract.safe_send_backups.is_empty(&deps.storage));
+    assert!(contract.pending_sends.is_empty(&deps.storage));
+}
+
+#[test]
+fn nested_safe_sends_keep_their_own_backups() {
+    let mut deps = mock_dependencies();
+    mock_contracts(&mut deps.querier, &["museum", "gallery"]);
+    let contract = setup_contract(deps.as_mut());
+    for token_id in ["vase", "urn"] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: String::from("collector"),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+    let send = |token_id: &str, receiver: &str| ExecuteMsg::SendNft {
+        contract: receiver.to_string(),
+        token_id: token_id.to_string(),
+        msg: to_binary("exhibit").unwrap(),
+        safe_send: true,
+        wrap: true,
+        with_owner: false,
+    };
+
+    // the second send happens before the first one's reply, as if from the museum's ReceiveNft
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("collector", &coins(3, "ujuno")),
+            send("vase", "museum"),
+        )
+        .unwrap();
+    let outer = res.messages[0].id;
+    assert_eq!(outer, ReplyId::SafeSendNft.id());
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("collector", &coins(5, "ujuno")),
+            send("urn", "gallery"),
+        )
+        .unwrap();
+    let inner = res.messages[0].id;
+    assert_eq!(inner, safe_send_reply_id(1));
+    assert_eq!(ReplyId::try_from(inner).unwrap(), ReplyId::SafeSendNft);
+
+    // the gallery rejects the urn: only the urn goes back, with its own funds
+    let res = contract
+        .reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: inner,
+                result: SubMsgResult::Err("gallery is full".into()),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: String::from("collector"),
+            amount: coins(5, "ujuno"),
+        })]
+    );
+    assert!(res.attributes.contains(&attr("recipient", "gallery")));
+    assert!(res.attributes.contains(&attr("token_id", "urn")));
+
+    // while the museum keeps the vase
+    let res = contract
+        .reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: outer,
+                result: SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data: None,
+                }),
+            },
+        )
+        .unwrap();
+    assert_eq!(res, Response::new());
+    for (token_id, owner) in [("vase", "museum"), ("urn", "collector")] {
+        let res = contract
+            .owner_of(deps.as_ref(), mock_env(), token_id.into(), false)
+            .unwrap();
+        assert_eq!(res.owner, owner);
+    }
+    assert!(contract.safe_send_backups.is_empty(&deps.storage));
+
+    // a slot on another flow's id isn't a reply id
+    let id = ReplyId::SendNft.id() | (1 << 32);
+    assert_eq!(
+        ReplyId::try_from(id).unwrap_err(),
+        ContractError::UnknownReplyId { id }
+    );
+}
+
+#[test]
+fn can_transfer_matches_what_a_transfer_would_do() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        max_per_owner: Some(1),
+        ..Default::default()
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    for (token_id, owner) in [("idol", "venus"), ("mask", "juno")] {
+        let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        });
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    }
+    let limit_msg = ExecuteMsg::SetMaxTransfers {
+        token_id: "idol".to_string(),
+        max_transfers: Some(1),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), limit_msg)
+        .unwrap();
+
+    let can_transfer = |deps: Deps, address: &str, recipient: Option<&str>| {
+        let query_msg: QueryMsg<Empty> = QueryMsg::CanTransfer {
+            token_id: String::from("idol"),
+            address: address.to_string(),
+            recipient: recipient.map(String::from),
+        };
+        let res: CanTransferResponse =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.can_transfer
+    };
+
+    // juno already holds as many tokens as anyone may, so only the recipient-aware query says no
+    assert!(can_transfer(deps.as_ref(), "venus", None));
+    assert!(can_transfer(deps.as_ref(), "venus", Some("mars")));
+    assert!(!can_transfer(deps.as_ref(), "venus", Some("juno")));
+    assert!(!can_transfer(deps.as_ref(), "venus", Some("venus")));
+
+    // the one allowed transfer uses the token up
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("mars"),
+                token_id: String::from("idol"),
+            },
+        )
+        .unwrap();
+    assert!(!can_transfer(deps.as_ref(), "mars", None));
+    assert!(!can_transfer(deps.as_ref(), "mars", Some("vulcan")));
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("mars", &[]),
+            ExecuteMsg::TransferNft {
+                recipient: String::from("vulcan"),
+                token_id: String::from("idol"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TransferLimitReached { max: 1 });
+}
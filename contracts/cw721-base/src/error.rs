@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Coin, Decimal, StdError};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -12,9 +12,162 @@ pub enum ContractError {
     #[error("token_id already claimed")]
     Claimed {},
 
+    #[error("Token not found: {token_id}")]
+    TokenNotFound { token_id: String },
+
     #[error("Cannot set approval that is already expired")]
     Expired {},
 
     #[error("Approval not found for: {spender}")]
     ApprovalNotFound { spender: String },
+
+    #[error("Cannot set approval for the token owner")]
+    ApprovalToSelf {},
+
+    #[error("Cannot transfer a token to its current owner")]
+    CannotTransferToSelf {},
+
+    #[error("Minter not found: {minter}")]
+    MinterNotFound { minter: String },
+
+    #[error("Cannot remove the last minter")]
+    CannotRemoveLastMinter {},
+
+    #[error("Burning is not allowed in this collection")]
+    BurnNotAllowed {},
+
+    #[error("Cannot mint to a contract address")]
+    CannotMintToContract {},
+
+    #[error("Token can't be transferred again until height {available_at}")]
+    TransferCooldown { available_at: u64 },
+
+    #[error("Receiver not allowed: {receiver}")]
+    ReceiverNotAllowed { receiver: String },
+
+    #[error("Cannot mint more than {max} tokens in one batch")]
+    BatchTooLarge { max: usize },
+
+    #[error("Invalid {field}: must be between 1 and {max} characters")]
+    InvalidContractInfo { field: String, max: usize },
+
+    #[error("Receiver {contract} rejected the NFT, it may not implement ReceiveNft")]
+    ReceiverRejected { contract: String },
+
+    #[error("Funds don't cover any of the accepted mint prices")]
+    NoAcceptedPaymentProvided {},
+
+    #[error("Royalty share can't be more than {max}")]
+    RoyaltyTooHigh { max: Decimal },
+
+    #[error("Sender is not on the mint allowlist")]
+    NotOnMintAllowlist {},
+
+    #[error("Metadata hash must be a hex-encoded sha256 digest (64 hex characters)")]
+    InvalidMetadataHash {},
+
+    #[error("Permit has expired")]
+    PermitExpired {},
+
+    #[error("Permit nonce is not the expected {expected}")]
+    InvalidPermitNonce { expected: u64 },
+
+    #[error("Permit is not signed by the token owner")]
+    InvalidPermitSignature {},
+
+    #[error("Transfers require a fee of {fee}")]
+    InsufficientTransferFee { fee: Coin },
+
+    #[error("Invalid token_id {token_id}: too long or has disallowed characters")]
+    InvalidTokenId { token_id: String },
+
+    #[error("Token id {token_id} is reserved for the minters")]
+    ReservedTokenId { token_id: String },
+
+    #[error("The contract itself can't be a minter")]
+    InvalidMinter {},
+
+    #[error("Mint is not authorized by the collection's signer")]
+    InvalidMintSignature {},
+
+    #[error("Transfers are disabled for this collection")]
+    TransfersDisabled {},
+
+    #[error("No open IBC channel {channel_id}")]
+    UnknownChannel { channel_id: String },
+
+    #[error("Unsupported IBC channel version {version}")]
+    InvalidIbcVersion { version: String },
+
+    #[error("Operator approval has expired")]
+    OperatorExpired {},
+
+    #[error("Approval for this token has expired")]
+    ApprovalExpired {},
+
+    #[error("Token is not held by the contract")]
+    NotHeldByContract {},
+
+    #[error("Edition must be at least 1 and no more than edition_of")]
+    InvalidEdition {},
+
+    #[error("Collection is capped at {max_supply} tokens")]
+    MaxSupplyReached { max_supply: u64 },
+
+    #[error("An address can hold at most {max_per_owner} tokens of this collection")]
+    OwnerLimitReached { max_per_owner: u64 },
+
+    #[error("An admin burn needs a reason")]
+    MissingBurnReason {},
+
+    #[error("A token can have at most {max} tags")]
+    TooManyTags { max: u32 },
+
+    #[error("Approval can't be used before height {active_from}")]
+    ApprovalNotYetActive { active_from: u64 },
+
+    #[error("Sending to contracts is disabled for this collection")]
+    SendDisabled {},
+
+    #[error("token_uri must be non-empty and at most {max} bytes")]
+    InvalidTokenUri { max: usize },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Token has already changed hands the maximum of {max} times")]
+    TransferLimitReached { max: u32 },
+
+    #[error("Operator approvals are disabled for this collection, approve single tokens instead")]
+    OperatorsDisabled {},
+
+    #[error("Only a minter can do this")]
+    NotMinter {},
+
+    #[error("Only the contract owner can do this")]
+    NotContractOwner {},
+
+    #[error("Sender is neither the token's owner nor approved for it")]
+    NotOwnerOrApproved {},
+
+    #[error("Signer pubkey must be a secp256k1 key, 33 bytes compressed or 65 uncompressed")]
+    InvalidSignerPubkey {},
+
+    #[error("Instantiating requires a deposit of {min_deposit}")]
+    InsufficientDeposit { min_deposit: Coin },
+
+    #[error("Nothing can be withdrawn without going below the contract's min_deposit")]
+    NothingToWithdraw {},
+
+    #[error("Receiver {contract} is not a contract, so it can't take the NFT")]
+    ReceiverNotContract { contract: String },
+
+    #[error("Only a minter or the metadata updater can do this")]
+    NotMetadataUpdater {},
+
+    #[error("Only a minter or the contract owner can do this")]
+    NotMinterOrOwner {},
+
+    #[error("Only the token's owner can do this")]
+    NotTokenOwner {},
 }
@@ -1,19 +1,94 @@
-use std::fmt::Debug; // added std::fmt::Debug for the derive(Debug) below
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Debug; // added std::fmt::Debug for the derive(Debug) below
 
-use cosmwasm_std::{Binary, CustomMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    has_coins, to_binary, to_vec, Addr, BankMsg, Binary, Coin, CustomMsg, Deps, DepsMut, Empty,
+    Env, MessageInfo, Order, OverflowError, OverflowOperation, Reply, ReplyOn, Response, StdError,
+    StdResult, Storage, SubMsg, SubMsgResult, Uint128, WasmMsg,
+};
 
 use cw2::set_contract_version;
-use cw721::{ContractInfoResponse, Cw721Execute, Cw721ReceiveMsg, Expiration};
+use cw721::{
+    ContractInfoResponse, Cw721Execute, Cw721ExecuteMsg, Cw721ReceiveMsg, Cw721ReceiveWithOwnerMsg,
+    Expiration,
+};
+use cw_storage_plus::Item;
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, MigrateMsg, MintAuthorization, MintMsg, MintPacket, RedeemMsg,
+    TransferHookMsg, TransferPermit,
+};
+use crate::state::{
+    Approval, BurnPolicy, Config, Cw721Contract, HistoryEntry, OperatorGrant, Phase,
+    SafeSendBackup, TokenInfo, TransferHook,
+};
 
 // Version info for migration
-const CONTRACT_NAME: &str = "crates.io:cw721-base";
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub(crate) const CONTRACT_NAME: &str = "crates.io:cw721-base";
+pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// Where deployments from before multiple minters kept their single minter
+const LEGACY_MINTER_KEY: &str = "minter";
+
+/// Ids of the submessages whose replies this contract handles, one per async flow.
+/// In-flight submessages carry these values, so existing ones must never be renumbered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplyId {
+    /// The ReceiveNft submessage of SendNft and SendNftWithFunds
+    SendNft = 1,
+    /// The ReceiveNft submessage of a SendNft with safe_send. Each send that is still waiting
+    /// on its receiver gets its own id, this one plus a slot above SAFE_SEND_SLOT_SHIFT (see
+    /// safe_send_reply_id)
+    SafeSendNft = 2,
+    /// The transfer hook's submessage, when the hook isn't required
+    TransferHook = 3,
+}
+
+impl ReplyId {
+    pub const fn id(self) -> u64 {
+        self as u64
+    }
+}
+
+// Safe sends nested in another's ReceiveNft are told apart by the high bits of their reply id
+const SAFE_SEND_SLOT_SHIFT: u32 = 32;
+
+/// The reply id of the safe send in `slot`, the number of safe sends it is nested in
+pub fn safe_send_reply_id(slot: u32) -> u64 {
+    ReplyId::SafeSendNft.id() | (u64::from(slot) << SAFE_SEND_SLOT_SHIFT)
+}
+
+impl TryFrom<u64> for ReplyId {
+    type Error = ContractError;
+
+    fn try_from(id: u64) -> Result<Self, Self::Error> {
+        match id {
+            1 => Ok(ReplyId::SendNft),
+            2 => Ok(ReplyId::SafeSendNft),
+            3 => Ok(ReplyId::TransferHook),
+            id if id & u64::from(u32::MAX) == ReplyId::SafeSendNft.id() => Ok(ReplyId::SafeSendNft),
+            id => Err(ContractError::UnknownReplyId { id }),
+        }
+    }
+}
+
+// Most tags SetTokenTag lets a single token carry
+const MAX_TOKEN_TAGS: u32 = 32;
+// Most tokens a single batch mint may create, to keep the transaction within gas limits
+const MAX_BATCH_MINT: usize = 50;
+// How many tokens BurnAllOwnedBy burns per call, unless asked for fewer
+const DEFAULT_BURN_LIMIT: u32 = 10;
+const MAX_BURN_LIMIT: u32 = 30;
+// Longest name and symbol UpdateContractInfo accepts
+const MAX_NAME_LENGTH: usize = 64;
+const MAX_SYMBOL_LENGTH: usize = 16;
+// Longest token_id a mint accepts, unless the collection picked its own limit
+pub(crate) const DEFAULT_MAX_TOKEN_ID_LENGTH: u32 = 128;
+// Longest token_uri a mint accepts; it's stored with every token
+const MAX_TOKEN_URI_LENGTH: usize = 2048;
 
 // Rust doesn't have classes, use impl blocks to group methods (T:, C:, E:, Q:)
 // https://doc.rust-lang.org/book/ch05-03-method-syntax.html
@@ -31,20 +106,148 @@ where
     pub fn instantiate(
         &self,
         deps: DepsMut,
-        _env: Env,
-        _info: MessageInfo,
-        msg: InstantiateMsg,
-    ) -> StdResult<Response<C>> {
+        env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg<T>,
+    ) -> Result<Response<C>, ContractError> {
         set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-        let info = ContractInfoResponse {
+        let contract_info = ContractInfoResponse {
             name: msg.name,
             symbol: msg.symbol,
         };
-        self.contract_info.save(deps.storage, &info)?;
+        self.contract_info.save(deps.storage, &contract_info)?;
         let minter = deps.api.addr_validate(&msg.minter)?;
-        self.minter.save(deps.storage, &minter)?;
-        Ok(Response::default())
+        // the contract can't sign mint txs itself, so it would be a minter nobody can use
+        if minter == env.contract.address {
+            return Err(ContractError::InvalidMinter {});
+        }
+        self.minters.save(deps.storage, &minter, &true)?;
+        // whoever instantiates owns the contract settings, unless told otherwise
+        let owner = match msg.owner {
+            Some(owner) => deps.api.addr_validate(&owner)?,
+            None => info.sender,
+        };
+        self.owner.save(deps.storage, &owner)?;
+        // on chains with storage rent the contract has to be able to pay its way from the start
+        if let Some(min_deposit) = &msg.min_deposit {
+            if !has_coins(&info.funds, min_deposit) {
+                return Err(ContractError::InsufficientDeposit {
+                    min_deposit: min_deposit.clone(),
+                });
+            }
+        }
+        let config = Config {
+            burn_policy: msg.burn_policy.unwrap_or_default(),
+            default_approval_duration: msg.default_approval_duration,
+            reject_contract_owners: msg.reject_contract_owners,
+            transfer_cooldown_blocks: msg.transfer_cooldown_blocks,
+            mint_prices: msg.mint_prices.unwrap_or_default(),
+            allowlist_mint_prices: msg.allowlist_mint_prices.unwrap_or_default(),
+            transfer_fee: msg.transfer_fee,
+            max_token_id_length: msg.max_token_id_length,
+            reserved_prefix: msg.reserved_prefix,
+            redemption_contract: msg
+                .redemption_contract
+                .map(|addr| deps.api.addr_validate(&addr))
+                .transpose()?,
+            minimal_events: msg.minimal_events,
+            signer_pubkey: msg.signer_pubkey.map(validate_signer_pubkey).transpose()?,
+            keep_approvals_on_send: msg.keep_approvals_on_send,
+            max_supply: msg.max_supply,
+            burns_free_supply: msg.burns_free_supply,
+            allowlist_free_mints: msg.allowlist_free_mints,
+            max_per_owner: msg.max_per_owner,
+            mint_price_curve: msg.mint_price_curve,
+            track_history: msg.track_history,
+            approval_delay_blocks: msg.approval_delay_blocks,
+            operators_enabled: msg.operators_enabled.unwrap_or(true),
+            min_deposit: msg.min_deposit,
+        };
+        self.config.save(deps.storage, &config)?;
+        self.collection_uri
+            .save(deps.storage, &msg.collection_uri)?;
+        if let Some(default_extension) = msg.default_extension {
+            self.default_extension
+                .save(deps.storage, &default_extension)?;
+        }
+        for receiver in msg.allowed_receivers.unwrap_or_default() {
+            let receiver = deps.api.addr_validate(&receiver)?;
+            self.allowed_receivers
+                .save(deps.storage, &receiver, &true)?;
+        }
+        // genesis tokens go through the same path as a batch mint, once the settings above are in place
+        // (the instantiator isn't asked to be a minter, they are the one setting the collection up)
+        let initial_mint_count =
+            self._batch_mint(deps, &env, msg.initial_mints.unwrap_or_default())?;
+        Ok(Response::new().add_attribute("initial_mint_count", initial_mint_count.to_string()))
+    }
+
+    /// Brings the storage of an older deployment up to date. Running it again changes nothing
+    pub fn migrate(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        _msg: MigrateMsg,
+    ) -> Result<Response<C>, ContractError> {
+        // the single minter of older deployments becomes the first entry of the minters map.
+        // Those deployments stored no owner either, and the minter is who ran the collection
+        let legacy_minter: Item<Addr> = Item::new(LEGACY_MINTER_KEY);
+        let migrated_minter = legacy_minter.may_load(deps.storage)?;
+        if let Some(minter) = &migrated_minter {
+            self.minters.save(deps.storage, minter, &true)?;
+            if self.owner.may_load(deps.storage)?.is_none() {
+                self.owner.save(deps.storage, minter)?;
+            }
+            legacy_minter.remove(deps.storage);
+        }
+        // balances weren't kept before either, so count them up from the owner index. A
+        // collection with any balance stored already keeps them all up to date
+        let has_balances = self
+            .balances
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+        if !has_balances {
+            let mut balances: BTreeMap<Addr, u64> = BTreeMap::new();
+            for item in self
+                .tokens
+                .idx
+                .owner
+                .range(deps.storage, None, None, Order::Ascending)
+            {
+                let (_, token) = item?;
+                *balances.entry(token.owner).or_default() += 1;
+            }
+            for (owner, balance) in balances {
+                self.balances.save(deps.storage, &owner, &balance)?;
+            }
+        }
+        // nor how many were ever minted. Counting the ones still around keeps MintNext from
+        // handing out ids they hold, and max_supply from undercounting
+        if self.minted_total.may_load(deps.storage)?.is_none() {
+            let token_count = self.token_count(deps.storage)?;
+            self.minted_total.save(deps.storage, &token_count)?;
+        }
+        // and none of those count as burned, so minted_total - burned_total == token_count holds
+        if self.burned_total.may_load(deps.storage)?.is_none() {
+            self.burned_total.save(deps.storage, &0)?;
+        }
+        // the owner count Stats reads is kept alongside the balances, so start it from them
+        if self.owner_count.may_load(deps.storage)?.is_none() {
+            let owners = self
+                .balances
+                .keys(deps.storage, None, None, Order::Ascending)
+                .count();
+            self.owner_count.save(deps.storage, &(owners as u64))?;
+        }
+        set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+        Ok(Response::new()
+            .add_attribute("action", "migrate")
+            .add_attribute(
+                "migrated_minter",
+                migrated_minter.map(Addr::into_string).unwrap_or_default(),
+            ))
     }
 
     pub fn execute(
@@ -56,31 +259,241 @@ where
     ) -> Result<Response<C>, ContractError> {
         match msg {
             ExecuteMsg::Mint(msg) => self.mint(deps, env, info, msg),
+            ExecuteMsg::MintNext {
+                owner,
+                token_uri,
+                extension,
+            } => self.mint_next(deps, env, info, owner, token_uri, extension),
+            ExecuteMsg::MintAndSend {
+                mint,
+                contract,
+                msg,
+            } => self.mint_and_send(deps, env, info, mint, contract, msg),
+            ExecuteMsg::MintSigned { msg, signature } => {
+                self.mint_signed(deps, env, info, msg, signature)
+            }
+            ExecuteMsg::UpdateExtension {
+                token_id,
+                extension,
+            } => self.update_extension(deps, env, info, token_id, extension),
+            ExecuteMsg::SetMetadataUpdater { address } => {
+                self.set_metadata_updater(deps, env, info, address)
+            }
+            ExecuteMsg::SetTokenTag {
+                token_id,
+                key,
+                value,
+            } => self.set_token_tag(deps, env, info, token_id, key, value),
+            ExecuteMsg::RemoveTokenTag { token_id, key } => {
+                self.remove_token_tag(deps, env, info, token_id, key)
+            }
+            ExecuteMsg::SetRoyaltyExempt { token_id, exempt } => {
+                self.set_royalty_exempt(deps, env, info, token_id, exempt)
+            }
+            ExecuteMsg::SetMaxTransfers {
+                token_id,
+                max_transfers,
+            } => self.set_max_transfers(deps, env, info, token_id, max_transfers),
             ExecuteMsg::Approve {
                 spender,
                 token_id,
                 expires,
             } => self.approve(deps, env, info, spender, token_id, expires),
+            ExecuteMsg::ApproveBatch {
+                spender,
+                token_ids,
+                expires,
+            } => self.approve_batch(deps, env, info, spender, token_ids, expires),
             ExecuteMsg::Revoke { spender, token_id } => {
                 self.revoke(deps, env, info, spender, token_id)
             }
-            ExecuteMsg::ApproveAll { operator, expires } => {
-                self.approve_all(deps, env, info, operator, expires)
+            ExecuteMsg::RevokeBatch { token_ids, spender } => {
+                self.revoke_batch(deps, env, info, token_ids, spender)
             }
+            ExecuteMsg::ApproveAll {
+                operator,
+                expires,
+                min_token_height,
+            } => self.approve_all_scoped(deps, env, info, operator, expires, min_token_height),
+            ExecuteMsg::SetPersistentApproval {
+                token_id,
+                spender,
+                persistent,
+            } => self.set_persistent_approval(deps, env, info, token_id, spender, persistent),
             ExecuteMsg::RevokeAll { operator } => self.revoke_all(deps, env, info, operator),
+            ExecuteMsg::AddMinter { minter } => self.add_minter(deps, env, info, minter),
+            ExecuteMsg::RemoveMinter { minter } => self.remove_minter(deps, env, info, minter),
+            ExecuteMsg::SetPhase { phase } => self.set_phase(deps, env, info, phase),
+            ExecuteMsg::SetSignerPubkey { pubkey } => {
+                self.set_signer_pubkey(deps, env, info, pubkey)
+            }
+            ExecuteMsg::SetMintAllowed { address, allowed } => {
+                self.set_mint_allowed(deps, env, info, address, allowed)
+            }
+            ExecuteMsg::SetTransfersEnabled { enabled } => {
+                self.set_transfers_enabled(deps, env, info, enabled)
+            }
+            ExecuteMsg::SetSendEnabled { enabled } => {
+                self.set_send_enabled(deps, env, info, enabled)
+            }
+            ExecuteMsg::SetCollectionUri { collection_uri } => {
+                self.set_collection_uri(deps, env, info, collection_uri)
+            }
+            ExecuteMsg::RescueNft {
+                collection,
+                token_id,
+                recipient,
+            } => self.rescue_nft(deps, env, info, collection, token_id, recipient),
+            ExecuteMsg::AdminBurn { token_id, reason } => {
+                self.admin_burn(deps, env, info, token_id, reason)
+            }
+            ExecuteMsg::SetMintChannel { channel_id } => {
+                self.set_mint_channel(deps, env, info, channel_id)
+            }
+            ExecuteMsg::SetReceiverAllowed { receiver, allowed } => {
+                self.set_receiver_allowed(deps, env, info, receiver, allowed)
+            }
+            ExecuteMsg::SetTransferHook { hook, required } => {
+                self.set_transfer_hook(deps, env, info, hook, required)
+            }
+            ExecuteMsg::UpdateContractInfo { name, symbol } => {
+                self.update_contract_info(deps, env, info, name, symbol)
+            }
             ExecuteMsg::TransferNft {
                 recipient,
                 token_id,
             } => self.transfer_nft(deps, env, info, recipient, token_id),
+            ExecuteMsg::TransferWithPermit { permit, signature } => {
+                self.transfer_with_permit(deps, env, info, permit, signature)
+            }
+            ExecuteMsg::SetPermitPubkey { pubkey } => {
+                self.set_permit_pubkey(deps, env, info, pubkey)
+            }
+            ExecuteMsg::SendNft {
+                contract,
+                token_id,
+                msg,
+                safe_send: false,
+                wrap,
+                with_owner,
+            } => self._send_nft(deps, env, info, contract, token_id, msg, wrap, with_owner),
             ExecuteMsg::SendNft {
                 contract,
                 token_id,
                 msg,
-            } => self.send_nft(deps, env, info, contract, token_id, msg),
+                safe_send: true,
+                wrap,
+                with_owner,
+            } => self.safe_send_nft(deps, env, info, contract, token_id, msg, wrap, with_owner),
+            ExecuteMsg::SendNftWithFunds {
+                contract,
+                token_id,
+                msg,
+            } => self.send_nft_with_funds(deps, env, info, contract, token_id, msg),
             ExecuteMsg::Burn { token_id } => self.burn(deps, env, info, token_id),
+            ExecuteMsg::BurnAllOwnedBy { owner, limit } => {
+                self.burn_all_owned_by(deps, env, info, owner, limit)
+            }
+            ExecuteMsg::Withdraw { amount } => self.withdraw(deps, env, info, amount),
             ExecuteMsg::Extension { msg: _ } => Ok(Response::default()),
         }
     }
+
+    // Routes each reply to the handler of the flow that sent the submessage. Sends are
+    // reply_always, so they can clean up after themselves, the transfer hook is reply_on_error,
+    // but each handler also copes with a success
+    pub fn reply(&self, deps: DepsMut, env: Env, msg: Reply) -> Result<Response<C>, ContractError> {
+        match ReplyId::try_from(msg.id)? {
+            ReplyId::SendNft => self.send_nft_reply(deps, msg.result),
+            ReplyId::SafeSendNft => self.safe_send_nft_reply(deps, env, msg.id, msg.result),
+            ReplyId::TransferHook => self.transfer_hook_reply(msg.result),
+        }
+    }
+
+    // A failed ReceiveNft reverts the whole transaction, transfer included, but with an error
+    // that names the receiver. Either way the send is no longer pending
+    fn send_nft_reply(
+        &self,
+        deps: DepsMut,
+        result: SubMsgResult,
+    ) -> Result<Response<C>, ContractError> {
+        let contract = self.pop_pending_send(deps.storage)?;
+        if let SubMsgResult::Ok(_) = result {
+            return Ok(Response::new());
+        }
+        Err(ContractError::ReceiverRejected {
+            contract: contract.to_string(),
+        })
+    }
+
+    // A safe send only pays the transfer fee and calls the hook once the receiver took the
+    // token. If it didn't, the transfer is undone here, the sender gets their funds back and
+    // the transaction succeeds
+    fn safe_send_nft_reply(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        id: u64,
+        result: SubMsgResult,
+    ) -> Result<Response<C>, ContractError> {
+        let SafeSendBackup {
+            token_id,
+            contract,
+            previous,
+            sender,
+            funds,
+        } = self.safe_send_backups.load(deps.storage, id)?;
+        self.safe_send_backups.remove(deps.storage, id);
+        if let SubMsgResult::Ok(_) = result {
+            let fee_payment =
+                self.transfer_fee_payment(deps.storage, &token_id, &sender, &funds)?;
+            let hook = self.transfer_hook_msg(
+                deps.storage,
+                &token_id,
+                &previous.owner,
+                contract.as_str(),
+            )?;
+            return Ok(Response::new()
+                .add_submessages(hook)
+                .add_messages(fee_payment));
+        }
+        // the receiver's own changes are already reverted, only our transfer is left
+        let current = self.load_token(deps.storage, &token_id)?;
+        self.decrement_balance(deps.storage, &current.owner)?;
+        self.increment_balance(deps.storage, &previous.owner)?;
+        self.unindex_approvals(deps.storage, &token_id, &current.approvals);
+        self.index_approvals(deps.storage, &token_id, &previous.approvals)?;
+        self.tokens.save(deps.storage, &token_id, &previous)?;
+        self.record_history(
+            deps.storage,
+            &env,
+            &token_id,
+            "send_rolled_back",
+            Some(&current.owner),
+            Some(&previous.owner),
+        )?;
+
+        let refund = (!funds.is_empty()).then(|| BankMsg::Send {
+            to_address: sender.to_string(),
+            amount: funds,
+        });
+
+        Ok(Response::new()
+            .add_messages(refund)
+            .add_attribute("action", "send_nft_rolled_back")
+            .add_attribute("recipient", contract)
+            .add_attribute("token_id", token_id))
+    }
+
+    // an optional hook failing shouldn't hold up the transfer
+    fn transfer_hook_reply(&self, result: SubMsgResult) -> Result<Response<C>, ContractError> {
+        match result {
+            SubMsgResult::Ok(_) => Ok(Response::new()),
+            SubMsgResult::Err(_) => {
+                Ok(Response::new().add_attribute("action", "transfer_hook_failed"))
+            }
+        }
+    }
 }
 
 // TODO pull this into some sort of trait extension??
@@ -94,47 +507,57 @@ where // contraints
     pub fn mint(
         &self, // self is the Cw721Contract, needs access to the rest of the contract (methods, state, etc); usually implicit
         deps: DepsMut, // storage, api, querier
-        _env: Env, // block info, contract info, message info
+        env: Env, // block info, contract info, message info
         info: MessageInfo, // sender, funds
         msg: MintMsg<T>, // info about token we are minting, look at definition
     ) -> Result<Response<C>, ContractError> {
         // check if the account that's minting is authorized to mint
-        // declaring a variable and load it up from storage (need access to storage)
-        // added .load(store: deps.storage)
+        // the minters live in a map in storage (need access to storage), so we look the sender up in it
         // ? is a shortcut for returning an error if there is one
-        let minter = self.minter.load(deps.storage)?;
-
-        // doing it this way does not load the value from storage, it just creates a variable that is a reference to the storage
-        // let minter: item<Addr> = self.minter; 
-      
-        // if the sender of the mint msg is not authorized minter (initially set when the contract is spun up) in contract storage (state.rs), return an error
-        if info.sender != minter {
-            return Err(ContractError::Unauthorized {});
+        // if the sender of the mint msg is not an authorized minter (initially set when the contract is spun up, more can be added by the owner) in contract storage (state.rs), return an error
+        // minters can always mint, anyone else only if the current mint phase lets them
+        let phase = self.current_phase(deps.storage)?;
+        let config = self.config(deps.storage)?;
+        let is_minter = self.is_minter(deps.storage, &info.sender)?;
+        if !is_minter {
+            match phase {
+                Phase::MinterOnly => return Err(ContractError::NotMinter {}),
+                Phase::Allowlist => {
+                    if !self.mint_allowlist.has(deps.storage, &info.sender) {
+                        return Err(ContractError::NotOnMintAllowlist {});
+                    }
+                }
+                Phase::Public => {}
+            }
+            // and ids under the reserved prefix (e.g. kept for the team) are the minters' alone
+            if let Some(prefix) = &config.reserved_prefix {
+                if msg.token_id.starts_with(prefix.as_str()) {
+                    return Err(ContractError::ReservedTokenId {
+                        token_id: msg.token_id,
+                    });
+                }
+            }
         }
 
-        // create the token
-        // makes a TokenInfo struct that we will save to storage
-        let token = TokenInfo {
-            owner: deps.api.addr_validate(&msg.owner)?,
-            approvals: vec![],
-            token_uri: msg.token_uri,
-            extension: msg.extension,
+        // if the collection charges for minting, the funds must cover any one of the accepted prices
+        // (e.g. either the native token or a stablecoin); the payment is passed on to the owner
+        // the allowlist phase may have its own, discounted, prices, and a price curve overrides both
+        // and allowlisted addresses mint for free while they have free mints left.
+        // Minters run the collection, so they never pay
+        let payment = if is_minter
+            || self.take_free_mint(deps.storage, config.allowlist_free_mints, &info.sender)?
+        {
+            vec![]
+        } else {
+            let mint_prices = self.mint_prices(deps.storage, phase)?;
+            self.mint_payment(deps.storage, &info.sender, &info.funds, &mint_prices)?
         };
-        // IndexMap is a map with additional index functionality
-        // Called update function
-        // pass in storage
-        // pass in token_id (msg.id) we are trying to update
-        // pass in old token (the token we just created) and matching it with the token_id
-        // If it matches, the token already claimed and we return an error
-        // If it doesn't match, it's available and send the token to the update function, saving the token to storage
-        self.tokens
-            .update(deps.storage, &msg.token_id, |old| match old {
-                Some(_) => Err(ContractError::Claimed {}),
-                None => Ok(token.clone()), // token needs to be cloned
-            })?;
-        
-        // We increment the number of tokens in the contract (function in state.rs)
-        self.increment_tokens(deps.storage)?;
+
+        // the rest (creating the token, counters) is shared with minting at instantiate
+        // an indexer on another chain may want to hear about the mint (see ibc.rs)
+        let packet = self.mint_packet_msg(deps.storage, &env, MintPacket::new(&env, &msg))?;
+        let token_id = msg.token_id.clone();
+        let token = self._mint(deps, &env, msg)?;
 
         // for easy local cargo test -- --show-output (to see the output of the tests)
         println!("token_info: {:?}", token.clone());
@@ -147,219 +570,1640 @@ where // contraints
         // Also useful for frontend devs or for anyone who needs to get information from the response
         // Getting attributes from resonse using js would be response.attributes[0] for example
         Ok(Response::new()
+            .add_messages(payment)
+            .add_messages(packet)
             .add_attribute("token_info", format!("{:?}", token)) // token must implement the Debug trait
             .add_attribute("action", "mint")
             .add_attribute("minter", info.sender)
-            .add_attribute("owner", msg.owner)
-            .add_attribute("token_id", msg.token_id))
+            .add_attribute("owner", token.owner)
+            .add_attribute("token_id", token_id))
     }
-}
-
-impl<'a, T, C, E, Q> Cw721Execute<T, C> for Cw721Contract<'a, T, C, E, Q>
-where
-    T: Serialize + DeserializeOwned + Clone,
-    C: CustomMsg,
-    E: CustomMsg,
-    Q: CustomMsg,
-{
-    type Err = ContractError;
 
-    fn transfer_nft(
+    /// Mints with the next id in sequence ("1", "2", ...), so clients don't have to pick one.
+    /// The assigned id is returned in the token_id attribute
+    pub fn mint_next(
         &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        recipient: String,
-        token_id: String,
+        owner: String,
+        token_uri: Option<String>,
+        extension: T,
     ) -> Result<Response<C>, ContractError> {
-        // calls self helper function _ so we don't duplicate fn names)
-        self._transfer_nft(deps, &env, &info, &recipient, &token_id)?; 
-
-        // could be used to get more information about the token
-        // let token = self._transfer_nft(deps, &env, &info, &recipient, &token_id)?; 
-
-        // You could make a TransferNFTMsg struct that contains info, recipient, and token_id if you wanted to
-
-        Ok(Response::new()
-            .add_attribute("action", "transfer_nft")
-            .add_attribute("sender", info.sender)
-            .add_attribute("recipient", recipient)
-            .add_attribute("token_id", token_id))
+        // a manually minted token may already hold the id, in which case mint returns Claimed
+        let token_id = self.next_token_id(deps.storage)?;
+        let msg = MintMsg {
+            token_id,
+            owner,
+            token_uri,
+            extension,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        self.mint(deps, env, info, msg)
     }
 
-    // doesn't just change the owner of the nft, it also takes a transaction
-    fn send_nft(
+    /// Mints a token owned by `contract` and sends it the ReceiveNft, as if it had been minted
+    /// and then sent. A failing receiver reverts the mint too
+    pub fn mint_and_send(
         &self,
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
+        mut mint: MintMsg<T>,
         contract: String,
-        token_id: String,
         msg: Binary,
     ) -> Result<Response<C>, ContractError> {
-        // Transfer token (helper function)
-        // _transfer_nft is being reused as a helper function
-        // sending the nft to a contract (&contract) so we can send a Cw721ReceiveMsg
-        self._transfer_nft(deps, &env, &info, &contract, &token_id)?;
+        // the receiver has to be one SendNft could reach
+        if !self.send_enabled(deps.storage)? {
+            return Err(ContractError::SendDisabled {});
+        }
+        self.check_receiver_allowed(deps.as_ref(), &contract)?;
 
-        // Here, we create a Cw721ReceiveMsg that has the sender (below)
-        // sender is who sent the token to the contract
-        // token_id is the token that was sent
-        // msg is the message that was sent with the token (Binary is a type that can be converted to a string). Could want to include addtional info (message) with the token
-        // ----
-        // Have to be sure that address of this contract must be known to the contract that is receiving the NFT (person receiving has to know the address of the contract being sent to him/her). This is different than the sender itself. 
-        // The sender is the person sending the token to the contract, but the contract itself must be known to the person receiving the token
-        // Otherwise, any contract can send an NFT to this contract and it will accept it
-        // ----
-        // Cw721ReceiveMsg can make itself into a binary or can make itself into a cosmos message (check definition of Cw721ReceiveMsg)
+        mint.owner = contract.clone();
+        let token_id = mint.token_id.clone();
+        let res = self.mint(deps.branch(), env, info.clone(), mint)?;
+
+        // same ReceiveNft as SendNft, and the same reply if it fails (there's no previous owner)
+        self.push_pending_send(deps.storage, &deps.api.addr_validate(&contract)?)?;
         let send = Cw721ReceiveMsg {
             sender: info.sender.to_string(),
-            token_id: token_id.clone(),
+            token_id,
             msg,
         };
-
-        // Send message
-        Ok(Response::new()
-        // chaining the send message to the response
-        // diff than add._submessage: used for ibc, won't fail the whole tx if the submessage fails
-        // .add_message: will fail the whole tx if the send message fails
-            .add_message(send.into_cosmos_msg(contract.clone())?)
-            .add_attribute("action", "send_nft")
-            .add_attribute("sender", info.sender)
-            .add_attribute("recipient", contract)
-            .add_attribute("token_id", token_id))
+        Ok(res
+            .add_submessage(SubMsg::reply_always(
+                send.into_cosmos_msg(contract.clone())?,
+                ReplyId::SendNft.id(),
+            ))
+            .add_attribute("recipient", contract))
     }
 
-    fn approve(
+    /// Mints on the authority of the off-chain signer rather than the minters or the mint phase,
+    /// once the signature over the MintAuthorization checks out
+    pub fn mint_signed(
         &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        spender: String,
-        token_id: String,
-        expires: Option<Expiration>,
+        msg: MintMsg<T>,
+        signature: Binary,
     ) -> Result<Response<C>, ContractError> {
-        self._update_approvals(deps, &env, &info, &spender, &token_id, true, expires)?;
+        let config = self.config(deps.storage)?;
+        let pubkey = config
+            .signer_pubkey
+            .ok_or(ContractError::InvalidMintSignature {})?;
+        let authorization = MintAuthorization {
+            contract: env.contract.address.to_string(),
+            msg,
+        };
+        let hash = Sha256::digest(to_vec(&authorization)?);
+        let valid = deps
+            .api
+            .secp256k1_verify(&hash, &signature, &pubkey)
+            .unwrap_or(false);
+        if !valid {
+            return Err(ContractError::InvalidMintSignature {});
+        }
+        // the token_id is the nonce: a signature is good for one mint, even after a burn
+        let msg = authorization.msg;
+        if self.signed_mints.has(deps.storage, &msg.token_id) {
+            return Err(ContractError::Claimed {});
+        }
+        self.signed_mints
+            .save(deps.storage, &msg.token_id, &Empty {})?;
+
+        // like Mint, minters don't pay
+        let payment = if self.is_minter(deps.storage, &info.sender)? {
+            vec![]
+        } else {
+            let mint_prices = self.mint_prices(deps.storage, Phase::Public)?;
+            self.mint_payment(deps.storage, &info.sender, &info.funds, &mint_prices)?
+        };
+        // an indexer on another chain may want to hear about the mint (see ibc.rs)
+        let packet = self.mint_packet_msg(deps.storage, &env, MintPacket::new(&env, &msg))?;
+        let token_id = msg.token_id.clone();
+        let token = self._mint(deps, &env, msg)?;
 
         Ok(Response::new()
-            .add_attribute("action", "approve")
-            .add_attribute("sender", info.sender)
-            .add_attribute("spender", spender)
+            .add_messages(payment)
+            .add_messages(packet)
+            .add_attribute("action", "mint_signed")
+            .add_attribute("minter", info.sender)
+            .add_attribute("owner", token.owner)
             .add_attribute("token_id", token_id))
     }
 
-    fn revoke(
+    /// Replaces the extension stored on a token, e.g. to level up a game item
+    pub fn update_extension(
         &self,
         deps: DepsMut,
-        env: Env,
+        _env: Env,
         info: MessageInfo,
-        spender: String,
         token_id: String,
+        extension: T,
     ) -> Result<Response<C>, ContractError> {
-        self._update_approvals(deps, &env, &info, &spender, &token_id, false, None)?;
+        // only the minter or the metadata updater may rewrite metadata
+        self.check_can_update_metadata(deps.as_ref(), &info)?;
+
+        // T is whatever extension the contract was built with, so we just swap it in
+        let mut token = self.load_token(deps.storage, &token_id)?;
+        token.extension = extension;
+        self.tokens.save(deps.storage, &token_id, &token)?;
 
         Ok(Response::new()
-            .add_attribute("action", "revoke")
+            .add_attribute("action", "update_extension")
             .add_attribute("sender", info.sender)
-            .add_attribute("spender", spender)
             .add_attribute("token_id", token_id))
     }
 
-    fn approve_all(
+    /// Sets or clears the address allowed to update metadata alongside the minter.
+    /// Can be called by a minter or the contract owner
+    pub fn set_metadata_updater(
         &self,
         deps: DepsMut,
-        env: Env,
+        _env: Env,
         info: MessageInfo,
-        operator: String,
-        expires: Option<Expiration>,
+        address: Option<String>,
     ) -> Result<Response<C>, ContractError> {
-        // reject expired data as invalid
-        let expires = expires.unwrap_or_default();
-        if expires.is_expired(&env.block) {
-            return Err(ContractError::Expired {});
-        }
+        self.check_minter_or_owner(deps.storage, &info.sender)?;
 
-        // set the operator for us
-        let operator_addr = deps.api.addr_validate(&operator)?;
-        self.operators
-            .save(deps.storage, (&info.sender, &operator_addr), &expires)?;
+        let updater = address
+            .as_deref()
+            .map(|addr| deps.api.addr_validate(addr))
+            .transpose()?;
+        self.metadata_updater.save(deps.storage, &updater)?;
 
         Ok(Response::new()
-            .add_attribute("action", "approve_all")
+            .add_attribute("action", "set_metadata_updater")
             .add_attribute("sender", info.sender)
-            .add_attribute("operator", operator))
+            .add_attribute("metadata_updater", address.unwrap_or_default()))
     }
 
-    fn revoke_all(
+    /// Sets a free-form tag on a token, overwriting the key's old value if it had one.
+    /// Can only be called by a minter or the contract owner
+    pub fn set_token_tag(
         &self,
         deps: DepsMut,
         _env: Env,
         info: MessageInfo,
-        operator: String,
+        token_id: String,
+        key: String,
+        value: String,
     ) -> Result<Response<C>, ContractError> {
-        let operator_addr = deps.api.addr_validate(&operator)?;
-        self.operators
-            .remove(deps.storage, (&info.sender, &operator_addr));
+        self.check_minter_or_owner(deps.storage, &info.sender)?;
+        self.load_token(deps.storage, &token_id)?;
+
+        // overwriting an existing key doesn't add a tag, so only new keys count against the cap
+        if !self.token_tags.has(deps.storage, (&token_id, &key)) {
+            let tag_count = self
+                .token_tags
+                .prefix(&token_id)
+                .keys(deps.storage, None, None, Order::Ascending)
+                .count() as u32;
+            if tag_count >= MAX_TOKEN_TAGS {
+                return Err(ContractError::TooManyTags {
+                    max: MAX_TOKEN_TAGS,
+                });
+            }
+        }
+        self.token_tags
+            .save(deps.storage, (&token_id, &key), &value)?;
 
         Ok(Response::new()
-            .add_attribute("action", "revoke_all")
+            .add_attribute("action", "set_token_tag")
             .add_attribute("sender", info.sender)
-            .add_attribute("operator", operator))
+            .add_attribute("token_id", token_id)
+            .add_attribute("key", key)
+            .add_attribute("value", value))
     }
 
-    fn burn(
+    /// Removes a tag from a token. Can only be called by a minter or the contract owner
+    pub fn remove_token_tag(
         &self,
         deps: DepsMut,
-        env: Env,
+        _env: Env,
         info: MessageInfo,
         token_id: String,
+        key: String,
     ) -> Result<Response<C>, ContractError> {
-        let token = self.tokens.load(deps.storage, &token_id)?;
-        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
-
-        self.tokens.remove(deps.storage, &token_id)?;
-        self.decrement_tokens(deps.storage)?;
+        self.check_minter_or_owner(deps.storage, &info.sender)?;
+        self.token_tags.remove(deps.storage, (&token_id, &key));
 
         Ok(Response::new()
-            .add_attribute("action", "burn")
+            .add_attribute("action", "remove_token_tag")
             .add_attribute("sender", info.sender)
-            .add_attribute("token_id", token_id))
+            .add_attribute("token_id", token_id)
+            .add_attribute("key", key))
     }
-}
 
-// helpers
-impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
-where
-    T: Serialize + DeserializeOwned + Clone,
-    C: CustomMsg,
-    E: CustomMsg,
-    Q: CustomMsg,
-{
-    pub fn _transfer_nft(
+    /// Lets a token skip the transfer fee and royalties, for promotional transfers.
+    /// Can only be called by a minter
+    pub fn set_royalty_exempt(
         &self,
         deps: DepsMut,
-        env: &Env,
-        info: &MessageInfo,
-        recipient: &str,
-        token_id: &str,
-    ) -> Result<TokenInfo<T>, ContractError> {
-        // takes the token (mutable), loads the token from the storage by token_id
-        // self.tokens would be an instance of IndexedMap, so we can use .load to get the token
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+        exempt: bool,
+    ) -> Result<Response<C>, ContractError> {
+        if !self.is_minter(deps.storage, &info.sender)? {
+            return Err(ContractError::NotMinter {});
+        }
+        let mut token = self.load_token(deps.storage, &token_id)?;
+        token.royalty_exempt = exempt;
+        self.tokens.save(deps.storage, &token_id, &token)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_royalty_exempt")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("exempt", exempt.to_string()))
+    }
+
+    /// Caps how many times a token can change hands in all, the transfers it already had included.
+    /// Can only be called by a minter
+    pub fn set_max_transfers(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+        max_transfers: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        if !self.is_minter(deps.storage, &info.sender)? {
+            return Err(ContractError::NotMinter {});
+        }
+        let mut token = self.load_token(deps.storage, &token_id)?;
+        token.max_transfers = max_transfers;
+        self.tokens.save(deps.storage, &token_id, &token)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_max_transfers")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute(
+                "max_transfers",
+                max_transfers.map(|max| max.to_string()).unwrap_or_default(),
+            ))
+    }
+
+    /// Like send_nft, but if the receiver fails on ReceiveNft the token goes back to its
+    /// previous owner (see reply) instead of the whole transaction failing. The receiver must
+    /// be a contract
+    #[allow(clippy::too_many_arguments)]
+    pub fn safe_send_nft(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract: String,
+        token_id: String,
+        msg: Binary,
+        wrap: bool,
+        with_owner: bool,
+    ) -> Result<Response<C>, ContractError> {
+        // only contracts can take the token, anything else would just be rolled back
+        if deps.querier.query_wasm_contract_info(&contract).is_err() {
+            return Err(ContractError::ReceiverNotContract { contract });
+        }
+        let previous = self.load_token(deps.storage, &token_id)?;
+        let receiver = deps.api.addr_validate(&contract)?;
+        let mut res = self._send_nft(
+            deps.branch(),
+            env,
+            info.clone(),
+            contract,
+            token_id.clone(),
+            msg,
+            wrap,
+            with_owner,
+        )?;
+        // the reply finds the receiver in the backup instead
+        self.pop_pending_send(deps.storage)?;
+        // a receiver may safe send another token from its ReceiveNft before this one's reply
+        // comes back, so every send still waiting takes the next slot and keeps its own backup.
+        // Replies come back innermost first, so a freed slot is never still in use
+        let slot = self
+            .safe_send_backups
+            .keys(deps.storage, None, None, Order::Ascending)
+            .count();
+        let id = safe_send_reply_id(slot as u32);
+        let backup = SafeSendBackup {
+            token_id,
+            contract: receiver,
+            previous,
+            sender: info.sender,
+            funds: info.funds,
+        };
+        self.safe_send_backups.save(deps.storage, id, &backup)?;
+        // only the same ReceiveNft goes out for now. The fee and hook wait for the reply, so a
+        // send that is undone doesn't pay or notify, and the reply always runs to clean up
+        res.messages.retain(|sub| sub.id == ReplyId::SendNft.id());
+        for sub in res.messages.iter_mut() {
+            sub.id = id;
+            sub.reply_on = ReplyOn::Always;
+        }
+        Ok(res)
+    }
+
+    /// Like send_nft, but the funds sent with this message are passed on to the receiver
+    pub fn send_nft_with_funds(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    ) -> Result<Response<C>, ContractError> {
+        if !self.send_enabled(deps.storage)? {
+            return Err(ContractError::SendDisabled {});
+        }
+        self.check_receiver_allowed(deps.as_ref(), &contract)?;
+        self.check_transfer_allowed(deps.as_ref(), &env, &info, &contract, &token_id)?;
+        // a transfer fee comes out of the attached funds, the receiver gets the rest
+        let (fee, funds) = self.split_transfer_fee(deps.storage, &token_id, &info.funds)?;
+        let fee_payment = match fee {
+            Some(fee) => vec![BankMsg::Send {
+                to_address: self.owner.load(deps.storage)?.to_string(),
+                amount: vec![fee],
+            }],
+            None => vec![],
+        };
+        let previous_owner = self.load_token(deps.storage, &token_id)?.owner;
+        self._transfer_nft(deps.branch(), &env, &info, &contract, &token_id)?;
+        self.push_pending_send(deps.storage, &deps.api.addr_validate(&contract)?)?;
+        let hook = self.transfer_hook_msg(deps.storage, &token_id, &previous_owner, &contract)?;
+
+        let send = Cw721ReceiveMsg {
+            sender: info.sender.to_string(),
+            token_id: token_id.clone(),
+            msg,
+        };
+
+        Ok(Response::new()
+            .add_submessage(SubMsg::reply_always(
+                send.into_cosmos_msg_with_funds(contract.clone(), funds)?,
+                ReplyId::SendNft.id(),
+            ))
+            .add_submessages(hook)
+            .add_messages(fee_payment)
+            .add_attribute("action", "send_nft_with_funds")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", contract)
+            .add_attribute("token_id", token_id)
+            .add_attribute("previous_owner", previous_owner))
+    }
+
+    /// Authorizes another address to mint, e.g. a parallel minting service
+    pub fn add_minter(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        minter: String,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        let minter_addr = deps.api.addr_validate(&minter)?;
+        if minter_addr == env.contract.address {
+            return Err(ContractError::InvalidMinter {});
+        }
+        self.minters.save(deps.storage, &minter_addr, &true)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "add_minter")
+            .add_attribute("sender", info.sender)
+            .add_attribute("minter", minter))
+    }
+
+    /// Revokes an address' right to mint. The last minter can't be removed
+    pub fn remove_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        minter: String,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        let minter_addr = deps.api.addr_validate(&minter)?;
+        if !self.is_minter(deps.storage, &minter_addr)? {
+            return Err(ContractError::MinterNotFound { minter });
+        }
+        // a collection with no minters could never mint again
+        let minter_count = self
+            .minters
+            .keys(deps.storage, None, None, Order::Ascending)
+            .take(2)
+            .count();
+        if minter_count < 2 {
+            return Err(ContractError::CannotRemoveLastMinter {});
+        }
+        self.minters.remove(deps.storage, &minter_addr);
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_minter")
+            .add_attribute("sender", info.sender)
+            .add_attribute("minter", minter))
+    }
+
+    /// Adds or removes a receiver contract from the SendNft allowlist
+    pub fn set_receiver_allowed(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        receiver: String,
+        allowed: bool,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        // removing rather than storing false keeps "empty means allow all" simple
+        let receiver_addr = deps.api.addr_validate(&receiver)?;
+        if allowed {
+            self.allowed_receivers
+                .save(deps.storage, &receiver_addr, &true)?;
+        } else {
+            self.allowed_receivers.remove(deps.storage, &receiver_addr);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_receiver_allowed")
+            .add_attribute("sender", info.sender)
+            .add_attribute("receiver", receiver)
+            .add_attribute("allowed", allowed.to_string()))
+    }
+
+    /// Points the transfer hook at a contract, or removes it with None.
+    /// Can only be called by the contract owner
+    pub fn set_transfer_hook(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        hook: Option<String>,
+        required: bool,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        match &hook {
+            Some(hook) => {
+                let contract = deps.api.addr_validate(hook)?;
+                self.transfer_hook
+                    .save(deps.storage, &TransferHook { contract, required })?;
+            }
+            None => self.transfer_hook.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_transfer_hook")
+            .add_attribute("sender", info.sender)
+            .add_attribute("hook", hook.unwrap_or_default())
+            .add_attribute("required", required.to_string()))
+    }
+
+    /// Transfers a token as its owner, on the strength of a permit the owner signed off-chain.
+    /// Whoever submits it (e.g. a relayer paying the gas) needs no approval of their own
+    pub fn transfer_with_permit(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        permit: TransferPermit,
+        signature: Binary,
+    ) -> Result<Response<C>, ContractError> {
+        if permit.expires.is_expired(&env.block) {
+            return Err(ContractError::PermitExpired {});
+        }
+        // a permit for another collection must not move a token with the same id here
+        if permit.contract != env.contract.address {
+            return Err(ContractError::InvalidPermitSignature {});
+        }
+
+        let owner = self.load_token(deps.storage, &permit.token_id)?.owner;
+        let expected = self
+            .permit_nonces
+            .may_load(deps.storage, &owner)?
+            .unwrap_or_default();
+        if permit.nonce != expected {
+            return Err(ContractError::InvalidPermitNonce { expected });
+        }
+
+        // the owner signs the sha256 of the permit's JSON with the key they registered
+        let pubkey = self
+            .permit_pubkeys
+            .may_load(deps.storage, &owner)?
+            .ok_or(ContractError::InvalidPermitSignature {})?;
+        let hash = Sha256::digest(to_vec(&permit)?);
+        let valid = deps
+            .api
+            .secp256k1_verify(&hash, &signature, &pubkey)
+            .unwrap_or(false);
+        if !valid {
+            return Err(ContractError::InvalidPermitSignature {});
+        }
+
+        // the token moves as if the owner had sent TransferNft, and the relayer pays any
+        // transfer fee once that is allowed
+        let owner_info = MessageInfo {
+            sender: owner.clone(),
+            funds: vec![],
+        };
+        self.check_transfer_allowed(
+            deps.as_ref(),
+            &env,
+            &owner_info,
+            &permit.recipient,
+            &permit.token_id,
+        )?;
+        let fee_payment =
+            self.transfer_fee_payment(deps.storage, &permit.token_id, &info.sender, &info.funds)?;
+        // use the nonce up, then move the token
+        let next_nonce = expected.checked_add(1).ok_or_else(|| {
+            StdError::from(OverflowError::new(OverflowOperation::Add, expected, 1))
+        })?;
+        self.permit_nonces.save(deps.storage, &owner, &next_nonce)?;
+        self._transfer_nft(
+            deps.branch(),
+            &env,
+            &owner_info,
+            &permit.recipient,
+            &permit.token_id,
+        )?;
+        let hook =
+            self.transfer_hook_msg(deps.storage, &permit.token_id, &owner, &permit.recipient)?;
+
+        Ok(Response::new()
+            .add_submessages(hook)
+            .add_messages(fee_payment)
+            .add_attribute("action", "transfer_with_permit")
+            .add_attribute("sender", owner)
+            .add_attribute("relayer", info.sender)
+            .add_attribute("recipient", permit.recipient)
+            .add_attribute("token_id", permit.token_id))
+    }
+
+    /// Registers the key the sender will sign transfer permits with. None stops accepting permits
+    pub fn set_permit_pubkey(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        pubkey: Option<Binary>,
+    ) -> Result<Response<C>, ContractError> {
+        match pubkey {
+            Some(pubkey) => self
+                .permit_pubkeys
+                .save(deps.storage, &info.sender, &pubkey)?,
+            None => self.permit_pubkeys.remove(deps.storage, &info.sender),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_permit_pubkey")
+            .add_attribute("sender", info.sender))
+    }
+
+    /// Replaces the key MintSigned authorizations must be signed with.
+    /// Can only be called by the contract owner
+    pub fn set_signer_pubkey(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        pubkey: Option<Binary>,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        let mut config = self.config(deps.storage)?;
+        config.signer_pubkey = pubkey.map(validate_signer_pubkey).transpose()?;
+        self.config.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_signer_pubkey")
+            .add_attribute("sender", info.sender))
+    }
+
+    /// Sends contract funds to the owner, never taking the balance below min_deposit.
+    /// Can only be called by the contract owner
+    pub fn withdraw(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Coin,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        let balance = deps
+            .querier
+            .query_balance(&env.contract.address, &amount.denom)?;
+        let reserved = match self.config(deps.storage)?.min_deposit {
+            Some(min_deposit) if min_deposit.denom == amount.denom => min_deposit.amount,
+            _ => Uint128::zero(),
+        };
+        let available = balance.amount.saturating_sub(reserved);
+        let withdrawn = amount.amount.min(available);
+        if withdrawn.is_zero() {
+            return Err(ContractError::NothingToWithdraw {});
+        }
+        let withdrawn = Coin::new(withdrawn.u128(), amount.denom);
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: owner.to_string(),
+                amount: vec![withdrawn.clone()],
+            })
+            .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender)
+            .add_attribute("amount", withdrawn.to_string()))
+    }
+
+    /// Moves minting to another phase, e.g. from the allowlist sale to the public one.
+    /// Can only be called by the contract owner
+    pub fn set_phase(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        phase: Phase,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        self.current_phase.save(deps.storage, &phase)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_phase")
+            .add_attribute("sender", info.sender)
+            .add_attribute("phase", format!("{:?}", phase)))
+    }
+
+    /// Picks the IBC channel mint packets go out on, or stops them with None.
+    /// Can only be called by the contract owner
+    pub fn set_mint_channel(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        channel_id: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        match &channel_id {
+            // only a channel that finished its handshake with us (see ibc.rs) can carry packets
+            Some(channel_id) => {
+                if !self.ibc_channels.has(deps.storage, channel_id) {
+                    return Err(ContractError::UnknownChannel {
+                        channel_id: channel_id.clone(),
+                    });
+                }
+                self.mint_channel.save(deps.storage, channel_id)?;
+            }
+            None => self.mint_channel.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_mint_channel")
+            .add_attribute("sender", info.sender)
+            .add_attribute("channel_id", channel_id.unwrap_or_default()))
+    }
+
+    /// Sets or clears the collection-level metadata uri. Can only be called by the contract owner
+    pub fn set_collection_uri(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        collection_uri: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        self.collection_uri.save(deps.storage, &collection_uri)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_collection_uri")
+            .add_attribute("sender", info.sender)
+            .add_attribute("collection_uri", collection_uri.unwrap_or_default()))
+    }
+
+    /// Returns a token someone sent to the contract's own address, from this collection or another
+    /// cw721 one. Can only be called by the contract owner
+    pub fn rescue_nft(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        collection: Option<String>,
+        token_id: String,
+        recipient: String,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        // naming this contract as the collection is the same as leaving it out
+        let collection = collection
+            .map(|collection| deps.api.addr_validate(&collection))
+            .transpose()?
+            .filter(|collection| *collection != env.contract.address);
+        let res = match collection {
+            Some(collection) => {
+                // the contract owns the token over there, so it can just transfer it
+                deps.api.addr_validate(&recipient)?;
+                let transfer = WasmMsg::Execute {
+                    contract_addr: collection.to_string(),
+                    msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                        recipient: recipient.clone(),
+                        token_id: token_id.clone(),
+                    })?,
+                    funds: vec![],
+                };
+                Response::new()
+                    .add_message(transfer)
+                    .add_attribute("collection", collection)
+            }
+            None => {
+                // only tokens stuck on the contract itself, this is no way to take other people's
+                if self.load_token(deps.storage, &token_id)?.owner != env.contract.address {
+                    return Err(ContractError::NotHeldByContract {});
+                }
+                // transfer as the contract, the token's owner
+                let contract_info = MessageInfo {
+                    sender: env.contract.address.clone(),
+                    funds: vec![],
+                };
+                self._transfer_nft(deps.branch(), &env, &contract_info, &recipient, &token_id)?;
+                let hook = self.transfer_hook_msg(
+                    deps.storage,
+                    &token_id,
+                    &env.contract.address,
+                    &recipient,
+                )?;
+                Response::new()
+                    .add_submessages(hook)
+                    .add_attribute("collection", env.contract.address)
+            }
+        };
+
+        Ok(res
+            .add_attribute("action", "rescue_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_id", token_id))
+    }
+
+    /// Turns transfers on or off for the whole collection. Can only be called by the contract owner
+    pub fn set_transfers_enabled(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        enabled: bool,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        self.transfers_enabled.save(deps.storage, &enabled)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_transfers_enabled")
+            .add_attribute("sender", info.sender)
+            .add_attribute("enabled", enabled.to_string()))
+    }
+
+    /// Turns sending to contracts on or off, leaving plain transfers alone.
+    /// Can only be called by the contract owner
+    pub fn set_send_enabled(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        enabled: bool,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        self.send_enabled.save(deps.storage, &enabled)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_send_enabled")
+            .add_attribute("sender", info.sender)
+            .add_attribute("enabled", enabled.to_string()))
+    }
+
+    /// Adds or removes an address from the mint allowlist. Can only be called by the contract owner
+    pub fn set_mint_allowed(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        address: String,
+        allowed: bool,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        let addr = deps.api.addr_validate(&address)?;
+        if allowed {
+            self.mint_allowlist.save(deps.storage, &addr, &true)?;
+        } else {
+            self.mint_allowlist.remove(deps.storage, &addr);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_mint_allowed")
+            .add_attribute("sender", info.sender)
+            .add_attribute("address", address)
+            .add_attribute("allowed", allowed.to_string()))
+    }
+
+    /// Renames the collection, e.g. after a rebrand. Fields left as None keep their current value.
+    /// Can only be called by the contract owner
+    pub fn update_contract_info(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        name: Option<String>,
+        symbol: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+
+        let mut contract_info = self.contract_info.load(deps.storage)?;
+        if let Some(name) = name {
+            validate_contract_info_field("name", &name, MAX_NAME_LENGTH)?;
+            contract_info.name = name;
+        }
+        if let Some(symbol) = symbol {
+            validate_contract_info_field("symbol", &symbol, MAX_SYMBOL_LENGTH)?;
+            contract_info.symbol = symbol;
+        }
+        self.contract_info.save(deps.storage, &contract_info)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_contract_info")
+            .add_attribute("sender", info.sender)
+            .add_attribute("name", contract_info.name)
+            .add_attribute("symbol", contract_info.symbol))
+    }
+
+    /// Approves the spender on every listed token, which the sender must control
+    pub fn approve_batch(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        token_ids: Vec<String>,
+        expires: Option<Expiration>,
+    ) -> Result<Response<C>, ContractError> {
+        // any token failing the permission check errors out, reverting the ones before it.
+        // Like Approve, each token's resulting approval list goes out with its token_id
+        let mut token_attributes = vec![];
+        for token_id in &token_ids {
+            let token = self._update_approvals(
+                deps.branch(),
+                &env,
+                &info,
+                &spender,
+                token_id,
+                true,
+                expires,
+            )?;
+            token_attributes.push(("token_id", token_id.clone()));
+            token_attributes.push(("approvals", approvals_attribute(&token.approvals)));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "approve_batch")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("approved_count", token_ids.len().to_string())
+            .add_attributes(token_attributes))
+    }
+
+    /// Removes the spender's approval from every listed token the sender controls
+    pub fn revoke_batch(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_ids: Vec<String>,
+        spender: String,
+    ) -> Result<Response<C>, ContractError> {
+        let spender_addr = deps.api.addr_validate(&spender)?;
+
+        let mut revoked_count = 0u32;
+        let mut token_attributes = vec![];
+        for token_id in &token_ids {
+            let mut token = self.load_token(deps.storage, token_id)?;
+            // ensure we have permissions on every token, not just the ones we revoke on
+            self.check_can_approve(deps.as_ref(), &env, &info, &token)?;
+
+            // skip tokens the spender was never approved on
+            let before = token.approvals.len();
+            token.approvals.retain(|apr| apr.spender != spender_addr);
+            if token.approvals.len() != before {
+                self.tokens.save(deps.storage, token_id, &token)?;
+                self.spender_approvals
+                    .remove(deps.storage, (&spender_addr, token_id));
+                revoked_count += 1;
+                // like Revoke, the approval list left on the token goes out with its token_id
+                token_attributes.push(("token_id", token_id.clone()));
+                token_attributes.push(("approvals", approvals_attribute(&token.approvals)));
+            }
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_batch")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("revoked_count", revoked_count.to_string())
+            .add_attributes(token_attributes))
+    }
+
+    /// Burns up to `limit` tokens held by `owner`, for decommissioning. Call again while
+    /// the `has_more` attribute is true to finish the job
+    pub fn burn_all_owned_by(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        owner: String,
+        limit: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        if !self.is_minter(deps.storage, &info.sender)? {
+            return Err(ContractError::NotMinter {});
+        }
+        // an admin tool doesn't get to override a collection that forbids burning
+        if self.config(deps.storage)?.burn_policy == BurnPolicy::Disabled {
+            return Err(ContractError::BurnNotAllowed {});
+        }
+
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let limit = limit.unwrap_or(DEFAULT_BURN_LIMIT).min(MAX_BURN_LIMIT) as usize;
+        // read one past the limit to find out if another call will be needed
+        let mut token_ids: Vec<String> = self
+            .tokens
+            .idx
+            .owner
+            .prefix(owner_addr)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .take(limit + 1)
+            .collect::<StdResult<_>>()?;
+        let has_more = token_ids.len() > limit;
+        token_ids.truncate(limit);
+
+        for token_id in &token_ids {
+            let token = self.load_token(deps.storage, token_id)?;
+            self.remove_token(deps.storage, &env, token_id, &token)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "burn_all_owned_by")
+            .add_attribute("sender", info.sender)
+            .add_attribute("owner", owner)
+            .add_attribute("burned_count", token_ids.len().to_string())
+            .add_attribute("has_more", has_more.to_string())
+            .add_attributes(token_ids.into_iter().map(|token_id| ("token_id", token_id))))
+    }
+
+    /// Burns a token without its owner's consent, e.g. for compliance. The reason is recorded
+    /// in the response for the audit trail. Can only be called by the contract owner
+    pub fn admin_burn(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        reason: String,
+    ) -> Result<Response<C>, ContractError> {
+        let owner = self.owner.load(deps.storage)?;
+        if info.sender != owner {
+            return Err(ContractError::NotContractOwner {});
+        }
+        if reason.trim().is_empty() {
+            return Err(ContractError::MissingBurnReason {});
+        }
+        // like BurnAllOwnedBy, this doesn't override a collection that forbids burning
+        if self.config(deps.storage)?.burn_policy == BurnPolicy::Disabled {
+            return Err(ContractError::BurnNotAllowed {});
+        }
+
+        // no RedeemMsg: the holder isn't redeeming anything
+        let token = self.load_token(deps.storage, &token_id)?;
+        self.remove_token(deps.storage, &env, &token_id, &token)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "admin_burn")
+            .add_attribute("sender", info.sender)
+            .add_attribute("previous_owner", token.owner)
+            .add_attribute("token_id", token_id)
+            .add_attribute("reason", reason))
+    }
+
+    /// Marks (or unmarks) a spender whose approval on this token survives transfers
+    pub fn set_persistent_approval(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+        spender: String,
+        persistent: bool,
+    ) -> Result<Response<C>, ContractError> {
+        let mut token = self.load_token(deps.storage, &token_id)?;
+        // an approval outliving the owner's tenure is the owner's call alone, not an operator's
+        if token.owner != info.sender {
+            return Err(ContractError::NotTokenOwner {});
+        }
+
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        token
+            .persistent_approvals
+            .retain(|addr| *addr != spender_addr);
+        if persistent {
+            token.persistent_approvals.push(spender_addr);
+        }
+        self.tokens.save(deps.storage, &token_id, &token)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_persistent_approval")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("persistent", persistent.to_string()))
+    }
+}
+
+impl<'a, T, C, E, Q> Cw721Execute<T, C> for Cw721Contract<'a, T, C, E, Q>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    C: CustomMsg,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    type Err = ContractError;
+
+    fn transfer_nft(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: String,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        // collections can charge a flat fee on every transfer, once it's allowed
+        self.check_transfer_allowed(deps.as_ref(), &env, &info, &recipient, &token_id)?;
+        let fee_payment =
+            self.transfer_fee_payment(deps.storage, &token_id, &info.sender, &info.funds)?;
+        let minimal_events = self.config(deps.storage)?.minimal_events;
+        // marketplaces want the prior owner in the events, the sender may just be a spender
+        let previous_owner = self.load_token(deps.storage, &token_id)?.owner;
+        // calls self helper function _ so we don't duplicate fn names)
+        self._transfer_nft(deps.branch(), &env, &info, &recipient, &token_id)?;
+        let hook = self.transfer_hook_msg(deps.storage, &token_id, &previous_owner, &recipient)?;
+
+        // could be used to get more information about the token
+        // let token = self._transfer_nft(deps, &env, &info, &recipient, &token_id)?; 
+
+        // You could make a TransferNFTMsg struct that contains info, recipient, and token_id if you wanted to
+
+        let res = Response::new()
+            .add_submessages(hook)
+            .add_messages(fee_payment)
+            .add_attribute("action", "transfer_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("previous_owner", previous_owner)
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_id", token_id);
+        Ok(trim_attributes(res, minimal_events))
+    }
+
+    // doesn't just change the owner of the nft, it also takes a transaction
+    fn send_nft(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    ) -> Result<Response<C>, ContractError> {
+        self._send_nft(deps, env, info, contract, token_id, msg, true, false)
+    }
+
+    fn approve(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response<C>, ContractError> {
+        let minimal_events = self.config(deps.storage)?.minimal_events;
+        let token =
+            self._update_approvals(deps, &env, &info, &spender, &token_id, true, expires)?;
+
+        let res = Response::new()
+            .add_attribute("action", "approve")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("approvals", approvals_attribute(&token.approvals));
+        Ok(trim_attributes(res, minimal_events))
+    }
+
+    fn revoke(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self._update_approvals(deps, &env, &info, &spender, &token_id, false, None)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke")
+            .add_attribute("sender", info.sender)
+            .add_attribute("spender", spender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("approvals", approvals_attribute(&token.approvals)))
+    }
+
+    fn approve_all(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response<C>, ContractError> {
+        // an unscoped grant covers every token the owner has
+        self.approve_all_scoped(deps, env, info, operator, expires, None)
+    }
+
+    fn revoke_all(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        operator: String,
+    ) -> Result<Response<C>, ContractError> {
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        self.operators
+            .remove(deps.storage, (&info.sender, &operator_addr));
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_all")
+            .add_attribute("sender", info.sender)
+            .add_attribute("operator", operator))
+    }
+
+    fn burn(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.load_token(deps.storage, &token_id)?;
+        // who may burn depends on the collection's policy
+        let config = self.config(deps.storage)?;
+        match config.burn_policy {
+            BurnPolicy::Owner => self.check_can_send(deps.as_ref(), &env, &info, &token)?,
+            BurnPolicy::MinterOnly => {
+                if !self.is_minter(deps.storage, &info.sender)? {
+                    return Err(ContractError::NotMinter {});
+                }
+            }
+            BurnPolicy::Disabled => return Err(ContractError::BurnNotAllowed {}),
+        }
+
+        self.remove_token(deps.storage, &env, &token_id, &token)?;
+
+        // redeemable collections tell their fulfillment contract; as a plain message (not a
+        // submessage), a failed redemption reverts the burn
+        let redemption = config
+            .redemption_contract
+            .map(|contract| {
+                RedeemMsg {
+                    token_id: token_id.clone(),
+                    redeemer: token.owner.to_string(),
+                    token_uri: token.token_uri,
+                }
+                .into_cosmos_msg(contract)
+            })
+            .transpose()?;
+
+        Ok(Response::new()
+            .add_messages(redemption)
+            .add_attribute("action", "burn")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+}
+
+// helpers
+impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    C: CustomMsg,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    /// send_nft, with a choice of how the ReceiveNft message is encoded: wrapped in the
+    /// `receive_nft` variant (the cw721 standard), or the bare Cw721ReceiveMsg for receivers
+    /// that expect it directly. `with_owner` sends the Cw721ReceiveWithOwnerMsg instead, for
+    /// receivers that need the previous owner
+    #[allow(clippy::too_many_arguments)]
+    pub fn _send_nft(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract: String,
+        token_id: String,
+        msg: Binary,
+        wrap: bool,
+        with_owner: bool,
+    ) -> Result<Response<C>, ContractError> {
+        // Transfer token (helper function)
+        // _transfer_nft is being reused as a helper function
+        // sending the nft to a contract (&contract) so we can send a Cw721ReceiveMsg
+        // refuse contracts that aren't on the allowlist, so tokens don't get stuck
+        // (or all of them, if the collection turned sending off)
+        if !self.send_enabled(deps.storage)? {
+            return Err(ContractError::SendDisabled {});
+        }
+        self.check_receiver_allowed(deps.as_ref(), &contract)?;
+        self.check_transfer_allowed(deps.as_ref(), &env, &info, &contract, &token_id)?;
+        let fee_payment =
+            self.transfer_fee_payment(deps.storage, &token_id, &info.sender, &info.funds)?;
+        let config = self.config(deps.storage)?;
+        // remember who owned it before, the sender may just be an approved spender or operator
+        let previous = self.load_token(deps.storage, &token_id)?;
+        let previous_owner = previous.owner;
+        let mut token = self._transfer_nft(deps.branch(), &env, &info, &contract, &token_id)?;
+        // escrow-style collections keep the approvals the transfer just cleared
+        if config.keep_approvals_on_send {
+            self.index_approvals(deps.storage, &token_id, &previous.approvals)?;
+            token.approvals = previous.approvals;
+            self.tokens.save(deps.storage, &token_id, &token)?;
+        }
+        // remember the receiver, in case it rejects the token (see reply)
+        self.push_pending_send(deps.storage, &deps.api.addr_validate(&contract)?)?;
+        let hook = self.transfer_hook_msg(deps.storage, &token_id, &previous_owner, &contract)?;
+
+        // Here, we create a Cw721ReceiveMsg that has the sender (below)
+        // sender is who sent the token to the contract
+        // token_id is the token that was sent
+        // msg is the message that was sent with the token (Binary is a type that can be converted to a string). Could want to include addtional info (message) with the token
+        // ----
+        // Have to be sure that address of this contract must be known to the contract that is receiving the NFT (person receiving has to know the address of the contract being sent to him/her). This is different than the sender itself. 
+        // The sender is the person sending the token to the contract, but the contract itself must be known to the person receiving the token
+        // Otherwise, any contract can send an NFT to this contract and it will accept it
+        // ----
+        // Cw721ReceiveMsg can make itself into a binary or can make itself into a cosmos message (check definition of Cw721ReceiveMsg)
+        let send = Cw721ReceiveMsg {
+            sender: info.sender.to_string(),
+            token_id: token_id.clone(),
+            msg,
+        };
+        let receive_msg = WasmMsg::Execute {
+            contract_addr: contract.clone(),
+            msg: receive_nft_binary(send, &previous_owner, wrap, with_owner)?,
+            funds: vec![],
+        };
+
+        // Send message
+        // the reply turns an opaque wasm failure into ReceiverRejected, and clears the pending send
+        let res = Response::new()
+        // chaining the send message to the response
+        // diff than add._submessage: used for ibc, won't fail the whole tx if the submessage fails
+        // .add_message: will fail the whole tx if the send message fails
+            .add_submessage(SubMsg::reply_always(receive_msg, ReplyId::SendNft.id()))
+            .add_submessages(hook)
+            .add_messages(fee_payment)
+            .add_attribute("action", "send_nft")
+            .add_attribute("sender", info.sender)
+            .add_attribute("previous_owner", previous_owner)
+            .add_attribute("recipient", contract)
+            .add_attribute("token_id", token_id);
+        Ok(trim_attributes(res, config.minimal_events))
+    }
+
+    /// the checks _transfer_nft makes, without moving anything. Lets callers work out a
+    /// transfer fee only once the transfer itself is allowed
+    pub fn check_transfer_allowed(
+        &self,
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+        recipient: &str,
+        token_id: &str,
+    ) -> Result<(), ContractError> {
+        if !self.transfers_enabled(deps.storage)? {
+            return Err(ContractError::TransfersDisabled {});
+        }
+        let token = self.load_token(deps.storage, token_id)?;
+        let recipient = deps.api.addr_validate(recipient)?;
+        self.check_can_transfer(deps, env, info, &token, Some(&recipient))
+    }
+
+    pub fn _transfer_nft(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        recipient: &str,
+        token_id: &str,
+    ) -> Result<TokenInfo<T>, ContractError> {
+        // takes the token (mutable), loads the token from the storage by token_id
+        // self.tokens would be an instance of IndexedMap, so we can use .load to get the token
         // here you don't only pass in the storage, but also the key (token_id)
-        let mut token = self.tokens.load(deps.storage, token_id)?;
-        // ensure we have permissions
-        self.check_can_send(deps.as_ref(), env, info, &token)?;
+        // every transfer and send comes through here, so this covers them all
+        if !self.transfers_enabled(deps.storage)? {
+            return Err(ContractError::TransfersDisabled {});
+        }
+        let mut token = self.load_token(deps.storage, token_id)?;
         // set owner and remove existing approvals
         // set owner to recipient (recipient is a string)
-        token.owner = deps.api.addr_validate(recipient)?;
-        // clear approvals, set to empty vector
-        token.approvals = vec![];
+        let recipient = deps.api.addr_validate(recipient)?;
+        // ensure we have permissions, and that nothing else holds the token back
+        self.check_can_transfer(deps.as_ref(), env, info, &token, Some(&recipient))?;
+        let transfer_count = token.transfer_count.checked_add(1).ok_or_else(|| {
+            StdError::from(OverflowError::new(
+                OverflowOperation::Add,
+                token.transfer_count,
+                1,
+            ))
+        })?;
+        // move one unit of balance from the old owner to the new one
+        self.decrement_balance(deps.storage, &token.owner)?;
+        self.increment_balance(deps.storage, &recipient)?;
+        let previous_owner = std::mem::replace(&mut token.owner, recipient);
+        // clear approvals, set to empty vector (and from the spender index)
+        // except those the owner marked as persistent, which carry over to the new owner
+        let (kept, cleared): (Vec<Approval>, Vec<Approval>) = token
+            .approvals
+            .into_iter()
+            .partition(|apr| token.persistent_approvals.contains(&apr.spender));
+        self.unindex_approvals(deps.storage, token_id, &cleared);
+        token.approvals = kept;
+        // remember when the token last changed hands, and how often it has
+        token.last_transfer = env.block.height;
+        token.transfer_count = transfer_count;
+        self.record_history(
+            deps.storage,
+            env,
+            token_id,
+            "transfer",
+            Some(&previous_owner),
+            Some(&token.owner),
+        )?;
         // save the token back to the storage
         self.tokens.save(deps.storage, token_id, &token)?; 
         // respond Ok with the token (the main function called will respond with the token (with add_attribute))
         Ok(token)
     } // could have used .update instead of .load and .save
 
+    /// Deletes a burned token and updates the counters and approval index.
+    /// Callers do the authorization
+    pub fn remove_token(
+        &self,
+        storage: &mut dyn Storage,
+        env: &Env,
+        token_id: &str,
+        token: &TokenInfo<T>,
+    ) -> StdResult<()> {
+        self.unindex_approvals(storage, token_id, &token.approvals);
+        self.tokens.remove(storage, token_id)?;
+        // a token minted again under this id starts without the old one's tags
+        let tag_keys: Vec<String> = self
+            .token_tags
+            .prefix(token_id)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        for key in tag_keys {
+            self.token_tags.remove(storage, (token_id, &key));
+        }
+        // the history stays, it is the provenance of the token_id
+        self.record_history(storage, env, token_id, "burn", Some(&token.owner), None)?;
+        self.increment_burned_total(storage)?;
+        self.decrement_tokens(storage)?;
+        self.decrement_balance(storage, &token.owner)?;
+        Ok(())
+    }
+
+    /// Creates a token without checking who is asking. Callers do the authorization
+    pub fn _mint(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        msg: MintMsg<T>,
+    ) -> Result<TokenInfo<T>, ContractError> {
+        // everything that can be checked from the message alone (id, uri, hash, edition)
+        let max_length = self
+            .config(deps.storage)?
+            .max_token_id_length
+            .unwrap_or(DEFAULT_MAX_TOKEN_ID_LENGTH);
+        msg.validate(max_length, MAX_TOKEN_URI_LENGTH)?;
+
+        // the owner has to be a valid address
+        // if the collection asks for it, also refuse contracts (only contracts have contract info)
+        let owner = deps.api.addr_validate(&msg.owner)?;
+        if self.config(deps.storage)?.reject_contract_owners
+            && deps.querier.query_wasm_contract_info(&owner).is_ok()
+        {
+            return Err(ContractError::CannotMintToContract {});
+        }
+        // a capped collection counts either the tokens still around, so burns make room again,
+        // or every token ever minted
+        let config = self.config(deps.storage)?;
+        if let Some(max_supply) = config.max_supply {
+            let supply = if config.burns_free_supply {
+                self.token_count(deps.storage)?
+            } else {
+                self.minted_total(deps.storage)?
+            };
+            if supply >= max_supply {
+                return Err(ContractError::MaxSupplyReached { max_supply });
+            }
+        }
+        // the new owner may already hold as many as the collection allows one address
+        self.check_owner_limit(deps.storage, &owner)?;
+
+        // an omitted extension falls back to the collection's default one, if it has one
+        let extension = self.extension_or_default(deps.storage, msg.extension)?;
+
+        // create the token
+        // makes a TokenInfo struct that we will save to storage
+        let token = TokenInfo {
+            owner,
+            approvals: vec![],
+            persistent_approvals: vec![],
+            token_uri: msg.token_uri,
+            // record the height the token was minted at, for analytics and vesting
+            mint_block: env.block.height,
+            last_transfer: env.block.height,
+            // minted_total is bumped below, once the token is saved
+            mint_seq: self.next_mint_seq(deps.storage)?,
+            extension,
+            metadata_hash: msg.metadata_hash,
+            edition: msg.edition,
+            edition_of: msg.edition_of,
+            royalty_exempt: false,
+            max_transfers: None,
+            transfer_count: 0,
+        };
+        // IndexMap is a map with additional index functionality
+        // Called update function
+        // pass in storage
+        // pass in token_id (msg.id) we are trying to update
+        // pass in old token (the token we just created) and matching it with the token_id
+        // If it matches, the token already claimed and we return an error
+        // If it doesn't match, it's available and send the token to the update function, saving the token to storage
+        self.tokens
+            .update(deps.storage, &msg.token_id, |old| match old {
+                Some(_) => Err(ContractError::Claimed {}),
+                None => Ok(token.clone()), // token needs to be cloned
+            })?;
+
+        // We increment the number of tokens in the contract (function in state.rs)
+        self.increment_minted_total(deps.storage)?;
+        self.increment_tokens(deps.storage)?;
+        // and the new owner's balance
+        self.increment_balance(deps.storage, &token.owner)?;
+        self.record_history(
+            deps.storage,
+            env,
+            &msg.token_id,
+            "mint",
+            None,
+            Some(&token.owner),
+        )?;
+        Ok(token)
+    }
+
+    /// Mints each of `mints` in order, failing the whole batch if any one fails
+    pub fn _batch_mint(
+        &self,
+        mut deps: DepsMut,
+        env: &Env,
+        mints: Vec<MintMsg<T>>,
+    ) -> Result<usize, ContractError> {
+        if mints.len() > MAX_BATCH_MINT {
+            return Err(ContractError::BatchTooLarge {
+                max: MAX_BATCH_MINT,
+            });
+        }
+        let count = mints.len();
+        for msg in mints {
+            self._mint(deps.branch(), env, msg)?;
+        }
+        Ok(count)
+    }
+
+    /// approve_all, optionally limited to tokens minted at or after min_token_height
+    pub fn approve_all_scoped(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+        expires: Option<Expiration>,
+        min_token_height: Option<u64>,
+    ) -> Result<Response<C>, ContractError> {
+        // high-security collections only allow approvals for a single token
+        if !self.config(deps.storage)?.operators_enabled {
+            return Err(ContractError::OperatorsDisabled {});
+        }
+        // reject expired data as invalid
+        let expires = self.approval_expiration(deps.storage, &env, expires)?;
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+
+        // set the operator for us
+        // grants are always keyed by the sender, so an operator can only ever grant over their
+        // own account and never hand out further operators for an owner they merely operate
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        // the sender already controls their own tokens
+        if operator_addr == info.sender {
+            return Err(ContractError::ApprovalToSelf {});
+        }
+        let grant = OperatorGrant {
+            expires,
+            min_token_height,
+        };
+        self.operators
+            .save(deps.storage, (&info.sender, &operator_addr), &grant)?;
+
+        let mut res = Response::new()
+            .add_attribute("action", "approve_all")
+            .add_attribute("sender", info.sender)
+            .add_attribute("operator", operator);
+        if let Some(height) = min_token_height {
+            res = res.add_attribute("min_token_height", height.to_string());
+        }
+        Ok(res)
+    }
+
+    /// loads a token, returning a typed error that names the token_id if it doesn't exist
+    pub fn load_token(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+    ) -> Result<TokenInfo<T>, ContractError> {
+        self.tokens
+            .may_load(storage, token_id)?
+            .ok_or_else(|| ContractError::TokenNotFound {
+                token_id: token_id.to_string(),
+            })
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn _update_approvals(
         &self,
@@ -372,24 +2216,41 @@ where
         add: bool,
         expires: Option<Expiration>,
     ) -> Result<TokenInfo<T>, ContractError> {
-        let mut token = self.tokens.load(deps.storage, token_id)?;
+        let mut token = self.load_token(deps.storage, token_id)?;
         // ensure we have permissions
         self.check_can_approve(deps.as_ref(), env, info, &token)?;
 
         // update the approval list (remove any for the same spender before adding)
         let spender_addr = deps.api.addr_validate(spender)?;
         token.approvals.retain(|apr| apr.spender != spender_addr);
+        self.spender_approvals
+            .remove(deps.storage, (&spender_addr, token_id));
 
         // only difference between approve and revoke
         if add {
+            // the owner already controls the token, approving them is a no-op
+            if spender_addr == token.owner {
+                return Err(ContractError::ApprovalToSelf {});
+            }
             // reject expired data as invalid
-            let expires = expires.unwrap_or_default();
+            let expires = self.approval_expiration(deps.storage, env, expires)?;
             if expires.is_expired(&env.block) {
                 return Err(ContractError::Expired {});
             }
+            let delay = self.config(deps.storage)?.approval_delay_blocks;
+            let active_from = env.block.height.checked_add(delay).ok_or_else(|| {
+                StdError::from(OverflowError::new(
+                    OverflowOperation::Add,
+                    env.block.height,
+                    delay,
+                ))
+            })?;
+            self.spender_approvals
+                .save(deps.storage, (&spender_addr, token_id), &Empty {})?;
             let approval = Approval {
                 spender: spender_addr,
                 expires,
+                active_from,
             };
             token.approvals.push(approval);
         }
@@ -399,6 +2260,28 @@ where
         Ok(token)
     }
 
+    /// the expiration to use for a grant; when none is given, grants last default_approval_duration
+    /// blocks if the collection set one, and never expire otherwise
+    pub fn approval_expiration(
+        &self,
+        storage: &dyn Storage,
+        env: &Env,
+        expires: Option<Expiration>,
+    ) -> StdResult<Expiration> {
+        if let Some(expires) = expires {
+            return Ok(expires);
+        }
+        Ok(match self.config(storage)?.default_approval_duration {
+            Some(blocks) => {
+                let height = env.block.height.checked_add(blocks).ok_or_else(|| {
+                    OverflowError::new(OverflowOperation::Add, env.block.height, blocks)
+                })?;
+                Expiration::AtHeight(height)
+            }
+            None => Expiration::Never {},
+        })
+    }
+
     /// returns true iff the sender can execute approve or reject on the contract
     pub fn check_can_approve(
         &self,
@@ -411,19 +2294,19 @@ where
         if token.owner == info.sender {
             return Ok(());
         }
-        // operator can approve
+        // operator can approve, as long as the token falls within the grant's scope
         let op = self
             .operators
             .may_load(deps.storage, (&token.owner, &info.sender))?;
         match op {
-            Some(ex) => {
-                if ex.is_expired(&env.block) {
-                    Err(ContractError::Unauthorized {})
+            Some(grant) => {
+                if grant.is_expired(&env.block) || !grant.covers(token.mint_block) {
+                    Err(ContractError::NotOwnerOrApproved {})
                 } else {
                     Ok(())
                 }
             }
-            None => Err(ContractError::Unauthorized {}),
+            None => Err(ContractError::NotOwnerOrApproved {}),
         }
     }
 
@@ -443,11 +2326,12 @@ where
         // any non-expired token approval can send
         // if this token has any approvals, then we check if the approvals are not expired
         // if the token has approvals and none of them are expired, then we return empty Ok result
-        if token
+        // (once the collection's approval delay has passed, if it has one)
+        let approval = token
             .approvals
             .iter()
-            .any(|apr| apr.spender == info.sender && !apr.is_expired(&env.block))
-        {
+            .find(|apr| apr.spender == info.sender && !apr.is_expired(&env.block));
+        if approval.is_some_and(|apr| apr.is_active(&env.block)) {
             return Ok(());
         }
 
@@ -457,19 +2341,417 @@ where
             .operators
             .may_load(deps.storage, (&token.owner, &info.sender))?;
         match op {
-            // check if the operator is expired
-            // if it is expired then we return an error
-            // if it is not expired, then we return empty Ok result
-            Some(ex) => {
-                if ex.is_expired(&env.block) {
-                    Err(ContractError::Unauthorized {})
-                } else {
-                    Ok(())
+            // check if the operator is expired or the token was minted before the grant's scope
+            // if it is not expired (and in scope), then we return empty Ok result
+            // if it is expired, say so, so frontends can ask the owner to approve again
+            Some(grant) if grant.covers(token.mint_block) => {
+                if grant.is_expired(&env.block) {
+                    return Err(ContractError::OperatorExpired {});
                 }
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        // an approval still waiting out the delay says when it can be used
+        if let Some(approval) = approval {
+            return Err(ContractError::ApprovalNotYetActive {
+                active_from: approval.active_from,
+            });
+        }
+        // likewise for a token approval that ran out, as opposed to never having had one
+        if token.approvals.iter().any(|apr| apr.spender == info.sender) {
+            return Err(ContractError::ApprovalExpired {});
+        }
+        Err(ContractError::NotOwnerOrApproved {})
+    }
+
+    // without the "ibc" feature no channel can be opened, so there's never a mint packet
+    #[cfg(not(feature = "ibc"))]
+    pub fn mint_packet_msg(
+        &self,
+        _storage: &dyn Storage,
+        _env: &Env,
+        _packet: MintPacket,
+    ) -> StdResult<Option<cosmwasm_std::CosmosMsg<C>>> {
+        Ok(None)
+    }
+
+    // with mint prices, the funds must cover any one of them and are passed on to the owner.
+    // anything paid over the price, in the price's denom, goes back to the sender instead
+    fn mint_payment(
+        &self,
+        storage: &dyn Storage,
+        sender: &Addr,
+        funds: &[Coin],
+        mint_prices: &[Coin],
+    ) -> Result<Vec<BankMsg>, ContractError> {
+        if mint_prices.is_empty() {
+            return Ok(vec![]);
+        }
+        let price = mint_prices
+            .iter()
+            .find(|price| has_coins(funds, price))
+            .ok_or(ContractError::NoAcceptedPaymentProvided {})?;
+
+        // the owner gets the price once, however many coins of its denom were attached, and
+        // everything paid over it in that denom goes back in a single refund
+        let mut paid = Uint128::zero();
+        let mut payment = vec![];
+        for coin in funds {
+            if coin.denom != price.denom {
+                payment.push(coin.clone());
+                continue;
+            }
+            if !payment.contains(price) {
+                payment.push(price.clone());
+            }
+            paid = paid.checked_add(coin.amount).map_err(StdError::from)?;
+        }
+        let excess = paid.checked_sub(price.amount).map_err(StdError::from)?;
+        let mut msgs = vec![BankMsg::Send {
+            to_address: self.owner.load(storage)?.to_string(),
+            amount: payment,
+        }];
+        if !excess.is_zero() {
+            msgs.push(BankMsg::Send {
+                to_address: sender.to_string(),
+                amount: vec![Coin::new(excess.u128(), &price.denom)],
+            });
+        }
+        Ok(msgs)
+    }
+
+    // fails if `owner` already holds max_per_owner tokens, so it can't be given another.
+    // reads the balance counter, so the check costs the same however many tokens they hold
+    fn check_owner_limit(&self, storage: &dyn Storage, owner: &Addr) -> Result<(), ContractError> {
+        if let Some(max_per_owner) = self.config(storage)?.max_per_owner {
+            if self.balance(storage, owner)? >= max_per_owner {
+                return Err(ContractError::OwnerLimitReached { max_per_owner });
+            }
+        }
+        Ok(())
+    }
+
+    // uses up one of the sender's free mints, if the collection gives allowlisted addresses any
+    // and they have some left
+    fn take_free_mint(
+        &self,
+        storage: &mut dyn Storage,
+        free_mints: Option<u32>,
+        sender: &Addr,
+    ) -> StdResult<bool> {
+        let free_mints = match free_mints {
+            Some(free_mints) => free_mints,
+            None => return Ok(false),
+        };
+        if !self.mint_allowlist.has(storage, sender) {
+            return Ok(false);
+        }
+        let used = self
+            .free_mints_used
+            .may_load(storage, sender)?
+            .unwrap_or_default();
+        if used >= free_mints {
+            return Ok(false);
+        }
+        self.free_mints_used.save(storage, sender, &(used + 1))?;
+        Ok(true)
+    }
+
+    /// returns the collection's transfer fee, if it has one, once `funds` are known to cover it.
+    /// Royalty-exempt tokens don't pay it
+    pub fn check_transfer_fee(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+        funds: &[Coin],
+    ) -> Result<Option<Coin>, ContractError> {
+        if self.load_token(storage, token_id)?.royalty_exempt {
+            return Ok(None);
+        }
+        match self.config(storage)?.transfer_fee {
+            Some(fee) if !has_coins(funds, &fee) => {
+                Err(ContractError::InsufficientTransferFee { fee })
+            }
+            fee => Ok(fee),
+        }
+    }
+
+    /// splits `funds` into the collection's transfer fee, if the token pays one, and what's left
+    /// of them. Fails if the funds don't cover the fee
+    pub fn split_transfer_fee(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+        funds: &[Coin],
+    ) -> Result<(Option<Coin>, Vec<Coin>), ContractError> {
+        let fee = self.check_transfer_fee(storage, token_id, funds)?;
+        let mut rest = funds.to_vec();
+        if let Some(fee) = &fee {
+            // has_coins looked at the first coin of the fee's denom, so that one pays it
+            if let Some(coin) = rest.iter_mut().find(|coin| coin.denom == fee.denom) {
+                coin.amount = coin
+                    .amount
+                    .checked_sub(fee.amount)
+                    .map_err(StdError::from)?;
+            }
+            rest.retain(|coin| !coin.amount.is_zero());
+        }
+        Ok((fee, rest))
+    }
+
+    // with a transfer fee, exactly the fee goes to the collection owner and anything else
+    // attached goes back to the sender
+    fn transfer_fee_payment(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+        sender: &Addr,
+        funds: &[Coin],
+    ) -> Result<Vec<BankMsg>, ContractError> {
+        let (fee, rest) = self.split_transfer_fee(storage, token_id, funds)?;
+        let fee = match fee {
+            Some(fee) => fee,
+            None => return Ok(vec![]),
+        };
+        let mut msgs = vec![BankMsg::Send {
+            to_address: self.owner.load(storage)?.to_string(),
+            amount: vec![fee],
+        }];
+        if !rest.is_empty() {
+            msgs.push(BankMsg::Send {
+                to_address: sender.to_string(),
+                amount: rest,
+            });
+        }
+        Ok(msgs)
+    }
+
+    /// appends an ownership change to the token's history, if the collection keeps one
+    pub fn record_history(
+        &self,
+        storage: &mut dyn Storage,
+        env: &Env,
+        token_id: &str,
+        action: &str,
+        from: Option<&Addr>,
+        to: Option<&Addr>,
+    ) -> StdResult<()> {
+        if !self.config(storage)?.track_history {
+            return Ok(());
+        }
+        // entries are numbered from 1 per token, following on from the last one
+        let seq = self
+            .token_history
+            .prefix(token_id)
+            .keys(storage, None, None, Order::Descending)
+            .next()
+            .transpose()?
+            .map_or(Some(1), |last| last.checked_add(1))
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Add, u64::MAX, 1))?;
+        let entry = HistoryEntry {
+            action: action.to_string(),
+            from: from.cloned(),
+            to: to.cloned(),
+            height: env.block.height,
+        };
+        self.token_history.save(storage, (token_id, seq), &entry)
+    }
+
+    // for collection-level chores like tags (which are for integrators, not holders) and the
+    // metadata updater, that either a minter or the contract owner may do
+    fn check_minter_or_owner(
+        &self,
+        storage: &dyn Storage,
+        sender: &Addr,
+    ) -> Result<(), ContractError> {
+        if self.is_minter(storage, sender)? || self.owner.load(storage)? == *sender {
+            return Ok(());
+        }
+        Err(ContractError::NotMinterOrOwner {})
+    }
+
+    /// the message telling the transfer hook, if there is one, that the token changed hands
+    fn transfer_hook_msg(
+        &self,
+        storage: &dyn Storage,
+        token_id: &str,
+        previous_owner: &Addr,
+        recipient: &str,
+    ) -> StdResult<Option<SubMsg<C>>> {
+        let hook = match self.transfer_hook.may_load(storage)? {
+            Some(hook) => hook,
+            None => return Ok(None),
+        };
+        let msg = TransferHookMsg {
+            token_id: token_id.to_string(),
+            previous_owner: previous_owner.to_string(),
+            recipient: recipient.to_string(),
+        }
+        .into_cosmos_msg(hook.contract)?;
+        // a plain submessage reverts the transfer with the hook, otherwise reply swallows the error
+        Ok(Some(if hook.required {
+            SubMsg::new(msg)
+        } else {
+            SubMsg::reply_on_error(msg, ReplyId::TransferHook.id())
+        }))
+    }
+
+    /// everything that must hold for `info.sender` to transfer `token` to `recipient`, checked
+    /// before anything changes. Shared by _transfer_nft and the CanTransfer query, which may
+    /// not know the recipient yet and then skips the checks that need one
+    pub fn check_can_transfer(
+        &self,
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+        token: &TokenInfo<T>,
+        recipient: Option<&Addr>,
+    ) -> Result<(), ContractError> {
+        if !self.transfers_enabled(deps.storage)? {
+            return Err(ContractError::TransfersDisabled {});
+        }
+        self.check_can_send(deps, env, info, token)?;
+        // moving a token to its current owner would only produce misleading events
+        if recipient == Some(&token.owner) {
+            return Err(ContractError::CannotTransferToSelf {});
+        }
+        // deter wash trading by making tokens wait between transfers, if the collection wants it
+        self.check_transfer_cooldown(deps.storage, env, token)?;
+        // a retired token stays with its last owner
+        if let Some(max) = token.max_transfers {
+            if token.transfer_count >= max {
+                return Err(ContractError::TransferLimitReached { max });
+            }
+        }
+        if let Some(recipient) = recipient {
+            self.check_owner_limit(deps.storage, recipient)?;
+        }
+        Ok(())
+    }
+
+    /// returns Ok unless the collection has a transfer cooldown the token is still within
+    pub fn check_transfer_cooldown(
+        &self,
+        storage: &dyn Storage,
+        env: &Env,
+        token: &TokenInfo<T>,
+    ) -> Result<(), ContractError> {
+        if let Some(cooldown) = self.config(storage)?.transfer_cooldown_blocks {
+            let available_at = token.last_transfer + cooldown;
+            if env.block.height < available_at {
+                return Err(ContractError::TransferCooldown { available_at });
             }
-            None => Err(ContractError::Unauthorized {}),
         }
+        Ok(())
+    }
+
+    /// returns Ok if the allowlist is empty or contains the receiver
+    pub fn check_receiver_allowed(&self, deps: Deps, receiver: &str) -> Result<(), ContractError> {
+        let receiver_addr = deps.api.addr_validate(receiver)?;
+        if self.allowed_receivers.has(deps.storage, &receiver_addr) {
+            return Ok(());
+        }
+        // an empty allowlist keeps the old behaviour of allowing any receiver
+        let any_listed = self
+            .allowed_receivers
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+        if any_listed {
+            return Err(ContractError::ReceiverNotAllowed {
+                receiver: receiver.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// returns Ok if the sender is the minter or the metadata updater
+    pub fn check_can_update_metadata(
+        &self,
+        deps: Deps,
+        info: &MessageInfo,
+    ) -> Result<(), ContractError> {
+        if self.is_minter(deps.storage, &info.sender)? {
+            return Ok(());
+        }
+        match self.metadata_updater(deps.storage)? {
+            Some(updater) if updater == info.sender => Ok(()),
+            _ => Err(ContractError::NotMetadataUpdater {}),
+        }
+    }
+}
+
+// the ReceiveNft a send delivers, encoded as SendNft was asked to: the standard `receive_nft`,
+// or `receive_nft_with_owner` with the previous owner, and either one bare without `wrap`.
+// SimulateSend uses it too, so the preview is what would really be sent
+pub(crate) fn receive_nft_binary(
+    send: Cw721ReceiveMsg,
+    previous_owner: &Addr,
+    wrap: bool,
+    with_owner: bool,
+) -> StdResult<Binary> {
+    if !with_owner {
+        return if wrap {
+            send.into_binary()
+        } else {
+            send.into_binary_unwrapped()
+        };
+    }
+    let send = Cw721ReceiveWithOwnerMsg {
+        sender: send.sender,
+        token_id: send.token_id,
+        msg: send.msg,
+        previous_owner: previous_owner.to_string(),
+    };
+    if wrap {
+        send.into_binary()
+    } else {
+        send.into_binary_unwrapped()
+    }
+}
+
+// with minimal_events on, only the action and token_id attributes are kept, to save on event bytes
+fn trim_attributes<C>(mut res: Response<C>, minimal_events: bool) -> Response<C> {
+    if minimal_events {
+        res.attributes
+            .retain(|attr| attr.key == "action" || attr.key == "token_id");
+    }
+    res
+}
+
+// the token's full approval list for indexers, as comma-separated spender:expiry pairs
+// where expiry is "never", "height:<block height>" or "time:<unix nanos>"
+fn approvals_attribute(approvals: &[Approval]) -> String {
+    approvals
+        .iter()
+        .map(|apr| match apr.expires {
+            Expiration::AtHeight(height) => format!("{}:height:{}", apr.spender, height),
+            Expiration::AtTime(time) => format!("{}:time:{}", apr.spender, time.nanos()),
+            Expiration::Never {} => format!("{}:never", apr.spender),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// secp256k1 pubkeys are 33 bytes compressed or 65 uncompressed, anything else can't verify
+fn validate_signer_pubkey(pubkey: Binary) -> Result<Binary, ContractError> {
+    if pubkey.len() != 33 && pubkey.len() != 65 {
+        return Err(ContractError::InvalidSignerPubkey {});
+    }
+    Ok(pubkey)
+}
+
+// name and symbol must be non-empty and short enough for wallets to display
+fn validate_contract_info_field(field: &str, value: &str, max: usize) -> Result<(), ContractError> {
+    if value.trim().is_empty() || value.chars().count() > max {
+        return Err(ContractError::InvalidContractInfo {
+            field: field.to_string(),
+            max,
+        });
     }
+    Ok(())
 }
 
 // need to deploy the contract, more testing, and then we can start building the frontend
\ No newline at end of file
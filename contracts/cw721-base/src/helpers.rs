@@ -132,6 +132,7 @@ impl<Q: CustomMsg, E: CustomMsg> Cw721Contract<Q, E> {
     ) -> StdResult<NftInfoResponse<U>> {
         let req = QueryMsg::NftInfo {
             token_id: token_id.into(),
+            with_defaults: None,
         };
         self.query(querier, req)
     }
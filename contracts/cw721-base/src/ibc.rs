@@ -0,0 +1,143 @@
+// IBC side of the mint packets: an indexer on another chain opens a channel to this contract,
+// the owner picks it with SetMintChannel, and from then on every mint sends a MintPacket over it.
+// Only built with the "ibc" feature, so chains without IBC can still run the contract
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use cosmwasm_std::{
+    Binary, CosmosMsg, CustomMsg, DepsMut, Empty, Env, IbcBasicResponse, IbcChannel,
+    IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcMsg, IbcPacketAckMsg,
+    IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, IbcTimeout, StdResult, Storage,
+};
+
+use crate::error::ContractError;
+use crate::msg::MintPacket;
+use crate::state::Cw721Contract;
+
+// version both ends of a mint packet channel have to agree on
+pub const IBC_VERSION: &str = "cw721-mint-1";
+// how long the indexer's chain has to receive a mint packet
+const MINT_PACKET_TIMEOUT_SECONDS: u64 = 60 * 60;
+
+impl<'a, T, C, E, Q> Cw721Contract<'a, T, C, E, Q>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    C: CustomMsg,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    pub fn ibc_channel_open(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        msg: IbcChannelOpenMsg,
+    ) -> Result<(), ContractError> {
+        check_version(msg.channel(), msg.counterparty_version())
+    }
+
+    // remember the channel, so the owner can pick it for mint packets
+    pub fn ibc_channel_connect(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        msg: IbcChannelConnectMsg,
+    ) -> Result<IbcBasicResponse<C>, ContractError> {
+        let channel = msg.channel();
+        check_version(channel, msg.counterparty_version())?;
+        self.ibc_channels
+            .save(deps.storage, &channel.endpoint.channel_id, &Empty {})?;
+
+        Ok(IbcBasicResponse::new()
+            .add_attribute("action", "ibc_channel_connect")
+            .add_attribute("channel_id", &channel.endpoint.channel_id))
+    }
+
+    // a closed channel can't carry mint packets anymore, mints go back to sending none
+    pub fn ibc_channel_close(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        msg: IbcChannelCloseMsg,
+    ) -> Result<IbcBasicResponse<C>, ContractError> {
+        let channel_id = &msg.channel().endpoint.channel_id;
+        self.ibc_channels.remove(deps.storage, channel_id);
+        if self.mint_channel.may_load(deps.storage)?.as_ref() == Some(channel_id) {
+            self.mint_channel.remove(deps.storage);
+        }
+
+        Ok(IbcBasicResponse::new()
+            .add_attribute("action", "ibc_channel_close")
+            .add_attribute("channel_id", channel_id))
+    }
+
+    // mint packets only go out, anything coming in is acknowledged with an error
+    pub fn ibc_packet_receive(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _msg: IbcPacketReceiveMsg,
+    ) -> Result<IbcReceiveResponse<C>, ContractError> {
+        Ok(IbcReceiveResponse::new()
+            .set_ack(Binary::from(
+                br#"{"error":"cw721 does not accept packets"}"#.to_vec(),
+            ))
+            .add_attribute("action", "ibc_packet_receive"))
+    }
+
+    // the mint happened either way, so there is nothing to undo on an ack or timeout
+    pub fn ibc_packet_ack(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _msg: IbcPacketAckMsg,
+    ) -> Result<IbcBasicResponse<C>, ContractError> {
+        Ok(IbcBasicResponse::new().add_attribute("action", "ibc_packet_ack"))
+    }
+
+    pub fn ibc_packet_timeout(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _msg: IbcPacketTimeoutMsg,
+    ) -> Result<IbcBasicResponse<C>, ContractError> {
+        Ok(IbcBasicResponse::new().add_attribute("action", "ibc_packet_timeout"))
+    }
+
+    /// The packet describing a mint, if the owner picked a channel for them
+    pub fn mint_packet_msg(
+        &self,
+        storage: &dyn Storage,
+        env: &Env,
+        packet: MintPacket,
+    ) -> StdResult<Option<CosmosMsg<C>>> {
+        let channel_id = match self.mint_channel.may_load(storage)? {
+            Some(channel_id) => channel_id,
+            None => return Ok(None),
+        };
+        let send = IbcMsg::SendPacket {
+            channel_id,
+            data: packet.to_binary()?,
+            timeout: IbcTimeout::with_timestamp(
+                env.block.time.plus_seconds(MINT_PACKET_TIMEOUT_SECONDS),
+            ),
+        };
+        Ok(Some(send.into()))
+    }
+}
+
+fn check_version(
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    if channel.version != IBC_VERSION {
+        return Err(ContractError::InvalidIbcVersion {
+            version: channel.version.clone(),
+        });
+    }
+    match counterparty_version {
+        Some(version) if version != IBC_VERSION => Err(ContractError::InvalidIbcVersion {
+            version: version.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
@@ -2,13 +2,23 @@ mod contract_tests;
 mod error;
 mod execute;
 pub mod helpers;
+#[cfg(feature = "ibc")]
+pub mod ibc;
 pub mod msg;
 mod query;
 pub mod state;
 
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, MinterResponse, QueryMsg};
-pub use crate::state::Cw721Contract;
+pub use crate::execute::{safe_send_reply_id, ReplyId};
+pub use crate::msg::{
+    BalanceResponse, BurnedTotalResponse, CanTransferResponse, CollectionInfoResponse,
+    ConfigResponse, CurrentMintPriceResponse, ExecuteMsg, ExistsResponse, InstantiateMsg,
+    IsOperatorResponse, MigrateMsg, MintAuthorization, MintMsg, MintPacket, MintedTotalResponse,
+    MinterResponse, MintersResponse, NextTokenIdResponse, OwnersResponse, QueryMsg, RedeemMsg,
+    SimulateSendResponse, StatsResponse, StatusResponse, SupportedStandardsResponse,
+    TokenApprovalCountResponse, TokenTagsResponse, TransferHookMsg, TransferPermit,
+};
+pub use crate::state::{Cw721Contract, PrefixedKeys};
 use cosmwasm_std::Empty;
 
 // This is a simple type to let us handle empty extensions
@@ -21,7 +31,12 @@ pub mod entry {
 
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
+    #[cfg(feature = "ibc")]
+    use cosmwasm_std::{
+        IbcBasicResponse, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
+        IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse,
+    };
 
     // This makes a conscious choice on the various generics used by the contract
     #[cfg_attr(not(feature = "library"), entry_point)]
@@ -29,8 +44,8 @@ pub mod entry {
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        msg: InstantiateMsg,
-    ) -> StdResult<Response> {
+        msg: InstantiateMsg<Extension>,
+    ) -> Result<Response, ContractError> {
         let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
         tract.instantiate(deps, env, info, msg)
     }
@@ -46,9 +61,88 @@ pub mod entry {
         tract.execute(deps, env, info, msg)
     }
 
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        tract.reply(deps, env, msg)
+    }
+
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        tract.migrate(deps, env, msg)
+    }
+
     #[cfg_attr(not(feature = "library"), entry_point)]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg<Empty>) -> StdResult<Binary> {
         let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
         tract.query(deps, env, msg)
     }
+
+    // IBC entry points, only exported with the "ibc" feature (see ibc.rs)
+    #[cfg(feature = "ibc")]
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn ibc_channel_open(
+        deps: DepsMut,
+        env: Env,
+        msg: IbcChannelOpenMsg,
+    ) -> Result<(), ContractError> {
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        tract.ibc_channel_open(deps, env, msg)
+    }
+
+    #[cfg(feature = "ibc")]
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn ibc_channel_connect(
+        deps: DepsMut,
+        env: Env,
+        msg: IbcChannelConnectMsg,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        tract.ibc_channel_connect(deps, env, msg)
+    }
+
+    #[cfg(feature = "ibc")]
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn ibc_channel_close(
+        deps: DepsMut,
+        env: Env,
+        msg: IbcChannelCloseMsg,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        tract.ibc_channel_close(deps, env, msg)
+    }
+
+    #[cfg(feature = "ibc")]
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn ibc_packet_receive(
+        deps: DepsMut,
+        env: Env,
+        msg: IbcPacketReceiveMsg,
+    ) -> Result<IbcReceiveResponse, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        tract.ibc_packet_receive(deps, env, msg)
+    }
+
+    #[cfg(feature = "ibc")]
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn ibc_packet_ack(
+        deps: DepsMut,
+        env: Env,
+        msg: IbcPacketAckMsg,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        tract.ibc_packet_ack(deps, env, msg)
+    }
+
+    #[cfg(feature = "ibc")]
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn ibc_packet_timeout(
+        deps: DepsMut,
+        env: Env,
+        msg: IbcPacketTimeoutMsg,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
+        tract.ibc_packet_timeout(deps, env, msg)
+    }
 }
@@ -1,20 +1,113 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Binary;
+use cosmwasm_std::{to_binary, Binary, Coin, CosmosMsg, Env, StdResult, WasmMsg};
 use cw721::Expiration;
 
+use crate::state::{BurnPolicy, MintPriceCurve, Phase};
+use crate::ContractError;
+
 #[cw_serde]
-pub struct InstantiateMsg {
+#[derive(Default)]
+pub struct InstantiateMsg<T> {
     /// Name of the NFT contract
     pub name: String,
     /// Symbol of the NFT contract
     pub symbol: String,
 
-    /// The minter is the only one who can create new NFTs.
+    /// The minter is the only one who can create new NFTs (until the owner adds more minters).
     /// This is designed for a base NFT that is controlled by an external program
     /// or contract. You will likely replace this with custom logic in custom NFTs
     pub minter: String,
+
+    /// The owner manages contract settings, such as which receivers SendNft may target.
+    /// Defaults to the account instantiating the contract
+    pub owner: Option<String>,
+    /// If set, SendNft may only target these contracts. Unset or empty allows any receiver
+    pub allowed_receivers: Option<Vec<String>>,
+    /// Who may burn tokens. Defaults to `BurnPolicy::Owner`
+    pub burn_policy: Option<BurnPolicy>,
+    /// If set, Approve and ApproveAll without an explicit `expires` expire this many blocks
+    /// after being granted, instead of never. Pass `Expiration::Never` to get a permanent grant
+    pub default_approval_duration: Option<u64>,
+    /// If true, minting to a contract address fails with `CannotMintToContract`, so tokens
+    /// can't get stranded in contracts that don't know how to move them
+    #[serde(default)]
+    pub reject_contract_owners: bool,
+    /// If set, a token can't be transferred again until this many blocks have passed since it
+    /// last changed hands (or was minted). Unset disables the cooldown
+    pub transfer_cooldown_blocks: Option<u64>,
+    /// If set, Mint requires funds covering any one of these prices (e.g. one per accepted denom).
+    /// The payment is forwarded to the owner. Unset or empty makes minting free
+    pub mint_prices: Option<Vec<Coin>>,
+    /// If set, what addresses on the mint allowlist pay during the allowlist phase.
+    /// Unset or empty charges them the full `mint_prices`
+    pub allowlist_mint_prices: Option<Vec<Coin>>,
+    /// If set, every TransferNft, SendNft and SendNftWithFunds must attach at least this fee,
+    /// which is paid to the owner. Unset makes transfers free
+    pub transfer_fee: Option<Coin>,
+    /// Longest token_id that can be minted. Defaults to 128 characters
+    pub max_token_id_length: Option<u32>,
+    /// If set, token_ids starting with this prefix (e.g. "team-") can only be minted by the
+    /// minters, even when the mint phase lets others mint
+    pub reserved_prefix: Option<String>,
+    /// If set, every Burn sends this contract a `RedeemMsg`, e.g. to ship the physical item
+    /// the token stands for. If the redemption fails, so does the burn
+    pub redemption_contract: Option<String>,
+    /// If true, TransferNft, SendNft and Approve responses only carry the `action` and `token_id`
+    /// attributes, for chains that charge for event bytes
+    #[serde(default)]
+    pub minimal_events: bool,
+    /// If true, SendNft leaves the token's approvals in place instead of clearing them like a
+    /// transfer does, e.g. so an escrow receiver's spender can later move the token back
+    #[serde(default)]
+    pub keep_approvals_on_send: bool,
+    /// Most tokens the collection can have. None leaves it uncapped
+    pub max_supply: Option<u64>,
+    /// If true, burning a token frees a slot under max_supply (the cap counts tokens in
+    /// existence). By default it counts every token ever minted, so burned editions stay gone
+    #[serde(default)]
+    pub burns_free_supply: bool,
+    /// secp256k1 pubkey of an off-chain signer. If set, anyone holding a `MintAuthorization`
+    /// signed with it can mint through MintSigned, for allowlists too large to keep on-chain
+    pub signer_pubkey: Option<Binary>,
+    /// OpenSea-style contractURI, pointing at collection-level metadata such as the banner and
+    /// description. The owner can change it later with SetCollectionUri
+    pub collection_uri: Option<String>,
+    /// How many mints each address on the mint allowlist gets for free, while everyone else pays
+    /// mint_prices. None means allowlisted addresses pay like anyone else
+    pub allowlist_free_mints: Option<u32>,
+    /// Most tokens a single address can hold, checked on every mint and transfer, e.g. to stop whales
+    /// concentrating a collection during the mint. None leaves it unlimited
+    pub max_per_owner: Option<u64>,
+    /// Makes the mint price climb with supply: each mint costs base_price plus step for every token
+    /// already in circulation. Replaces mint_prices and allowlist_mint_prices while set
+    pub mint_price_curve: Option<MintPriceCurve>,
+    /// If true, every mint, transfer and burn is appended to the token's history (see TokenHistory),
+    /// at the cost of storage on each
+    #[serde(default)]
+    pub track_history: bool,
+    /// Blocks a new token approval waits before the spender can use it, against front-running.
+    /// 0 (the default) makes approvals usable straight away
+    #[serde(default)]
+    pub approval_delay_blocks: u64,
+    /// Whether owners may grant operators over all their tokens with ApproveAll. False limits
+    /// high-security collections to per-token approvals. Defaults to true
+    pub operators_enabled: Option<bool>,
+    /// Funds that must be attached to instantiate, for chains with storage rent. Withdraw
+    /// always leaves at least this much in the contract
+    pub min_deposit: Option<Coin>,
+    /// If set, tokens minted without an extension (one that serializes to null, i.e. None for the
+    /// usual `Option` extensions, or to `{}`) get this one instead, for collections sharing most
+    /// metadata
+    pub default_extension: Option<T>,
+    /// Tokens to mint while instantiating, e.g. for a genesis collection. Subject to the same
+    /// cap as any batch mint, and a duplicate token_id fails the whole instantiate
+    pub initial_mints: Option<Vec<MintMsg<T>>>,
 }
 
+/// Nothing to configure yet, migrating only brings older storage up to date
+#[cw_serde]
+pub struct MigrateMsg {}
+
 /// This is like Cw721ExecuteMsg but we add a Mint command for an owner
 /// to make this stand-alone. You will likely want to remove mint and
 /// use other control logic in any contract that inherits this.
@@ -24,11 +117,39 @@ pub enum ExecuteMsg<T, E> {
     TransferNft { recipient: String, token_id: String },
     /// Send is a base message to transfer a token to a contract and trigger an action
     /// on the receiving contract.
+    /// With `safe_send`, the receiver must be a contract, and if it fails on ReceiveNft the
+    /// transaction doesn't revert: the token is given back to its previous owner instead, the
+    /// attached funds are refunded and no transfer fee is paid or transfer hook called.
+    /// With `wrap: false` the receiver gets the bare Cw721ReceiveMsg rather than the standard
+    /// `{"receive_nft": ...}`, for contracts that expect it directly.
+    /// With `with_owner`, the receiver gets a Cw721ReceiveWithOwnerMsg under
+    /// `{"receive_nft_with_owner": ...}` instead, which also names the token's previous owner
     SendNft {
         contract: String,
         token_id: String,
         msg: Binary,
+        #[serde(default)]
+        safe_send: bool,
+        #[serde(default = "default_wrap")]
+        wrap: bool,
+        #[serde(default)]
+        with_owner: bool,
     },
+    /// Same as SendNft, but any funds attached to this message are forwarded
+    /// to the receiving contract along with the Cw721ReceiveMsg
+    SendNftWithFunds {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    /// Transfer on behalf of the owner, who signed the permit off-chain, so a relayer can pay the gas.
+    /// The signature is checked against the pubkey the owner registered with SetPermitPubkey
+    TransferWithPermit {
+        permit: TransferPermit,
+        signature: Binary,
+    },
+    /// Register (or clear with None) the secp256k1 pubkey that signs the sender's transfer permits
+    SetPermitPubkey { pubkey: Option<Binary> },
     /// Approve, Revoke, ApproveAll, RevokeAll: Giving rights to another account to transfer/send an nft that is held by your account 
     /// Allows operator to transfer / send the token from the owner's account.
     /// If expiration is set, then this allowance has a time/height limit
@@ -37,22 +158,160 @@ pub enum ExecuteMsg<T, E> {
         token_id: String,
         expires: Option<Expiration>,
     },
+    /// Approve the spender on each of the given tokens in one go, e.g. to list them on a
+    /// marketplace. Fails as a whole if the sender can't approve any one of them
+    ApproveBatch {
+        spender: String,
+        token_ids: Vec<String>,
+        expires: Option<Expiration>,
+    },
     /// Remove previously granted Approval
     Revoke { spender: String, token_id: String },
+    /// Remove the spender's Approval from each of the given tokens in one go.
+    /// Tokens the spender isn't approved on are skipped
+    RevokeBatch {
+        token_ids: Vec<String>,
+        spender: String,
+    },
+    /// Keep (or stop keeping) the spender's approval on this token when it is transferred,
+    /// e.g. for a custodian. Other approvals are still cleared. Can only be called by the token owner
+    SetPersistentApproval {
+        token_id: String,
+        spender: String,
+        persistent: bool,
+    },
     /// Allows operator to transfer / send any token from the owner's account.
-    /// If expiration is set, then this allowance has a time/height limit
+    /// If expiration is set, then this allowance has a time/height limit.
+    /// If min_token_height is set, only tokens minted at or after that height are covered
     ApproveAll {
         operator: String,
         expires: Option<Expiration>,
+        min_token_height: Option<u64>,
     },
     /// Remove previously granted ApproveAll permission
     RevokeAll { operator: String },
 
+    /// Authorize another address to mint. Can only be called by the contract owner
+    AddMinter { minter: String },
+    /// Revoke an address' right to mint. The last minter can't be removed.
+    /// Can only be called by the contract owner
+    RemoveMinter { minter: String },
+
+    /// Change the collection's name and/or symbol. Can only be called by the contract owner
+    UpdateContractInfo {
+        name: Option<String>,
+        symbol: Option<String>,
+    },
+
+    /// Switch who may mint: only minters, minters and the mint allowlist, or anyone.
+    /// Can only be called by the contract owner
+    SetPhase { phase: Phase },
+    /// Rotate the off-chain signer key MintSigned checks against, e.g. after a leak. Signatures
+    /// by the old key stop working. None turns MintSigned off. Can only be called by the
+    /// contract owner
+    SetSignerPubkey { pubkey: Option<Binary> },
+    /// Add or remove an address from the mint allowlist used during the allowlist phase.
+    /// Can only be called by the contract owner
+    SetMintAllowed { address: String, allowed: bool },
+    /// Turn all transfers and sends on or off, e.g. to keep a collection non-transferable during
+    /// a vesting period. Burns still work. Can only be called by the contract owner
+    SetTransfersEnabled { enabled: bool },
+    /// Turn SendNft (and its variants) on or off, for collections that only want plain transfers
+    /// and no contract interactions. Can only be called by the contract owner
+    SetSendEnabled { enabled: bool },
+    /// Set or clear the collection-level metadata uri. Can only be called by the contract owner
+    SetCollectionUri { collection_uri: Option<String> },
+    /// Move a token stuck on the contract's own address to `recipient`. With `collection` None
+    /// (or this contract) it is one of this collection's tokens, otherwise the contract sends
+    /// TransferNft to that cw721 contract. Can only be called by the contract owner
+    RescueNft {
+        collection: Option<String>,
+        token_id: String,
+        recipient: String,
+    },
+    /// Pick the IBC channel every mint sends a `MintPacket` over, e.g. to an indexer on another
+    /// chain, or stop sending them with None. The channel must already be open, which needs
+    /// the contract built with the "ibc" feature. Can only be called by the contract owner
+    SetMintChannel { channel_id: Option<String> },
+
+    /// Add or remove a contract from the SendNft receiver allowlist.
+    /// Can only be called by the contract owner
+    SetReceiverAllowed { receiver: String, allowed: bool },
+    /// Point the transfer hook at a contract, which then gets a `TransferHookMsg` on every
+    /// transfer and send, or stop sending them with None. With `required` a failing hook fails
+    /// the transfer, otherwise its failure is ignored. Can only be called by the contract owner
+    SetTransferHook {
+        hook: Option<String>,
+        required: bool,
+    },
+
     /// Mint a new NFT, can only be called by the contract minter
+    /// (or, depending on the mint phase, allowlisted addresses or anyone)
     Mint(MintMsg<T>), // <T> is the extension type
 
+    /// Mint a new NFT with the next sequential token_id, derived from the number of
+    /// tokens ever minted. Can only be called by the contract minter
+    MintNext {
+        owner: String,
+        token_uri: Option<String>,
+        extension: T,
+    },
+
+    /// Mint a new NFT straight into `contract` and notify it with a ReceiveNft carrying `msg`,
+    /// all in one transaction, e.g. into a game's vault contract. `mint.owner` is replaced by
+    /// `contract`. The same rules as Mint and SendNft apply
+    MintAndSend {
+        mint: MintMsg<T>,
+        contract: String,
+        msg: Binary,
+    },
+
+    /// Mint on the authority of the collection's signer instead of a minter: `signature` must be
+    /// the signer's over a `MintAuthorization` for exactly this msg. Each token_id can only ever
+    /// be minted this way once. Mint prices apply as for a regular mint
+    MintSigned { msg: MintMsg<T>, signature: Binary },
+
+    /// Replace the extension of an existing NFT, can only be called by the contract minter
+    /// or the metadata updater
+    UpdateExtension { token_id: String, extension: T },
+
+    /// Set (or clear with None) an address that may update metadata but not mint.
+    /// Can only be called by a minter or the contract owner
+    SetMetadataUpdater { address: Option<String> },
+
+    /// Attach a free-form key/value tag to a token, replacing any value already under `key`.
+    /// A token can have at most 32 tags. Can only be called by a minter or the contract owner
+    SetTokenTag {
+        token_id: String,
+        key: String,
+        value: String,
+    },
+    /// Remove a token's tag. Can only be called by a minter or the contract owner
+    RemoveTokenTag { token_id: String, key: String },
+
+    /// Exempt a token from the transfer fee and royalties, e.g. for a giveaway, or lift the
+    /// exemption again. Can only be called by a minter
+    SetRoyaltyExempt { token_id: String, exempt: bool },
+    /// Retire a token from circulation after it has changed hands `max_transfers` times, or
+    /// lift the limit with None. Can only be called by a minter
+    SetMaxTransfers {
+        token_id: String,
+        max_transfers: Option<u32>,
+    },
+
     /// Burn an NFT the sender has access to
     Burn { token_id: String },
+    /// Burn up to `limit` of the tokens held by `owner`, for decommissioning.
+    /// Can only be called by the contract minter. The response's `has_more` attribute
+    /// says whether another call is needed
+    BurnAllOwnedBy { owner: String, limit: Option<u32> },
+    /// Burn a token without its owner's consent, e.g. for compliance. `reason` must not be
+    /// empty and is recorded in the response. Can only be called by the contract owner
+    AdminBurn { token_id: String, reason: String },
+
+    /// Send `amount` of the contract's funds to the contract owner. It is capped so the
+    /// contract keeps its min_deposit. Can only be called by the contract owner
+    Withdraw { amount: Coin },
 
     /// Extension msg
     Extension { msg: E },
@@ -71,6 +330,183 @@ pub struct MintMsg<T> {
     /// Any custom extension used by this contract 
     /// NFT creators may want to store their NFT metadata on-chain so other contracts are able to interact with it. Store any data on chain you wish, using a generic extension: T.
     pub extension: T,
+    /// Hex-encoded sha256 of the JSON at token_uri, so holders can check the off-chain
+    /// metadata wasn't swapped out. Must be 64 hex characters if set
+    pub metadata_hash: Option<String>,
+    /// Serial number for limited editions, from 1 up to edition_of, e.g. 3 for "3 of 50"
+    pub edition: Option<u32>,
+    /// Size of the limited edition the token belongs to, e.g. 50 for "3 of 50"
+    pub edition_of: Option<u32>,
+}
+
+impl<T> MintMsg<T> {
+    /// Checks everything about a mint that doesn't need contract state: the token_id against
+    /// max_token_id_length and the allowed characters, the token_uri against max_uri_length,
+    /// the metadata hash and the edition numbers. Who may mint, the owner address and supply
+    /// caps are left to the contract
+    pub fn validate(
+        &self,
+        max_token_id_length: u32,
+        max_uri_length: usize,
+    ) -> Result<(), ContractError> {
+        // token_ids end up as keys in indexers, so keep them short and free of odd characters
+        validate_token_id(&self.token_id, max_token_id_length)?;
+        // an empty uri points nowhere, leave it out instead
+        if let Some(uri) = &self.token_uri {
+            if uri.trim().is_empty() || uri.len() > max_uri_length {
+                return Err(ContractError::InvalidTokenUri {
+                    max: max_uri_length,
+                });
+            }
+        }
+        // a metadata hash is only useful if it could actually be a sha256 digest
+        if let Some(hash) = &self.metadata_hash {
+            validate_metadata_hash(hash)?;
+        }
+        // serials count from 1, and can't go past the size of the edition
+        validate_edition(self.edition, self.edition_of)
+    }
+}
+
+// non-empty, at most max_length long, and only alphanumerics, '-', '_' and ':'
+fn validate_token_id(token_id: &str, max_length: u32) -> Result<(), ContractError> {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':');
+    if token_id.is_empty() || token_id.len() > max_length as usize || !token_id.chars().all(allowed)
+    {
+        return Err(ContractError::InvalidTokenId {
+            token_id: token_id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+// edition and edition_of are optional, but 0 is never valid and a serial can't exceed the size
+fn validate_edition(edition: Option<u32>, edition_of: Option<u32>) -> Result<(), ContractError> {
+    match (edition, edition_of) {
+        (Some(0), _) | (_, Some(0)) => Err(ContractError::InvalidEdition {}),
+        (Some(edition), Some(edition_of)) if edition > edition_of => {
+            Err(ContractError::InvalidEdition {})
+        }
+        _ => Ok(()),
+    }
+}
+
+// sha256 digests are 32 bytes, i.e. 64 hex characters
+fn validate_metadata_hash(hash: &str) -> Result<(), ContractError> {
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ContractError::InvalidMetadataHash {});
+    }
+    Ok(())
+}
+
+// SendNft wraps the ReceiveNft message unless asked not to
+fn default_wrap() -> bool {
+    true
+}
+
+/// What a token owner signs to let anyone submit a transfer for them.
+/// The signature is over the sha256 of this struct's JSON encoding
+#[cw_serde]
+pub struct TransferPermit {
+    /// The cw721 contract the permit is for, so it can't be replayed on another collection
+    pub contract: String,
+    pub token_id: String,
+    pub recipient: String,
+    /// Must equal the owner's current permit nonce, which goes up by one with each used permit
+    pub nonce: u64,
+    pub expires: Expiration,
+}
+
+/// What the collection's signer signs to authorize a MintSigned.
+/// The signature is over the sha256 of this struct's JSON encoding
+#[cw_serde]
+pub struct MintAuthorization<T> {
+    /// The cw721 contract the mint is for, so it can't be replayed on another collection
+    pub contract: String,
+    pub msg: MintMsg<T>,
+}
+
+/// Sent to the redemption contract when a token is burned, under a `Redeem` variant
+/// of its ExecuteMsg (like ReceiveNft for SendNft)
+#[cw_serde]
+pub struct RedeemMsg {
+    pub token_id: String,
+    /// Owner of the token when it was burned
+    pub redeemer: String,
+    pub token_uri: Option<String>,
+}
+
+impl RedeemMsg {
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<C>(self, contract_addr: impl Into<String>) -> StdResult<CosmosMsg<C>> {
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: to_binary(&RedemptionExecuteMsg::Redeem(self))?,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// Sent to the transfer hook whenever a token changes hands, under a `NftTransferred` variant
+/// of its ExecuteMsg
+#[cw_serde]
+pub struct TransferHookMsg {
+    pub token_id: String,
+    pub previous_owner: String,
+    pub recipient: String,
+}
+
+impl TransferHookMsg {
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<C>(self, contract_addr: impl Into<String>) -> StdResult<CosmosMsg<C>> {
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: to_binary(&TransferHookExecuteMsg::NftTransferred(self))?,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// Describes a mint to an indexer on another chain, sent as the data of an IBC packet
+#[cw_serde]
+pub struct MintPacket {
+    /// The cw721 contract the token was minted on
+    pub contract: String,
+    pub token_id: String,
+    pub owner: String,
+    pub token_uri: Option<String>,
+    pub block_height: u64,
+}
+
+impl MintPacket {
+    pub fn new<T>(env: &Env, msg: &MintMsg<T>) -> Self {
+        MintPacket {
+            contract: env.contract.address.to_string(),
+            token_id: msg.token_id.clone(),
+            owner: msg.owner.clone(),
+            token_uri: msg.token_uri.clone(),
+            block_height: env.block.height,
+        }
+    }
+
+    /// serializes the packet as the JSON the indexer reads
+    pub fn to_binary(&self) -> StdResult<Binary> {
+        to_binary(self)
+    }
+}
+
+// helper to serialize RedeemMsg as the variant the redemption contract expects
+#[cw_serde]
+enum RedemptionExecuteMsg {
+    Redeem(RedeemMsg),
+}
+
+// helper to serialize TransferHookMsg as the variant the hook contract expects
+#[cw_serde]
+enum TransferHookExecuteMsg {
+    NftTransferred(TransferHookMsg),
 }
 
 #[cw_serde]
@@ -104,17 +540,79 @@ pub enum QueryMsg<Q> {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// The expiration of the ApproveAll grant from owner to operator, expired or not.
+    /// Return type: `Option<Expiration>`, None if there is no grant
+    OperatorExpiration {
+        owner: String,
+        operator: String,
+    },
+    /// Whether owner has an ApproveAll grant to operator that hasn't expired at this block.
+    /// Return type: `IsOperatorResponse`
+    IsOperator {
+        owner: String,
+        operator: String,
+    },
     /// Total number of tokens issued
     NumTokens {},
+    /// Number of tokens ever minted, burned ones included
+    /// Return type: `MintedTotalResponse`
+    MintedTotal {},
+    /// The token_id the next MintNext will assign, for optimistic UIs.
+    /// Return type: `NextTokenIdResponse`
+    NextTokenId {},
+    /// Number of tokens ever burned. MintedTotal minus this is always NumTokens
+    /// Return type: `BurnedTotalResponse`
+    BurnedTotal {},
+    /// Supply, minted and burned totals and the number of distinct owners in one call, for
+    /// dashboards. Counting owners walks every holder, so this gets pricier as the collection grows
+    /// Return type: `StatsResponse`
+    Stats {},
 
     /// With MetaData Extension.
     /// Returns top-level metadata about the contract: `ContractInfoResponse`
     ContractInfo {},
+    /// The cw2 contract name and version stored at instantiate (or the last migration), for
+    /// upgrade tooling that can't read raw storage
+    /// Return type: `cw2::ContractVersion`
+    Version {},
+    /// Name, symbol and the collection-level metadata uri (OpenSea's contractURI)
+    /// Return type: `CollectionInfoResponse`
+    CollectionInfo {},
+    /// Interface identifiers this contract implements (e.g. "cw721-1.0", "cw2981"), so
+    /// aggregators can tell what to expect. Contracts built on cw721-base append their own
+    /// Return type: `SupportedStandardsResponse`
+    SupportedStandards {},
+    /// The collection's on/off switches in one call, for frontends.
+    /// Return type: `StatusResponse`
+    Status {},
     /// With MetaData Extension.
     /// Returns metadata about one particular token, based on *ERC721 Metadata JSON Schema*
     /// but directly from the contract: `NftInfoResponse`
+    /// With `with_defaults: Some(true)`, a token stored without an extension (one that
+    /// serializes to null or `{}`) is returned with the collection's default_extension instead
     NftInfo {
         token_id: String,
+        with_defaults: Option<bool>,
+    },
+    /// NftInfo for up to 30 tokens in one call, paired with their token_id.
+    /// Tokens that don't exist map to None.
+    /// Return type: `Vec<(String, Option<NftInfoResponse>)>`
+    BatchNftInfo {
+        token_ids: Vec<String>,
+    },
+    /// Owners of up to 30 tokens in one call, paired with their token_id.
+    /// Tokens that don't exist map to None.
+    /// Return type: `Vec<(String, Option<String>)>`
+    BatchOwnerOf {
+        token_ids: Vec<String>,
+    },
+    /// Tokens held by each of up to 30 owners, e.g. for airdrop tooling, grouped by owner in
+    /// the order given. Stops once `limit` tokens have been listed in total, so the owners at
+    /// the end may be cut short or missing.
+    /// Return type: `Vec<(String, Vec<String>)>`
+    TokensByOwners {
+        owners: Vec<String>,
+        limit: Option<u32>,
     },
     /// With MetaData Extension.
     /// Returns the result of both `NftInfo` and `OwnerOf` as one query as an optimization
@@ -133,6 +631,40 @@ pub enum QueryMsg<Q> {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Lists the owner's token_ids that the given spender currently has an unexpired Approve on.
+    /// This walks the owner's tokens until `limit` matches are found, so a large wallet with few
+    /// approvals to the spender costs more gas per page than Tokens does.
+    /// Return type: TokensResponse.
+    OwnerTokensApprovedTo {
+        owner: String,
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Lists token_ids the given spender is currently approved to move (via Approve, not ApproveAll).
+    /// Return type: TokensResponse.
+    TokensApprovedTo {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The newest tokens first, by mint order rather than token_id. Each entry pairs the token's
+    /// position in mint order with its token_id; pass the last position as start_after to get
+    /// the next (older) page.
+    /// Return type: `Vec<(u64, String)>`
+    RecentTokens {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// The token's ownership changes, oldest first, as (seq, entry) pairs. Only recorded while
+    /// the collection was instantiated with track_history; pass the last seq as start_after to
+    /// get the next page.
+    /// Return type: `Vec<(u64, HistoryEntry)>`
+    TokenHistory {
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     /// With Enumerable extension.
     /// Requires pagination. Lists all token_ids controlled by the contract.
     /// Return type: TokensResponse.
@@ -141,8 +673,82 @@ pub enum QueryMsg<Q> {
         limit: Option<u32>,
     },
 
-    // Return the minter
+    // Return the minter (the first one by address, if there are several)
     Minter {},
+    /// Return everyone allowed to mint
+    /// Return type: `MintersResponse`
+    Minters {},
+
+    /// Collection-level settings and roles in one call
+    /// Return type: `ConfigResponse`
+    Config {},
+    /// What a mint costs right now, in the current phase, following the price curve if there is one.
+    /// Return type: `CurrentMintPriceResponse`
+    CurrentMintPrice {},
+
+    /// Number of tokens held by the given owner
+    /// Return type: `BalanceResponse`
+    BalanceOf {
+        owner: String,
+    },
+    /// Every distinct address currently holding at least one token, e.g. for airdrops.
+    /// Return type: `OwnersResponse`
+    AllOwners {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Preview the message SendNft would deliver to `contract`, as if sent by the token's owner,
+    /// encoded as SendNft's `wrap` and `with_owner` would have it.
+    /// Return type: `SimulateSendResponse`
+    SimulateSend {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+        #[serde(default = "default_wrap")]
+        wrap: bool,
+        #[serde(default)]
+        with_owner: bool,
+    },
+
+    /// Whether `address` could transfer the token right now, as checked by TransferNft
+    /// (transfers enabled, ownership, approvals, operators, any transfer cooldown and transfer
+    /// limit). With a `recipient`, also whether it can take the token (not the current owner,
+    /// and below any per-owner limit)
+    /// Return type: `CanTransferResponse`
+    CanTransfer {
+        token_id: String,
+        address: String,
+        #[serde(default)]
+        recipient: Option<String>,
+    },
+
+    /// Cheap check for whether a token_id has already been minted
+    /// Return type: `ExistsResponse`
+    Exists {
+        token_id: String,
+    },
+
+    /// Debug/introspection only: the token exactly as stored, internal fields included
+    /// (approvals, persistent approvals, mint and transfer heights). Not part of the cw721 spec
+    /// and its shape follows the storage layout, so don't build on it.
+    /// Return type: `TokenInfo<T>`
+    RawTokenInfo {
+        token_id: String,
+    },
+
+    /// Number of approvals on the token that haven't expired at the current block,
+    /// e.g. to tell which tokens a transfer won't have to clear approvals for
+    /// Return type: `TokenApprovalCountResponse`
+    TokenApprovalCount {
+        token_id: String,
+    },
+
+    /// All of the token's tags set with SetTokenTag, sorted by key.
+    /// Return type: `TokenTagsResponse`
+    TokenTags {
+        token_id: String,
+    },
 
     /// Extension query
     Extension {
@@ -155,3 +761,146 @@ pub enum QueryMsg<Q> {
 pub struct MinterResponse {
     pub minter: String,
 }
+
+/// Everyone who can mint these tokens
+#[cw_serde]
+pub struct MintersResponse {
+    pub minters: Vec<String>,
+}
+
+/// Collection settings and the addresses that manage them
+#[cw_serde]
+pub struct ConfigResponse {
+    pub minters: Vec<String>,
+    pub owner: String,
+    pub metadata_updater: Option<String>,
+    pub burn_policy: BurnPolicy,
+    pub default_approval_duration: Option<u64>,
+    pub reject_contract_owners: bool,
+    pub transfer_cooldown_blocks: Option<u64>,
+    pub mint_prices: Vec<Coin>,
+    pub allowlist_mint_prices: Vec<Coin>,
+    pub phase: Phase,
+    pub transfer_fee: Option<Coin>,
+    pub max_token_id_length: u32,
+    pub reserved_prefix: Option<String>,
+    pub redemption_contract: Option<String>,
+    pub minimal_events: bool,
+    pub signer_pubkey: Option<Binary>,
+    pub transfers_enabled: bool,
+    pub send_enabled: bool,
+    pub keep_approvals_on_send: bool,
+    pub max_supply: Option<u64>,
+    pub burns_free_supply: bool,
+    pub allowlist_free_mints: Option<u32>,
+    pub max_per_owner: Option<u64>,
+    pub mint_price_curve: Option<MintPriceCurve>,
+    pub track_history: bool,
+    pub approval_delay_blocks: u64,
+    pub operators_enabled: bool,
+    pub min_deposit: Option<Coin>,
+}
+
+/// Collection-level metadata, like ContractInfoResponse plus the collection uri
+#[cw_serde]
+pub struct CollectionInfoResponse {
+    pub name: String,
+    pub symbol: String,
+    pub collection_uri: Option<String>,
+}
+
+/// Collection-wide switches. Other flags belong here as their features are added
+#[cw_serde]
+pub struct StatusResponse {
+    pub transfers_enabled: bool,
+    pub send_enabled: bool,
+}
+
+/// The interface identifiers the contract implements
+#[cw_serde]
+pub struct SupportedStandardsResponse {
+    pub standards: Vec<String>,
+}
+
+/// The prices a mint accepts right now, any one of which covers it. Empty if minting is free
+#[cw_serde]
+pub struct CurrentMintPriceResponse {
+    pub prices: Vec<Coin>,
+}
+
+/// How many tokens were ever minted
+#[cw_serde]
+pub struct MintedTotalResponse {
+    pub count: u64,
+}
+
+#[cw_serde]
+pub struct NextTokenIdResponse {
+    pub token_id: String,
+}
+
+/// How many tokens were ever burned
+#[cw_serde]
+pub struct BurnedTotalResponse {
+    pub count: u64,
+}
+
+/// Collection-wide counters
+#[cw_serde]
+pub struct StatsResponse {
+    /// Tokens currently in existence
+    pub supply: u64,
+    pub minted_total: u64,
+    pub burned_total: u64,
+    /// Addresses holding at least one token
+    pub owner_count: u64,
+}
+
+/// How many tokens an owner holds
+#[cw_serde]
+pub struct BalanceResponse {
+    pub count: u64,
+}
+
+/// Distinct token holders, ordered by address
+#[cw_serde]
+pub struct OwnersResponse {
+    pub owners: Vec<String>,
+}
+
+/// What SendNft would execute: `msg` is the serialized `ReceiveNft` sent to `contract`
+#[cw_serde]
+pub struct SimulateSendResponse {
+    pub contract: String,
+    pub msg: Binary,
+}
+
+/// Whether the queried address may transfer the token at the current block
+#[cw_serde]
+pub struct CanTransferResponse {
+    pub can_transfer: bool,
+}
+
+/// Whether a token with the queried id exists
+#[cw_serde]
+pub struct ExistsResponse {
+    pub exists: bool,
+}
+
+/// Whether the queried operator currently holds an ApproveAll grant from the owner
+#[cw_serde]
+pub struct IsOperatorResponse {
+    pub is_operator: bool,
+}
+
+/// Active (non-expired) approvals on the queried token
+#[cw_serde]
+pub struct TokenApprovalCountResponse {
+    pub count: u32,
+}
+
+/// A token's (key, value) tags
+#[cw_serde]
+pub struct TokenTagsResponse {
+    pub tags: Vec<(String, String)>,
+}
@@ -2,22 +2,39 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use cosmwasm_std::{
-    to_binary, Addr, Binary, BlockInfo, CustomMsg, Deps, Env, Order, StdError, StdResult,
+    to_binary, Addr, Binary, BlockInfo, CustomMsg, Deps, Env, MessageInfo, Order, StdError,
+    StdResult,
 };
 
+use cw2::get_contract_version;
 use cw721::{
     AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, Cw721Query,
-    Expiration, NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse,
-    TokensResponse,
+    Cw721ReceiveMsg, Expiration, NftInfoResponse, NumTokensResponse, OperatorsResponse,
+    OwnerOfResponse, TokensResponse,
 };
 use cw_storage_plus::Bound;
 use cw_utils::maybe_addr;
 
-use crate::msg::{MinterResponse, QueryMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::execute::{receive_nft_binary, DEFAULT_MAX_TOKEN_ID_LENGTH};
+use crate::msg::{
+    BalanceResponse, BurnedTotalResponse, CanTransferResponse, CollectionInfoResponse,
+    ConfigResponse, CurrentMintPriceResponse, ExistsResponse, IsOperatorResponse,
+    MintedTotalResponse, MinterResponse, MintersResponse, NextTokenIdResponse, OwnersResponse,
+    QueryMsg, SimulateSendResponse, StatsResponse, StatusResponse, SupportedStandardsResponse,
+    TokenApprovalCountResponse, TokenTagsResponse,
+};
+use crate::state::{Approval, Cw721Contract, HistoryEntry, OperatorGrant, TokenInfo};
+
+// Identifier of the CW721 spec this contract implements, for SupportedStandards
+const CW721_STANDARD: &str = "cw721-1.0";
 
 const DEFAULT_LIMIT: u32 = 10;
 const MAX_LIMIT: u32 = 100;
+// BatchNftInfo and BatchOwnerOf load every requested token, so keep the list short to bound
+// query gas
+const MAX_BATCH_TOKENS: usize = 30;
+// and TokensByOwners walks each owner's tokens
+const MAX_BATCH_OWNERS: usize = 30;
 
 impl<'a, T, C, E, Q> Cw721Query<T> for Cw721Contract<'a, T, C, E, Q>
 where
@@ -39,7 +56,11 @@ where
         let info = self.tokens.load(deps.storage, &token_id)?;
         Ok(NftInfoResponse {
             token_uri: info.token_uri,
+            mint_block: recorded_height(info.mint_block),
             extension: info.extension,
+            metadata_hash: info.metadata_hash,
+            edition: info.edition,
+            edition_of: info.edition_of,
         })
     }
 
@@ -108,6 +129,8 @@ where
             .approvals
             .into_iter()
             .filter(|t| t.spender == spender)
+            // a delayed approval can't be used yet, so it isn't reported either
+            .filter(|t| t.is_active(&env.block))
             .filter(|t| include_expired || !t.is_expired(&env.block))
             .map(|a| cw721::Approval {
                 spender: a.spender.into_string(),
@@ -133,15 +156,7 @@ where
         include_expired: bool,
     ) -> StdResult<ApprovalsResponse> {
         let token = self.tokens.load(deps.storage, &token_id)?;
-        let approvals: Vec<_> = token
-            .approvals
-            .into_iter()
-            .filter(|t| include_expired || !t.is_expired(&env.block))
-            .map(|a| cw721::Approval {
-                spender: a.spender.into_string(),
-                expires: a.expires,
-            })
-            .collect();
+        let approvals = humanize_approvals(&env.block, &token, include_expired);
 
         Ok(ApprovalsResponse { approvals })
     }
@@ -203,8 +218,13 @@ where
             },
             info: NftInfoResponse {
                 token_uri: info.token_uri,
+                mint_block: recorded_height(info.mint_block),
                 extension: info.extension,
+                metadata_hash: info.metadata_hash,
+                edition: info.edition,
+                edition_of: info.edition_of,
             },
+            last_transfer: recorded_height(info.last_transfer),
         })
     }
 }
@@ -217,17 +237,519 @@ where
     Q: CustomMsg,
 {
     pub fn minter(&self, deps: Deps) -> StdResult<MinterResponse> {
-        let minter_addr = self.minter.load(deps.storage)?;
+        // there is always at least one minter
+        let minter_addr = self
+            .minters
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .ok_or_else(|| StdError::not_found("minter"))??;
         Ok(MinterResponse {
             minter: minter_addr.to_string(),
         })
     }
 
+    pub fn minters(&self, deps: Deps) -> StdResult<MintersResponse> {
+        let minters = self
+            .minters
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|addr| addr.map(String::from))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(MintersResponse { minters })
+    }
+
+    // free mints are per address, so they aren't reflected here
+    pub fn current_mint_price(&self, deps: Deps) -> StdResult<CurrentMintPriceResponse> {
+        let phase = self.current_phase(deps.storage)?;
+        Ok(CurrentMintPriceResponse {
+            prices: self.mint_prices(deps.storage, phase)?,
+        })
+    }
+
+    pub fn collection_config(&self, deps: Deps) -> StdResult<ConfigResponse> {
+        let config = self.config(deps.storage)?;
+        Ok(ConfigResponse {
+            minters: self.minters(deps)?.minters,
+            owner: self.owner.load(deps.storage)?.to_string(),
+            metadata_updater: self
+                .metadata_updater(deps.storage)?
+                .map(|addr| addr.to_string()),
+            burn_policy: config.burn_policy,
+            default_approval_duration: config.default_approval_duration,
+            reject_contract_owners: config.reject_contract_owners,
+            transfer_cooldown_blocks: config.transfer_cooldown_blocks,
+            mint_prices: config.mint_prices,
+            allowlist_mint_prices: config.allowlist_mint_prices,
+            phase: self.current_phase(deps.storage)?,
+            transfer_fee: config.transfer_fee,
+            max_token_id_length: config
+                .max_token_id_length
+                .unwrap_or(DEFAULT_MAX_TOKEN_ID_LENGTH),
+            reserved_prefix: config.reserved_prefix,
+            redemption_contract: config.redemption_contract.map(|addr| addr.to_string()),
+            minimal_events: config.minimal_events,
+            signer_pubkey: config.signer_pubkey,
+            transfers_enabled: self.transfers_enabled(deps.storage)?,
+            send_enabled: self.send_enabled(deps.storage)?,
+            keep_approvals_on_send: config.keep_approvals_on_send,
+            max_supply: config.max_supply,
+            burns_free_supply: config.burns_free_supply,
+            allowlist_free_mints: config.allowlist_free_mints,
+            max_per_owner: config.max_per_owner,
+            mint_price_curve: config.mint_price_curve,
+            track_history: config.track_history,
+            approval_delay_blocks: config.approval_delay_blocks,
+            operators_enabled: config.operators_enabled,
+            min_deposit: config.min_deposit,
+        })
+    }
+
+    pub fn collection_info(&self, deps: Deps) -> StdResult<CollectionInfoResponse> {
+        let contract_info = self.contract_info.load(deps.storage)?;
+        Ok(CollectionInfoResponse {
+            name: contract_info.name,
+            symbol: contract_info.symbol,
+            collection_uri: self.collection_uri(deps.storage)?,
+        })
+    }
+
+    // NftInfo, but an empty extension (null, or `{}`) is swapped for the collection's
+    // default_extension if it has one. Nothing is written back, so the stored token is unchanged
+    pub fn nft_info_with_defaults(
+        &self,
+        deps: Deps,
+        token_id: String,
+    ) -> StdResult<NftInfoResponse<T>> {
+        let mut info = self.nft_info(deps, token_id)?;
+        info.extension = self.extension_or_default(deps.storage, info.extension)?;
+        Ok(info)
+    }
+
+    pub fn status(&self, deps: Deps) -> StdResult<StatusResponse> {
+        Ok(StatusResponse {
+            transfers_enabled: self.transfers_enabled(deps.storage)?,
+            send_enabled: self.send_enabled(deps.storage)?,
+        })
+    }
+
+    // what's compiled in: the cw721 spec, plus the mint packet channel with the "ibc" feature
+    pub fn supported_standards(&self) -> SupportedStandardsResponse {
+        #[allow(unused_mut)]
+        let mut standards = vec![CW721_STANDARD.to_string()];
+        #[cfg(feature = "ibc")]
+        standards.push(crate::ibc::IBC_VERSION.to_string());
+        SupportedStandardsResponse { standards }
+    }
+
+    pub fn num_minted(&self, deps: Deps) -> StdResult<MintedTotalResponse> {
+        Ok(MintedTotalResponse {
+            count: self.minted_total(deps.storage)?,
+        })
+    }
+
+    pub fn upcoming_token_id(&self, deps: Deps) -> StdResult<NextTokenIdResponse> {
+        Ok(NextTokenIdResponse {
+            token_id: self.next_token_id(deps.storage)?,
+        })
+    }
+
+    pub fn num_burned(&self, deps: Deps) -> StdResult<BurnedTotalResponse> {
+        Ok(BurnedTotalResponse {
+            count: self.burned_total(deps.storage)?,
+        })
+    }
+
+    // balances only has entries for addresses holding something, so its size is the owner count
+    pub fn stats(&self, deps: Deps) -> StdResult<StatsResponse> {
+        Ok(StatsResponse {
+            supply: self.token_count(deps.storage)?,
+            minted_total: self.minted_total(deps.storage)?,
+            burned_total: self.burned_total(deps.storage)?,
+            owner_count: self.owner_count(deps.storage)?,
+        })
+    }
+
+    pub fn tokens_approved_to(
+        &self,
+        deps: Deps,
+        env: Env,
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        let mut tokens = vec![];
+        for token_id in self.spender_approvals.prefix(&spender_addr).keys(
+            deps.storage,
+            start,
+            None,
+            Order::Ascending,
+        ) {
+            if tokens.len() >= limit {
+                break;
+            }
+            let token_id = token_id?;
+            // expired approvals stay indexed until cleared, and delayed ones are indexed before
+            // they activate, but neither lets the spender move anything
+            let token = self.tokens.load(deps.storage, &token_id)?;
+            if token.approvals.iter().any(|apr| {
+                apr.spender == spender_addr
+                    && apr.is_active(&env.block)
+                    && !apr.is_expired(&env.block)
+            }) {
+                tokens.push(token_id);
+            }
+        }
+
+        Ok(TokensResponse { tokens })
+    }
+
+    // walks the owner index and filters in memory, as there is no (owner, spender) index.
+    // every token scanned is loaded, matching or not, so pages over big wallets with sparse
+    // approvals are the expensive case
+    pub fn owner_tokens_approved_to(
+        &self,
+        deps: Deps,
+        env: Env,
+        owner: String,
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        let tokens = self
+            .tokens
+            .idx
+            .owner
+            .prefix(owner_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|item| match item {
+                Ok((_, token)) => token.approvals.iter().any(|apr| {
+                    apr.spender == spender_addr
+                        && apr.is_active(&env.block)
+                        && !apr.is_expired(&env.block)
+                }),
+                Err(_) => true,
+            })
+            .take(limit)
+            .map(|item| item.map(|(token_id, _)| token_id))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TokensResponse { tokens })
+    }
+
+    // walks the mint order index from the newest token down
+    pub fn recent_tokens(
+        &self,
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<(u64, String)>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        // index keys are (mint_seq, token_id), and every token_id sorts after ""
+        let end = start_after.map(|seq| Bound::exclusive((seq, String::new())));
+
+        self.tokens
+            .idx
+            .mint_seq
+            .range(deps.storage, None, end, Order::Descending)
+            .take(limit)
+            .map(|item| item.map(|(token_id, token)| (token.mint_seq, token_id)))
+            .collect()
+    }
+
+    // kept after a burn, so this works for tokens that no longer exist
+    pub fn token_history(
+        &self,
+        deps: Deps,
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<(u64, HistoryEntry)>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        self.token_history
+            .prefix(&token_id)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect()
+    }
+
+    pub fn balance_of(&self, deps: Deps, owner: String) -> StdResult<BalanceResponse> {
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        Ok(BalanceResponse {
+            count: self.balance(deps.storage, &owner_addr)?,
+        })
+    }
+
+    /// distinct holders, read straight off the balance counters (owners at 0 have no entry)
+    pub fn all_owners(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OwnersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let owners = self
+            .balances
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|owner| owner.map(String::from))
+            .collect::<StdResult<_>>()?;
+        Ok(OwnersResponse { owners })
+    }
+
+    /// builds the receive message send_nft would produce, without transferring anything
+    pub fn simulate_send(
+        &self,
+        deps: Deps,
+        contract: String,
+        token_id: String,
+        msg: Binary,
+        wrap: bool,
+        with_owner: bool,
+    ) -> StdResult<SimulateSendResponse> {
+        let contract_addr = deps.api.addr_validate(&contract)?;
+        // a query has no sender, so preview the common case of the owner sending
+        let owner = self.tokens.load(deps.storage, &token_id)?.owner;
+        let send = Cw721ReceiveMsg {
+            sender: owner.to_string(),
+            token_id,
+            msg,
+        };
+        Ok(SimulateSendResponse {
+            contract: contract_addr.to_string(),
+            msg: receive_nft_binary(send, &owner, wrap, with_owner)?,
+        })
+    }
+
+    /// dry-runs the checks of a transfer by `address`, to `recipient` if it is known
+    pub fn can_transfer(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        address: String,
+        recipient: Option<String>,
+    ) -> StdResult<CanTransferResponse> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        let info = MessageInfo {
+            sender: deps.api.addr_validate(&address)?,
+            funds: vec![],
+        };
+        let recipient = maybe_addr(deps.api, recipient)?;
+        let can_transfer = self
+            .check_can_transfer(deps, &env, &info, &token, recipient.as_ref())
+            .is_ok();
+        Ok(CanTransferResponse { can_transfer })
+    }
+
+    /// the raw expiry of an operator grant; unlike AllOperators this doesn't filter expired ones
+    pub fn operator_expiration(
+        &self,
+        deps: Deps,
+        owner: String,
+        operator: String,
+    ) -> StdResult<Option<Expiration>> {
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        let grant = self
+            .operators
+            .may_load(deps.storage, (&owner_addr, &operator_addr))?;
+        Ok(grant.map(|grant| grant.expires))
+    }
+
+    // a grant scoped with min_token_height still counts, it covers some of the owner's tokens
+    pub fn is_operator(
+        &self,
+        deps: Deps,
+        env: Env,
+        owner: String,
+        operator: String,
+    ) -> StdResult<IsOperatorResponse> {
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        let grant = self
+            .operators
+            .may_load(deps.storage, (&owner_addr, &operator_addr))?;
+        Ok(IsOperatorResponse {
+            is_operator: grant.is_some_and(|grant| !grant.is_expired(&env.block)),
+        })
+    }
+
+    pub fn exists(&self, deps: Deps, token_id: String) -> StdResult<ExistsResponse> {
+        Ok(ExistsResponse {
+            exists: self.tokens.has(deps.storage, &token_id),
+        })
+    }
+
+    // the stored struct as is, for debugging and migrations
+    pub fn raw_token_info(&self, deps: Deps, token_id: String) -> StdResult<TokenInfo<T>> {
+        self.tokens.load(deps.storage, &token_id)
+    }
+
+    // counts only approvals still valid at this block, expired ones linger until cleared
+    pub fn token_approval_count(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+    ) -> StdResult<TokenApprovalCountResponse> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        let count = token
+            .approvals
+            .iter()
+            .filter(|apr| apr.is_active(&env.block) && !apr.is_expired(&env.block))
+            .count() as u32;
+        Ok(TokenApprovalCountResponse { count })
+    }
+
+    // tags are capped per token, so this can return them all without paging
+    pub fn token_tags(&self, deps: Deps, token_id: String) -> StdResult<TokenTagsResponse> {
+        self.tokens.load(deps.storage, &token_id)?;
+        let tags = self
+            .token_tags
+            .prefix(&token_id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        Ok(TokenTagsResponse { tags })
+    }
+
+    /// nft_info for several tokens at once. Missing tokens come back as None instead of failing
+    pub fn batch_nft_info(
+        &self,
+        deps: Deps,
+        token_ids: Vec<String>,
+    ) -> StdResult<Vec<(String, Option<NftInfoResponse<T>>)>> {
+        check_batch_size(&token_ids)?;
+        token_ids
+            .into_iter()
+            .map(|token_id| {
+                let info = self.tokens.may_load(deps.storage, &token_id)?;
+                let info = info.map(|info| NftInfoResponse {
+                    token_uri: info.token_uri,
+                    mint_block: recorded_height(info.mint_block),
+                    extension: info.extension,
+                    metadata_hash: info.metadata_hash,
+                    edition: info.edition,
+                    edition_of: info.edition_of,
+                });
+                Ok((token_id, info))
+            })
+            .collect()
+    }
+
+    pub fn batch_owner_of(
+        &self,
+        deps: Deps,
+        token_ids: Vec<String>,
+    ) -> StdResult<Vec<(String, Option<String>)>> {
+        check_batch_size(&token_ids)?;
+        token_ids
+            .into_iter()
+            .map(|token_id| {
+                let owner = self.tokens.may_load(deps.storage, &token_id)?;
+                Ok((token_id, owner.map(|info| info.owner.to_string())))
+            })
+            .collect()
+    }
+
+    pub fn tokens_by_owners(
+        &self,
+        deps: Deps,
+        owners: Vec<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<(String, Vec<String>)>> {
+        if owners.len() > MAX_BATCH_OWNERS {
+            return Err(StdError::generic_err(format!(
+                "Cannot query more than {} owners at once",
+                MAX_BATCH_OWNERS
+            )));
+        }
+        let mut remaining = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let mut res = vec![];
+        for owner in owners {
+            if remaining == 0 {
+                break;
+            }
+            let owner_addr = deps.api.addr_validate(&owner)?;
+            let tokens: Vec<String> = self
+                .tokens
+                .idx
+                .owner
+                .prefix(owner_addr)
+                .keys(deps.storage, None, None, Order::Ascending)
+                .take(remaining)
+                .collect::<StdResult<_>>()?;
+            remaining -= tokens.len();
+            res.push((owner, tokens));
+        }
+        Ok(res)
+    }
+
     pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg<Q>) -> StdResult<Binary> {
         match msg {
             QueryMsg::Minter {} => to_binary(&self.minter(deps)?),
+            QueryMsg::Minters {} => to_binary(&self.minters(deps)?),
+            QueryMsg::Config {} => to_binary(&self.collection_config(deps)?),
+            QueryMsg::CurrentMintPrice {} => to_binary(&self.current_mint_price(deps)?),
+            QueryMsg::BalanceOf { owner } => to_binary(&self.balance_of(deps, owner)?),
+            QueryMsg::AllOwners { start_after, limit } => {
+                to_binary(&self.all_owners(deps, start_after, limit)?)
+            }
+            QueryMsg::SimulateSend {
+                contract,
+                token_id,
+                msg,
+                wrap,
+                with_owner,
+            } => to_binary(&self.simulate_send(deps, contract, token_id, msg, wrap, with_owner)?),
+            QueryMsg::CanTransfer {
+                token_id,
+                address,
+                recipient,
+            } => to_binary(&self.can_transfer(deps, env, token_id, address, recipient)?),
+            QueryMsg::Exists { token_id } => to_binary(&self.exists(deps, token_id)?),
+            QueryMsg::RawTokenInfo { token_id } => to_binary(&self.raw_token_info(deps, token_id)?),
+            QueryMsg::TokenApprovalCount { token_id } => {
+                to_binary(&self.token_approval_count(deps, env, token_id)?)
+            }
+            QueryMsg::TokenTags { token_id } => to_binary(&self.token_tags(deps, token_id)?),
             QueryMsg::ContractInfo {} => to_binary(&self.contract_info(deps)?),
-            QueryMsg::NftInfo { token_id } => to_binary(&self.nft_info(deps, token_id)?),
+            QueryMsg::Version {} => to_binary(&get_contract_version(deps.storage)?),
+            QueryMsg::CollectionInfo {} => to_binary(&self.collection_info(deps)?),
+            QueryMsg::SupportedStandards {} => to_binary(&self.supported_standards()),
+            QueryMsg::Status {} => to_binary(&self.status(deps)?),
+            QueryMsg::NftInfo {
+                token_id,
+                with_defaults,
+            } => {
+                if with_defaults.unwrap_or(false) {
+                    to_binary(&self.nft_info_with_defaults(deps, token_id)?)
+                } else {
+                    to_binary(&self.nft_info(deps, token_id)?)
+                }
+            }
+            QueryMsg::BatchNftInfo { token_ids } => {
+                to_binary(&self.batch_nft_info(deps, token_ids)?)
+            }
+            QueryMsg::BatchOwnerOf { token_ids } => {
+                to_binary(&self.batch_owner_of(deps, token_ids)?)
+            }
+            QueryMsg::TokensByOwners { owners, limit } => {
+                to_binary(&self.tokens_by_owners(deps, owners, limit)?)
+            }
             QueryMsg::OwnerOf {
                 token_id,
                 include_expired,
@@ -256,12 +778,48 @@ where
                 start_after,
                 limit,
             )?),
+            QueryMsg::OperatorExpiration { owner, operator } => {
+                to_binary(&self.operator_expiration(deps, owner, operator)?)
+            }
+            QueryMsg::IsOperator { owner, operator } => {
+                to_binary(&self.is_operator(deps, env, owner, operator)?)
+            }
             QueryMsg::NumTokens {} => to_binary(&self.num_tokens(deps)?),
+            QueryMsg::MintedTotal {} => to_binary(&self.num_minted(deps)?),
+            QueryMsg::NextTokenId {} => to_binary(&self.upcoming_token_id(deps)?),
+            QueryMsg::BurnedTotal {} => to_binary(&self.num_burned(deps)?),
+            QueryMsg::Stats {} => to_binary(&self.stats(deps)?),
             QueryMsg::Tokens {
                 owner,
                 start_after,
                 limit,
             } => to_binary(&self.tokens(deps, owner, start_after, limit)?),
+            QueryMsg::OwnerTokensApprovedTo {
+                owner,
+                spender,
+                start_after,
+                limit,
+            } => to_binary(&self.owner_tokens_approved_to(
+                deps,
+                env,
+                owner,
+                spender,
+                start_after,
+                limit,
+            )?),
+            QueryMsg::RecentTokens { start_after, limit } => {
+                to_binary(&self.recent_tokens(deps, start_after, limit)?)
+            }
+            QueryMsg::TokenHistory {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&self.token_history(deps, token_id, start_after, limit)?),
+            QueryMsg::TokensApprovedTo {
+                spender,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_approved_to(deps, env, spender, start_after, limit)?),
             QueryMsg::AllTokens { start_after, limit } => {
                 to_binary(&self.all_tokens(deps, start_after, limit)?)
             }
@@ -287,10 +845,10 @@ where
     }
 }
 
-fn parse_approval(item: StdResult<(Addr, Expiration)>) -> StdResult<cw721::Approval> {
-    item.map(|(spender, expires)| cw721::Approval {
+fn parse_approval(item: StdResult<(Addr, OperatorGrant)>) -> StdResult<cw721::Approval> {
+    item.map(|(spender, grant)| cw721::Approval {
         spender: spender.to_string(),
-        expires,
+        expires: grant.expires,
     })
 }
 
@@ -299,8 +857,10 @@ fn humanize_approvals<T>(
     info: &TokenInfo<T>,
     include_expired: bool,
 ) -> Vec<cw721::Approval> {
+    // approvals still waiting out approval_delay_blocks can't be used, so they're left out
     info.approvals
         .iter()
+        .filter(|apr| apr.is_active(block))
         .filter(|apr| include_expired || !apr.is_expired(block))
         .map(humanize_approval)
         .collect()
@@ -312,3 +872,19 @@ fn humanize_approval(approval: &Approval) -> cw721::Approval {
         expires: approval.expires,
     }
 }
+
+// tokens saved before a height was recorded have 0 stored for it, which reads as unknown
+fn recorded_height(height: u64) -> Option<u64> {
+    (height != 0).then_some(height)
+}
+
+// the batch queries refuse long lists outright rather than silently truncating them
+fn check_batch_size(token_ids: &[String]) -> StdResult<()> {
+    if token_ids.len() > MAX_BATCH_TOKENS {
+        return Err(StdError::generic_err(format!(
+            "Cannot query more than {} tokens at once",
+            MAX_BATCH_TOKENS
+        )));
+    }
+    Ok(())
+}
@@ -3,7 +3,10 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
-use cosmwasm_std::{Addr, BlockInfo, CustomMsg, StdResult, Storage};
+use cosmwasm_std::{
+    to_vec, Addr, Binary, BlockInfo, Coin, CustomMsg, Empty, Order, OverflowError,
+    OverflowOperation, StdError, StdResult, Storage, Uint128,
+};
 
 use cw721::{ContractInfoResponse, Cw721, Expiration};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
@@ -22,8 +25,21 @@ where
 {
     // This is a reference to the CosmWasm storage. It's a reference because we don't want to own the storage, we just want to borrow it. We don't want to own it because we don't want to be able to change it. We just want to be able to read it.
     pub contract_info: Item<'a, ContractInfoResponse>,
-    pub minter: Item<'a, Addr>,
+    // collection-wide settings chosen at instantiate
+    pub config: Item<'a, Config>,
+    // everyone allowed to mint; there is always at least one
+    pub minters: Map<'a, &'a Addr, bool>,
+    // manages contract settings, separate from the minter
+    pub owner: Item<'a, Addr>,
+    // an optional second address that may update token metadata, but not mint
+    pub metadata_updater: Item<'a, Option<Addr>>,
     pub token_count: Item<'a, u64>,
+    // every token ever minted; unlike token_count this never goes down on burn
+    pub minted_total: Item<'a, u64>,
+    // number of tokens held by each owner, so balances don't need to scan the owner index
+    pub balances: Map<'a, &'a Addr, u64>,
+    // contracts SendNft may target; when empty, any receiver is allowed
+    pub allowed_receivers: Map<'a, &'a Addr, bool>,
     /// The Map() method: a function to each element in an iterable and returns the resulting iterable, of each iteration, to the next function.
     /// Stored as (granter, operator) giving operator full control over granter's account
     /// <'a, (&'a Addr, &'a Addr), Expiration> is the type of the map. 
@@ -32,11 +48,55 @@ where
     /// The expiration is the expiration of the operator's ability to control the granter's account (when it expires if it exists). 
     /// When a granter gives an operator permission to control their NFTs, there can be an expiration date (could be never).
     /// lifetime 'a, key type (&'a Addr, &'a Addr), value type Expiration
-    pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
+    /// The value also carries an optional scope limiting the grant to tokens minted at or after a given height.
+    pub operators: Map<'a, (&'a Addr, &'a Addr), OperatorGrant>,
     /// lifetime 'a, key type &'a Addr, value type TokenInfo (data that is stored in the map as a struct, which can have the extension T). We also take an IndexList type which we imported above.
     /// We need to define the TokenIndexes are in order to use the IndexedMap() method. We also need to define the TokenInfo struct (what each token contains).
     /// Wouldn't need to add addition arguments because for additional indexes because TokenIndexes is a list of all indexes.
     pub tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
+    /// Reverse index of token approvals, stored as (spender, token_id).
+    /// Kept in sync with TokenInfo.approvals so we can list what a spender can move without scanning every token.
+    pub spender_approvals: Map<'a, (&'a Addr, &'a str), Empty>,
+    // the receivers of the sends in flight, innermost last, so a failed ReceiveNft can name its
+    // receiver in the reply (see push_pending_send)
+    pub pending_sends: Map<'a, u32, Addr>,
+    // who may mint besides the minters; MinterOnly if never set
+    pub current_phase: Item<'a, Phase>,
+    // addresses that may mint during the allowlist phase
+    pub mint_allowlist: Map<'a, &'a Addr, bool>,
+    // secp256k1 pubkeys owners registered to sign transfer permits with
+    pub permit_pubkeys: Map<'a, &'a Addr, Binary>,
+    // the nonce each owner's next permit must carry, so a permit can't be replayed
+    pub permit_nonces: Map<'a, &'a Addr, u64>,
+    // extension given to tokens minted without one, if the collection set one at instantiate
+    pub default_extension: Item<'a, T>,
+    // the token as it was before each safe SendNft still waiting on its receiver, by the send's
+    // reply id, so the reply can put it back if the receiver fails
+    pub safe_send_backups: Map<'a, u64, SafeSendBackup<T>>,
+    // token_ids ever minted with a signature, so burning one doesn't let its signature be reused
+    pub signed_mints: Map<'a, &'a str, Empty>,
+    // whether tokens can change hands at all, e.g. off during a vesting period; true if never set
+    pub transfers_enabled: Item<'a, bool>,
+    // IBC channels that completed their handshake with this contract
+    pub ibc_channels: Map<'a, &'a str, Empty>,
+    // the channel every mint sends a MintPacket over, if the owner picked one
+    pub mint_channel: Item<'a, String>,
+    // every token ever burned, so minted_total - burned_total == token_count
+    pub burned_total: Item<'a, u64>,
+    // OpenSea-style contractURI, pointing at collection-level metadata (banner, description, ...)
+    pub collection_uri: Item<'a, Option<String>>,
+    // free mints each allowlisted address has used up, see Config::allowlist_free_mints
+    pub free_mints_used: Map<'a, &'a Addr, u32>,
+    // contract notified of every transfer, see SetTransferHook
+    pub transfer_hook: Item<'a, TransferHook>,
+    // free-form (token_id, key) -> value tags, see SetTokenTag
+    pub token_tags: Map<'a, (&'a str, &'a str), String>,
+    // (token_id, seq) -> ownership change, kept only with Config::track_history
+    pub token_history: Map<'a, (&'a str, u64), HistoryEntry>,
+    // whether SendNft (to contracts) is allowed at all; true if never set
+    pub send_enabled: Item<'a, bool>,
+    // addresses holding at least one token, kept by increment_balance and decrement_balance
+    pub owner_count: Item<'a, u64>,
 
     pub(crate) _custom_response: PhantomData<C>,
     pub(crate) _custom_query: PhantomData<Q>,
@@ -61,14 +121,55 @@ where
 {
     // We find the storage by these keys. Everything that lives in storage is found by these keys.
     fn default() -> Self {
-        Self::new(
-            "nft_info",
-            "minter",
-            "num_tokens",
-            "operators",
-            "tokens",
-            "tokens__owner",
-        )
+        Self::with_keys(STORAGE_KEYS)
+    }
+}
+
+// the default storage keys, in the order `new` takes them
+const STORAGE_KEYS: [&str; 33] = [
+    "nft_info",
+    // not "config", derived contracts (e.g. cw2981, non-transferable) keep their own config there
+    "collection_config",
+    "minters",
+    "owner",
+    "metadata_updater",
+    "num_tokens",
+    "minted_total",
+    "balances",
+    "allowed_receivers",
+    "operators",
+    "tokens",
+    "tokens__owner",
+    "spender_approvals",
+    "pending_sends",
+    "current_phase",
+    "mint_allowlist",
+    "permit_pubkeys",
+    "permit_nonces",
+    "default_extension",
+    "safe_send_backups",
+    "signed_mints",
+    "transfers_enabled",
+    "ibc_channels",
+    "mint_channel",
+    "burned_total",
+    "collection_uri",
+    "free_mints_used",
+    "tokens__mint_seq",
+    "transfer_hook",
+    "token_tags",
+    "token_history",
+    "send_enabled",
+    "owner_count",
+];
+
+/// The default storage keys with a common prefix, built once and borrowed by
+/// Cw721Contract::with_prefix for as long as the contract is used
+pub struct PrefixedKeys([String; STORAGE_KEYS.len()]);
+
+impl PrefixedKeys {
+    pub fn new(prefix: &str) -> Self {
+        PrefixedKeys(STORAGE_KEYS.map(|name| format!("{}{}", prefix, name)))
     }
 }
 
@@ -78,53 +179,528 @@ where
     E: CustomMsg,
     Q: CustomMsg,
 {
-    // storage keys calls this new function which set all the keys as storage keys in the contract storage
-    fn new(
+    /// The default contract with every storage key (token indexes included) starting with the
+    /// prefix of `keys`, so a wrapper contract can host several collections side by side, each
+    /// with its own prefix. Contract-wide data outside Cw721Contract, such as the cw2 version,
+    /// stays shared
+    pub fn with_prefix(keys: &'a PrefixedKeys) -> Self {
+        Self::with_keys(keys.0.each_ref().map(String::as_str))
+    }
+
+    fn with_keys(keys: [&'a str; STORAGE_KEYS.len()]) -> Self {
+        // rustfmt can't wrap a pattern this long and would put it all on one line
+        #[rustfmt::skip]
+        let [
+            contract_key,
+            config_key,
+            minters_key,
+            owner_key,
+            metadata_updater_key,
+            token_count_key,
+            minted_total_key,
+            balances_key,
+            allowed_receivers_key,
+            operator_key,
+            tokens_key,
+            tokens_owner_key,
+            spender_approvals_key,
+            pending_sends_key,
+            current_phase_key,
+            mint_allowlist_key,
+            permit_pubkeys_key,
+            permit_nonces_key,
+            default_extension_key,
+            safe_send_backups_key,
+            signed_mints_key,
+            transfers_enabled_key,
+            ibc_channels_key,
+            mint_channel_key,
+            burned_total_key,
+            collection_uri_key,
+            free_mints_used_key,
+            tokens_mint_seq_key,
+            transfer_hook_key,
+            token_tags_key,
+            token_history_key,
+            send_enabled_key,
+            owner_count_key,
+        ] = keys;
+        Self::new(
+            contract_key,
+            config_key,
+            minters_key,
+            owner_key,
+            metadata_updater_key,
+            token_count_key,
+            minted_total_key,
+            balances_key,
+            allowed_receivers_key,
+            operator_key,
+            tokens_key,
+            tokens_owner_key,
+            spender_approvals_key,
+            pending_sends_key,
+            current_phase_key,
+            mint_allowlist_key,
+            permit_pubkeys_key,
+            permit_nonces_key,
+            default_extension_key,
+            safe_send_backups_key,
+            signed_mints_key,
+            transfers_enabled_key,
+            ibc_channels_key,
+            mint_channel_key,
+            burned_total_key,
+            collection_uri_key,
+            free_mints_used_key,
+            tokens_mint_seq_key,
+            transfer_hook_key,
+            token_tags_key,
+            token_history_key,
+            send_enabled_key,
+            owner_count_key,
+        )
+    }
+
+    /// Builds the contract over the given storage keys: one per storage field, named after it,
+    /// plus tokens_owner_key and tokens_mint_seq_key for the owner and mint order indexes of
+    /// tokens. Keys must be distinct. Wrappers hosting several collections can use with_prefix
+    /// instead of listing them all
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
         contract_key: &'a str,
-        minter_key: &'a str,
+        config_key: &'a str,
+        minters_key: &'a str,
+        owner_key: &'a str,
+        metadata_updater_key: &'a str,
         token_count_key: &'a str,
+        minted_total_key: &'a str,
+        balances_key: &'a str,
+        allowed_receivers_key: &'a str,
         operator_key: &'a str,
         tokens_key: &'a str,
         tokens_owner_key: &'a str,
+        spender_approvals_key: &'a str,
+        pending_sends_key: &'a str,
+        current_phase_key: &'a str,
+        mint_allowlist_key: &'a str,
+        permit_pubkeys_key: &'a str,
+        permit_nonces_key: &'a str,
+        default_extension_key: &'a str,
+        safe_send_backups_key: &'a str,
+        signed_mints_key: &'a str,
+        transfers_enabled_key: &'a str,
+        ibc_channels_key: &'a str,
+        mint_channel_key: &'a str,
+        burned_total_key: &'a str,
+        collection_uri_key: &'a str,
+        free_mints_used_key: &'a str,
+        tokens_mint_seq_key: &'a str,
+        transfer_hook_key: &'a str,
+        token_tags_key: &'a str,
+        token_history_key: &'a str,
+        send_enabled_key: &'a str,
+        owner_count_key: &'a str,
     ) -> Self {
         // asks by address, returns all token ids owned by that address
         // look at TokenIndexes struct below
         let indexes = TokenIndexes {
             // new owner index which is a MultiIndex
             owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
+            mint_seq: MultiIndex::new(token_mint_seq_idx, tokens_key, tokens_mint_seq_key),
         };
         Self {
             contract_info: Item::new(contract_key),
-            // looking at the minter_key (fn default) to get the Item that is in storage (struct Cw721Contract) at the key "minter" (fn default)
-            minter: Item::new(minter_key), 
+            config: Item::new(config_key),
+            // looking at the minters_key (fn default) to get the Map that is in storage (struct Cw721Contract) at the key "minters" (fn default)
+            minters: Map::new(minters_key),
+            owner: Item::new(owner_key),
+            metadata_updater: Item::new(metadata_updater_key),
             token_count: Item::new(token_count_key),
+            minted_total: Item::new(minted_total_key),
+            balances: Map::new(balances_key),
+            allowed_receivers: Map::new(allowed_receivers_key),
             operators: Map::new(operator_key),
             tokens: IndexedMap::new(tokens_key, indexes),
+            spender_approvals: Map::new(spender_approvals_key),
+            pending_sends: Map::new(pending_sends_key),
+            current_phase: Item::new(current_phase_key),
+            mint_allowlist: Map::new(mint_allowlist_key),
+            permit_pubkeys: Map::new(permit_pubkeys_key),
+            permit_nonces: Map::new(permit_nonces_key),
+            default_extension: Item::new(default_extension_key),
+            safe_send_backups: Map::new(safe_send_backups_key),
+            signed_mints: Map::new(signed_mints_key),
+            transfers_enabled: Item::new(transfers_enabled_key),
+            ibc_channels: Map::new(ibc_channels_key),
+            mint_channel: Item::new(mint_channel_key),
+            burned_total: Item::new(burned_total_key),
+            collection_uri: Item::new(collection_uri_key),
+            free_mints_used: Map::new(free_mints_used_key),
+            transfer_hook: Item::new(transfer_hook_key),
+            token_tags: Map::new(token_tags_key),
+            token_history: Map::new(token_history_key),
+            send_enabled: Item::new(send_enabled_key),
+            owner_count: Item::new(owner_count_key),
             _custom_response: PhantomData,
             _custom_execute: PhantomData,
             _custom_query: PhantomData,
         }
     }
 
+    // the collection settings, defaults if they were never saved
+    pub fn config(&self, storage: &dyn Storage) -> StdResult<Config> {
+        Ok(self.config.may_load(storage)?.unwrap_or_default())
+    }
+
+    // the active mint phase, MinterOnly if the owner never set one
+    pub fn current_phase(&self, storage: &dyn Storage) -> StdResult<Phase> {
+        Ok(self.current_phase.may_load(storage)?.unwrap_or_default())
+    }
+
+    // what a mint in this phase must pay (any one of them), empty if minting is free.
+    // with a price curve that is a single price, going up with the supply
+    pub fn mint_prices(&self, storage: &dyn Storage, phase: Phase) -> StdResult<Vec<Coin>> {
+        let config = self.config(storage)?;
+        if let Some(curve) = config.mint_price_curve {
+            return Ok(vec![curve.price(self.token_count(storage)?)?]);
+        }
+        if phase == Phase::Allowlist && !config.allowlist_mint_prices.is_empty() {
+            Ok(config.allowlist_mint_prices)
+        } else {
+            Ok(config.mint_prices)
+        }
+    }
+
+    // whether transfers are currently allowed, true unless the owner turned them off
+    pub fn transfers_enabled(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.transfers_enabled.may_load(storage)?.unwrap_or(true))
+    }
+
+    // whether tokens can be sent to contracts, true unless the owner turned it off
+    pub fn send_enabled(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.send_enabled.may_load(storage)?.unwrap_or(true))
+    }
+
+    // the collection's default extension in place of an empty one (null, e.g. None for the usual
+    // Option extensions, or `{}`), if it has one. Mints and NftInfo with_defaults both go through here
+    pub fn extension_or_default(&self, storage: &dyn Storage, extension: T) -> StdResult<T> {
+        let raw = to_vec(&extension)?;
+        if raw == b"null" || raw == b"{}" {
+            if let Some(default_extension) = self.default_extension.may_load(storage)? {
+                return Ok(default_extension);
+            }
+        }
+        Ok(extension)
+    }
+
+    // the collection-level metadata uri, if one was ever set
+    pub fn collection_uri(&self, storage: &dyn Storage) -> StdResult<Option<String>> {
+        Ok(self.collection_uri.may_load(storage)?.flatten())
+    }
+
     // keeping track of number of tokens
     pub fn token_count(&self, storage: &dyn Storage) -> StdResult<u64> {
         Ok(self.token_count.may_load(storage)?.unwrap_or_default())
     }
 
+    // whether addr is one of the minters
+    pub fn is_minter(&self, storage: &dyn Storage, addr: &Addr) -> StdResult<bool> {
+        Ok(self.minters.may_load(storage, addr)?.unwrap_or_default())
+    }
+
+    // the metadata updater, if one was ever set
+    pub fn metadata_updater(&self, storage: &dyn Storage) -> StdResult<Option<Addr>> {
+        Ok(self.metadata_updater.may_load(storage)?.flatten())
+    }
+
     // incrementing the token count
     // takes the storage, storage at the the key "num_tokens" as an Item 
     // loads the value at that key, increments it by 1, and saves it back to the storage
+    // checked so a (theoretical) overflow is an error instead of a panic
     pub fn increment_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
-        let val = self.token_count(storage)? + 1;
+        let count = self.token_count(storage)?;
+        let val = count
+            .checked_add(1)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Add, count, 1))?;
         self.token_count.save(storage, &val)?;
+        self.debug_check_token_count(storage);
         Ok(val)
     }
 
+    // likewise, going below zero would mean the count is already out of sync
     pub fn decrement_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
-        let val = self.token_count(storage)? - 1;
+        let count = self.token_count(storage)?;
+        let val = count
+            .checked_sub(1)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Sub, count, 1))?;
         self.token_count.save(storage, &val)?;
+        self.debug_check_token_count(storage);
+        Ok(val)
+    }
+
+    // token_count must always match the number of stored tokens, and minted_total - burned_total.
+    // The tokens and totals are already updated when the count changes, so they can all be
+    // compared right after. Counting walks every token, so this only runs in debug builds (i.e. tests)
+    fn debug_check_token_count(&self, storage: &dyn Storage) {
+        if cfg!(debug_assertions) {
+            let stored = self
+                .tokens
+                .keys(storage, None, None, Order::Ascending)
+                .count() as u64;
+            debug_assert_eq!(self.token_count(storage).ok(), Some(stored));
+            let minted = self.minted_total(storage).unwrap_or_default();
+            let burned = self.burned_total(storage).unwrap_or_default();
+            debug_assert_eq!(minted.checked_sub(burned), Some(stored));
+        }
+    }
+
+    // number of tokens ever minted, including burned ones
+    pub fn minted_total(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.minted_total.may_load(storage)?.unwrap_or_default())
+    }
+
+    // the id MintNext hands out next: ids follow minted_total, so burned ids aren't reused
+    pub fn next_token_id(&self, storage: &dyn Storage) -> StdResult<String> {
+        Ok(self.next_mint_seq(storage)?.to_string())
+    }
+
+    // the mint_seq the next minted token gets, which is minted_total once it's minted
+    pub fn next_mint_seq(&self, storage: &dyn Storage) -> StdResult<u64> {
+        let total = self.minted_total(storage)?;
+        let val = total
+            .checked_add(1)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Add, total, 1))?;
+        Ok(val)
+    }
+
+    pub fn increment_minted_total(&self, storage: &mut dyn Storage) -> StdResult<u64> {
+        let val = self.next_mint_seq(storage)?;
+        self.minted_total.save(storage, &val)?;
+        Ok(val)
+    }
+
+    // number of tokens ever burned
+    pub fn burned_total(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.burned_total.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn increment_burned_total(&self, storage: &mut dyn Storage) -> StdResult<u64> {
+        let total = self.burned_total(storage)?;
+        let val = total
+            .checked_add(1)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Add, total, 1))?;
+        self.burned_total.save(storage, &val)?;
         Ok(val)
     }
+
+    // drops the reverse index entries for approvals that are being cleared from a token
+    pub fn unindex_approvals(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        approvals: &[Approval],
+    ) {
+        for approval in approvals {
+            self.spender_approvals
+                .remove(storage, (&approval.spender, token_id));
+        }
+    }
+
+    // adds the reverse index entries for approvals a token (again) carries
+    pub fn index_approvals(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        approvals: &[Approval],
+    ) -> StdResult<()> {
+        for approval in approvals {
+            self.spender_approvals
+                .save(storage, (&approval.spender, token_id), &Empty {})?;
+        }
+        Ok(())
+    }
+
+    // number of addresses holding at least one token
+    pub fn owner_count(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.owner_count.may_load(storage)?.unwrap_or_default())
+    }
+
+    // number of tokens held by owner, 0 if they hold none
+    pub fn balance(&self, storage: &dyn Storage, owner: &Addr) -> StdResult<u64> {
+        Ok(self.balances.may_load(storage, owner)?.unwrap_or_default())
+    }
+
+    pub fn increment_balance(&self, storage: &mut dyn Storage, owner: &Addr) -> StdResult<u64> {
+        let balance = self.balance(storage, owner)?;
+        let val = balance
+            .checked_add(1)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Add, balance, 1))?;
+        self.balances.save(storage, owner, &val)?;
+        // a first token makes them an owner
+        if balance == 0 {
+            let owners = self.owner_count(storage)?;
+            let owners = owners
+                .checked_add(1)
+                .ok_or_else(|| OverflowError::new(OverflowOperation::Add, owners, 1))?;
+            self.owner_count.save(storage, &owners)?;
+        }
+        Ok(val)
+    }
+
+    // drops the entry once the owner holds nothing, so storage doesn't fill with zeros
+    pub fn decrement_balance(&self, storage: &mut dyn Storage, owner: &Addr) -> StdResult<u64> {
+        let balance = self.balance(storage, owner)?;
+        let val = balance
+            .checked_sub(1)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Sub, balance, 1))?;
+        if val == 0 {
+            self.balances.remove(storage, owner);
+            let owners = self.owner_count(storage)?;
+            let owners = owners
+                .checked_sub(1)
+                .ok_or_else(|| OverflowError::new(OverflowOperation::Sub, owners, 1))?;
+            self.owner_count.save(storage, &owners)?;
+        } else {
+            self.balances.save(storage, owner, &val)?;
+        }
+        Ok(val)
+    }
+
+    // a receiver may send on another token from its ReceiveNft before this send's reply comes
+    // back, so the pending receivers form a stack. Replies come back innermost first
+    pub fn push_pending_send(&self, storage: &mut dyn Storage, contract: &Addr) -> StdResult<()> {
+        let depth = self
+            .pending_sends
+            .keys(storage, None, None, Order::Ascending)
+            .count();
+        self.pending_sends.save(storage, depth as u32, contract)
+    }
+
+    pub fn pop_pending_send(&self, storage: &mut dyn Storage) -> StdResult<Addr> {
+        let (depth, contract) = self
+            .pending_sends
+            .range(storage, None, None, Order::Descending)
+            .next()
+            .ok_or_else(|| StdError::not_found("pending send"))??;
+        self.pending_sends.remove(storage, depth);
+        Ok(contract)
+    }
+}
+
+// Collection-wide settings, set at instantiate
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct Config {
+    /// Who may burn tokens
+    pub burn_policy: BurnPolicy,
+    /// How many blocks an approval lasts when granted without an expiration.
+    /// None keeps the old behaviour of never expiring
+    pub default_approval_duration: Option<u64>,
+    /// If true, tokens can't be minted to contract addresses, which may not be able to move them
+    pub reject_contract_owners: bool,
+    /// Minimum number of blocks between two transfers of the same token, None disables it
+    pub transfer_cooldown_blocks: Option<u64>,
+    /// Accepted mint prices, any one of which pays for a mint. Empty means minting is free
+    #[serde(default)]
+    pub mint_prices: Vec<Coin>,
+    /// Discounted prices for allowlisted mints. Empty means they pay the full mint_prices
+    #[serde(default)]
+    pub allowlist_mint_prices: Vec<Coin>,
+    /// Flat fee every transfer must pay to the collection owner, None makes transfers free
+    #[serde(default)]
+    pub transfer_fee: Option<Coin>,
+    /// Longest token_id that can be minted, None uses the default of 128 characters
+    #[serde(default)]
+    pub max_token_id_length: Option<u32>,
+    /// token_ids starting with this can only be minted by the minters
+    #[serde(default)]
+    pub reserved_prefix: Option<String>,
+    /// Contract notified with a RedeemMsg whenever a token is burned
+    #[serde(default)]
+    pub redemption_contract: Option<Addr>,
+    /// If true, transfer, send and approve responses only carry the action and token_id attributes
+    #[serde(default)]
+    pub minimal_events: bool,
+    /// secp256k1 pubkey of the off-chain signer whose signatures authorize MintSigned
+    #[serde(default)]
+    pub signer_pubkey: Option<Binary>,
+    /// If true, SendNft keeps the token's approvals, where a transfer clears them
+    #[serde(default)]
+    pub keep_approvals_on_send: bool,
+    /// Most tokens the collection can have, None for no cap
+    #[serde(default)]
+    pub max_supply: Option<u64>,
+    /// If true, max_supply counts tokens in existence rather than every token ever minted
+    #[serde(default)]
+    pub burns_free_supply: bool,
+    /// Free mints per allowlisted address, before they pay like anyone else
+    #[serde(default)]
+    pub allowlist_free_mints: Option<u32>,
+    /// Most tokens one address can hold, None for no limit
+    #[serde(default)]
+    pub max_per_owner: Option<u64>,
+    /// Linear bonding curve for the mint price, replacing mint_prices while set
+    #[serde(default)]
+    pub mint_price_curve: Option<MintPriceCurve>,
+    /// Whether ownership changes are recorded in token_history
+    #[serde(default)]
+    pub track_history: bool,
+    /// Blocks between approving a spender and the approval taking effect
+    #[serde(default)]
+    pub approval_delay_blocks: u64,
+    /// Whether ApproveAll operator grants are allowed, false leaves only per-token approvals
+    #[serde(default = "default_operators_enabled")]
+    pub operators_enabled: bool,
+    /// Funds Withdraw always leaves in the contract, for chains with storage rent
+    #[serde(default)]
+    pub min_deposit: Option<Coin>,
+}
+
+// configs saved before operators_enabled existed allowed operators
+fn default_operators_enabled() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Only the minters can mint (the standard cw721-base behaviour)
+    #[default]
+    MinterOnly,
+    /// Addresses on the mint allowlist can mint too, at the allowlist price if there is one
+    Allowlist,
+    /// Anyone can mint, at the full price
+    Public,
+}
+
+/// A mint price of `base_price` plus `step` (in the same denom) per token in circulation
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct MintPriceCurve {
+    pub base_price: Coin,
+    pub step: Uint128,
+}
+
+impl MintPriceCurve {
+    /// the price of the next mint, with `token_count` tokens already in circulation
+    pub fn price(&self, token_count: u64) -> StdResult<Coin> {
+        let amount = self
+            .step
+            .checked_mul(Uint128::from(token_count))?
+            .checked_add(self.base_price.amount)?;
+        Ok(Coin::new(amount.u128(), &self.base_price.denom))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BurnPolicy {
+    /// The owner, an approved spender or an operator can burn (the standard cw721 behaviour)
+    #[default]
+    Owner,
+    /// Only the minter can burn, e.g. so holders can't destroy limited editions
+    MinterOnly,
+    /// Tokens can never be burned
+    Disabled,
 }
 
 // Stored for each token    
@@ -132,16 +708,75 @@ where
 pub struct TokenInfo<T> {
     /// The owner of the newly minted NFT
     pub owner: Addr,
-    /// Approvals are stored here, as we clear them all upon transfer and cannot accumulate much
+    /// Approvals are stored here, as we clear them upon transfer (bar persistent ones)
+    /// and cannot accumulate much
     pub approvals: Vec<Approval>,
+    /// Spenders whose approval is kept when the token changes hands, e.g. a custodian.
+    /// Set by the token owner through SetPersistentApproval
+    #[serde(default)]
+    pub persistent_approvals: Vec<Addr>,
 
     /// Universal resource identifier for this NFT
     /// Should point to a JSON file that conforms to the ERC721
     /// Metadata JSON Schema
     pub token_uri: Option<String>,
 
+    /// Block height at which this token was minted, 0 for tokens minted before this was recorded
+    #[serde(default)]
+    pub mint_block: u64,
+
+    /// Block height of the last ownership change (the mint height until first transferred),
+    /// 0 for tokens that haven't moved since before this was recorded
+    #[serde(default)]
+    pub last_transfer: u64,
+
+    /// Position in mint order: 1 for the first token ever minted, 2 for the next, and so on.
+    /// 0 for tokens minted before this was recorded
+    #[serde(default)]
+    pub mint_seq: u64,
+
     /// You can add any custom metadata here when you extend cw721-base
     pub extension: T,
+
+    /// Hex-encoded sha256 of the off-chain metadata, for integrity checks
+    #[serde(default)]
+    pub metadata_hash: Option<String>,
+
+    /// Serial number within a limited edition, e.g. 3 for "3 of 50"
+    #[serde(default)]
+    pub edition: Option<u32>,
+
+    /// Size of the limited edition, e.g. 50 for "3 of 50"
+    #[serde(default)]
+    pub edition_of: Option<u32>,
+
+    /// Set by a minter for giveaways and promotions: the token moves without the collection's
+    /// transfer fee, and reports no royalty
+    #[serde(default)]
+    pub royalty_exempt: bool,
+
+    /// How many times the token may change hands before it is retired (it can still be burned).
+    /// Set by a minter through SetMaxTransfers, None for no limit
+    #[serde(default)]
+    pub max_transfers: Option<u32>,
+
+    /// How many times the token has changed hands since it was minted
+    #[serde(default)]
+    pub transfer_count: u32,
+}
+
+/// A safe SendNft waiting on its receiver, so the reply can complete or undo it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SafeSendBackup<T> {
+    pub token_id: String,
+    /// The contract it was sent to
+    pub contract: Addr,
+    /// The token as it was before the send
+    pub previous: TokenInfo<T>,
+    /// Who sent it, and the funds they attached. The transfer fee is paid from these if the
+    /// receiver accepts the token, otherwise they are refunded
+    pub sender: Addr,
+    pub funds: Vec<Coin>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -150,12 +785,62 @@ pub struct Approval {
     pub spender: Addr,
     /// When the Approval expires (maybe Expiration::never)
     pub expires: Expiration,
+    /// Height from which the spender can use it, see Config::approval_delay_blocks
+    #[serde(default)]
+    pub active_from: u64,
 }
 
 impl Approval {
     pub fn is_expired(&self, block: &BlockInfo) -> bool {
         self.expires.is_expired(block)
     }
+
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        block.height >= self.active_from
+    }
+}
+
+// Stored for each (granter, operator) pair
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OperatorGrant {
+    /// When the grant expires (maybe Expiration::never)
+    pub expires: Expiration,
+    /// If set, the operator may only act on tokens minted at or after this height
+    pub min_token_height: Option<u64>,
+}
+
+impl OperatorGrant {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.expires.is_expired(block)
+    }
+
+    /// returns true if the grant's scope includes a token minted at the given height
+    pub fn covers(&self, mint_block: u64) -> bool {
+        match self.min_token_height {
+            Some(min) => mint_block >= min,
+            None => true,
+        }
+    }
+}
+
+// One ownership change of a token, recorded when the collection tracks history
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct HistoryEntry {
+    /// "mint", "transfer", "send_rolled_back" or "burn"
+    pub action: String,
+    /// None for a mint
+    pub from: Option<Addr>,
+    /// None for a burn
+    pub to: Option<Addr>,
+    pub height: u64,
+}
+
+// The contract told about every transfer, see SetTransferHook
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TransferHook {
+    pub contract: Addr,
+    /// If true, a failing hook fails the transfer, otherwise the failure is ignored
+    pub required: bool,
 }
 
 // Index by owner, approvals, token_uri, and extension (from TokenInfo)
@@ -170,7 +855,8 @@ where
     // Look at the MultiIndex struct definition for more info
     // MultiIndex stores (namespace, index_name, idx_value, pk) -> b"pk_len".
     pub owner: MultiIndex<'a, Addr, TokenInfo<T>, String>,
-
+    // mint order, for listing the newest tokens whatever their token_ids look like
+    pub mint_seq: MultiIndex<'a, u64, TokenInfo<T>, String>,
     // can add more indexes here
 }
 
@@ -179,7 +865,7 @@ where
     T: Serialize + DeserializeOwned + Clone,
 {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<TokenInfo<T>>> + '_> {
-        let v: Vec<&dyn Index<TokenInfo<T>>> = vec![&self.owner];
+        let v: Vec<&dyn Index<TokenInfo<T>>> = vec![&self.owner, &self.mint_seq];
         Box::new(v.into_iter())
     }
 }
@@ -188,4 +874,8 @@ pub fn token_owner_idx<T>(_pk: &[u8], d: &TokenInfo<T>) -> Addr {
     d.owner.clone()
 }
 
+pub fn token_mint_seq_idx<T>(_pk: &[u8], d: &TokenInfo<T>) -> u64 {
+    d.mint_seq
+}
+
 // can add index functions here
\ No newline at end of file
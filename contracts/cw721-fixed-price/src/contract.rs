@@ -58,10 +58,11 @@ pub fn instantiate(
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
         msg: WasmMsg::Instantiate {
             code_id: msg.token_code_id,
-            msg: to_binary(&Cw721InstantiateMsg {
+            msg: to_binary(&Cw721InstantiateMsg::<Extension> {
                 name: msg.name.clone(),
                 symbol: msg.symbol,
                 minter: env.contract.address.to_string(),
+                ..Default::default()
             })?,
             funds: vec![],
             admin: None,
@@ -81,7 +82,7 @@ pub fn instantiate(
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     let mut config: Config = CONFIG.load(deps.storage)?;
 
-    if config.cw721_address != None {
+    if config.cw721_address.is_some() {
         return Err(ContractError::Cw721AlreadyLinked {});
     }
 
@@ -147,7 +148,7 @@ pub fn execute_receive(
         return Err(ContractError::UnauthorizedTokenContract {});
     }
 
-    if config.cw721_address == None {
+    if config.cw721_address.is_none() {
         return Err(ContractError::Uninitialized {});
     }
 
@@ -164,6 +165,9 @@ pub fn execute_receive(
         owner: sender,
         token_uri: config.token_uri.clone().into(),
         extension: config.extension.clone(),
+        metadata_hash: None,
+        edition: None,
+        edition_of: None,
     });
 
     match config.cw721_address.clone() {
@@ -222,10 +226,11 @@ mod tests {
             vec![SubMsg {
                 msg: WasmMsg::Instantiate {
                     code_id: msg.token_code_id,
-                    msg: to_binary(&Cw721InstantiateMsg {
+                    msg: to_binary(&Cw721InstantiateMsg::<Extension> {
                         name: msg.name.clone(),
                         symbol: msg.symbol.clone(),
                         minter: MOCK_CONTRACT_ADDR.to_string(),
+                        ..Default::default()
                     })
                     .unwrap(),
                     funds: vec![],
@@ -376,6 +381,9 @@ mod tests {
             owner: String::from("minter"),
             token_uri: Some(String::from("https://ipfs.io/ipfs/Q")),
             extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
         });
 
         assert_eq!(
@@ -1,7 +1,7 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::Empty;
 use cw2::set_contract_version;
-pub use cw721_base::{ContractError, InstantiateMsg, MintMsg, MinterResponse};
+pub use cw721_base::{ContractError, MintMsg, MinterResponse};
 
 // Version info for migration
 const CONTRACT_NAME: &str = "crates.io:cw721-metadata-onchain";
@@ -34,13 +34,14 @@ pub type Extension = Option<Metadata>;
 pub type Cw721MetadataContract<'a> = cw721_base::Cw721Contract<'a, Extension, Empty, Empty, Empty>;
 pub type ExecuteMsg = cw721_base::ExecuteMsg<Extension, Empty>;
 pub type QueryMsg = cw721_base::QueryMsg<Empty>;
+pub type InstantiateMsg = cw721_base::InstantiateMsg<Extension>;
 
 #[cfg(not(feature = "library"))]
 pub mod entry {
     use super::*;
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
 
     // This makes a conscious choice on the various generics used by the contract
     #[entry_point]
@@ -67,6 +68,11 @@ pub mod entry {
         Cw721MetadataContract::default().execute(deps, env, info, msg)
     }
 
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        Cw721MetadataContract::default().reply(deps, env, msg)
+    }
+
     #[entry_point]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         Cw721MetadataContract::default().query(deps, env, msg)
@@ -92,6 +98,7 @@ mod tests {
             name: "SpaceShips".to_string(),
             symbol: "SPACE".to_string(),
             minter: CREATOR.to_string(),
+            ..Default::default()
         };
         contract
             .instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg)
@@ -107,6 +114,9 @@ mod tests {
                 name: Some("Starship USS Enterprise".to_string()),
                 ..Metadata::default()
             }),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
         };
         let exec_msg = ExecuteMsg::Mint(mint_msg.clone());
         contract
@@ -117,4 +127,203 @@ mod tests {
         assert_eq!(res.token_uri, mint_msg.token_uri);
         assert_eq!(res.extension, mint_msg.extension);
     }
+
+    #[test]
+    fn update_metadata_extension() {
+        let mut deps = mock_dependencies();
+        let contract = Cw721MetadataContract::default();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "Dungeon Loot".to_string(),
+            symbol: "LOOT".to_string(),
+            minter: CREATOR.to_string(),
+            ..Default::default()
+        };
+        contract
+            .instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg)
+            .unwrap();
+
+        let token_id = "sword";
+        let level = |value: &str| Metadata {
+            name: Some("Rusty Sword".to_string()),
+            attributes: Some(vec![Trait {
+                display_type: None,
+                trait_type: "level".to_string(),
+                value: value.to_string(),
+            }]),
+            ..Metadata::default()
+        };
+        let mint_msg = MintMsg {
+            token_id: token_id.to_string(),
+            owner: "john".to_string(),
+            token_uri: None,
+            extension: Some(level("1")),
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::Mint(mint_msg),
+            )
+            .unwrap();
+
+        // the owner can't rewrite metadata, only the minter can
+        let update_msg = ExecuteMsg::UpdateExtension {
+            token_id: token_id.to_string(),
+            extension: Some(level("99")),
+        };
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("john", &[]),
+                update_msg.clone(),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotMetadataUpdater {});
+
+        // level up
+        let update_msg = ExecuteMsg::UpdateExtension {
+            token_id: token_id.to_string(),
+            extension: Some(level("2")),
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), info.clone(), update_msg)
+            .unwrap();
+        let res = contract.nft_info(deps.as_ref(), token_id.into()).unwrap();
+        assert_eq!(res.extension, Some(level("2")));
+
+        // unknown tokens can't be updated
+        let update_msg = ExecuteMsg::UpdateExtension {
+            token_id: "shield".to_string(),
+            extension: None,
+        };
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), info, update_msg)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TokenNotFound {
+                token_id: "shield".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn metadata_updater_role() {
+        let mut deps = mock_dependencies();
+        let contract = Cw721MetadataContract::default();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "Dungeon Loot".to_string(),
+            symbol: "LOOT".to_string(),
+            minter: CREATOR.to_string(),
+            ..Default::default()
+        };
+        contract
+            .instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg)
+            .unwrap();
+        let mint_msg = MintMsg {
+            token_id: "helmet".to_string(),
+            owner: "john".to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::Mint(mint_msg),
+            )
+            .unwrap();
+
+        // only a minter or the owner can appoint an updater
+        let set_msg = ExecuteMsg::SetMetadataUpdater {
+            address: Some("backend".to_string()),
+        };
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("backend", &[]),
+                set_msg.clone(),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotMinterOrOwner {});
+        contract
+            .execute(deps.as_mut(), mock_env(), info.clone(), set_msg)
+            .unwrap();
+
+        // the updater can change metadata
+        let extension = Some(Metadata {
+            name: Some("Iron Helmet".to_string()),
+            ..Metadata::default()
+        });
+        let update_msg = ExecuteMsg::UpdateExtension {
+            token_id: "helmet".to_string(),
+            extension: extension.clone(),
+        };
+        let backend = mock_info("backend", &[]);
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                backend.clone(),
+                update_msg.clone(),
+            )
+            .unwrap();
+        let res = contract.nft_info(deps.as_ref(), "helmet".into()).unwrap();
+        assert_eq!(res.extension, extension);
+
+        // but random addresses can't
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("random", &[]),
+                update_msg.clone(),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotMetadataUpdater {});
+
+        // and the updater can't mint
+        let mint_msg = MintMsg {
+            token_id: "boots".to_string(),
+            owner: "backend".to_string(),
+            token_uri: None,
+            extension: None,
+            metadata_hash: None,
+            edition: None,
+            edition_of: None,
+        };
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                backend.clone(),
+                ExecuteMsg::Mint(mint_msg),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotMinter {});
+
+        // once cleared, the old updater loses access
+        let clear_msg = ExecuteMsg::SetMetadataUpdater { address: None };
+        contract
+            .execute(deps.as_mut(), mock_env(), info, clear_msg)
+            .unwrap();
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), backend, update_msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotMetadataUpdater {});
+    }
 }
@@ -1,7 +1,7 @@
 pub use crate::msg::{InstantiateMsg, QueryMsg};
 use cosmwasm_std::Empty;
 pub use cw721_base::{
-    entry::{execute as _execute, query as _query},
+    entry::{execute as _execute, query as _query, reply as _reply},
     ContractError, Cw721Contract, ExecuteMsg, Extension, InstantiateMsg as Cw721BaseInstantiateMsg,
     MintMsg, MinterResponse,
 };
@@ -22,7 +22,8 @@ pub mod entry {
     use crate::query::admin;
     use crate::state::{Config, CONFIG};
     use cosmwasm_std::{
-        entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+        entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+        StdResult,
     };
 
     #[entry_point]
@@ -46,6 +47,7 @@ pub mod entry {
             name: msg.name,
             symbol: msg.symbol,
             minter: msg.minter,
+            ..Default::default()
         };
 
         Cw721NonTransferableContract::default().instantiate(
@@ -87,6 +89,12 @@ pub mod entry {
         }
     }
 
+    // the admin may still SendNft, so failed ReceiveNft replies come back here
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        _reply(deps, env, msg)
+    }
+
     #[entry_point]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         match msg {
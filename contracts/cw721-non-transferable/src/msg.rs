@@ -65,7 +65,10 @@ impl From<QueryMsg> for Cw721QueryMsg<Empty> {
             },
             QueryMsg::NumTokens {} => Cw721QueryMsg::NumTokens {},
             QueryMsg::ContractInfo {} => Cw721QueryMsg::ContractInfo {},
-            QueryMsg::NftInfo { token_id } => Cw721QueryMsg::NftInfo { token_id },
+            QueryMsg::NftInfo { token_id } => Cw721QueryMsg::NftInfo {
+                token_id,
+                with_defaults: None,
+            },
             QueryMsg::AllNftInfo {
                 token_id,
                 include_expired,
@@ -6,8 +6,8 @@ use cosmwasm_std::Empty;
 
 use cw721::{
     AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, Cw721ExecuteMsg,
-    Cw721QueryMsg, Cw721ReceiveMsg, NftInfoResponse, NumTokensResponse, OperatorsResponse,
-    OwnerOfResponse, TokensResponse,
+    Cw721QueryMsg, Cw721ReceiveMsg, Cw721ReceiveWithOwnerMsg, NftInfoResponse, NumTokensResponse,
+    OperatorsResponse, OwnerOfResponse, TokensResponse,
 };
 
 type Extension = Option<Empty>;
@@ -21,6 +21,7 @@ fn main() {
     export_schema(&schema_for!(Cw721ExecuteMsg), &out_dir);
     export_schema(&schema_for!(Cw721QueryMsg), &out_dir);
     export_schema(&schema_for!(Cw721ReceiveMsg), &out_dir);
+    export_schema(&schema_for!(Cw721ReceiveWithOwnerMsg), &out_dir);
     export_schema_with_title(
         &schema_for!(AllNftInfoResponse<Extension>),
         &out_dir,
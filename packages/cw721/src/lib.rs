@@ -11,5 +11,5 @@ pub use crate::query::{
     Cw721QueryMsg, NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse,
     TokensResponse,
 };
-pub use crate::receiver::Cw721ReceiveMsg;
+pub use crate::receiver::{Cw721ReceiveMsg, Cw721ReceiveWithOwnerMsg};
 pub use crate::traits::{Cw721, Cw721Execute, Cw721Query};
@@ -116,8 +116,16 @@ pub struct NftInfoResponse<T> {
     /// Should point to a JSON file that conforms to the ERC721
     /// Metadata JSON Schema
     pub token_uri: Option<String>,
+    /// Block height at which the token was minted, if the contract recorded it
+    pub mint_block: Option<u64>,
     /// You can add any custom metadata here when you extend cw721-base
     pub extension: T,
+    /// Hex-encoded sha256 of the off-chain metadata, if the minter provided one
+    pub metadata_hash: Option<String>,
+    /// Serial number within a limited edition, e.g. 3 for "3 of 50"
+    pub edition: Option<u32>,
+    /// Size of the limited edition the token belongs to, e.g. 50 for "3 of 50"
+    pub edition_of: Option<u32>,
 }
 
 #[cw_serde]
@@ -126,6 +134,8 @@ pub struct AllNftInfoResponse<T> {
     pub access: OwnerOfResponse,
     /// Data on the token itself,
     pub info: NftInfoResponse<T>,
+    /// Block height of the last ownership change of the token, if the contract recorded it
+    pub last_transfer: Option<u64>,
 }
 
 #[cw_serde]
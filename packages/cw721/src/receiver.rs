@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{to_binary, Binary, CosmosMsg, StdResult, WasmMsg};
+use cosmwasm_std::{to_binary, Binary, Coin, CosmosMsg, StdResult, WasmMsg};
 
 /// Cw721ReceiveMsg should be de/serialized under `Receive()` variant in a ExecuteMsg
 #[cw_serde]
@@ -19,28 +19,118 @@ impl Cw721ReceiveMsg {
         to_binary(&msg)
     }
 
+    /// serializes the bare message, without the `receive_nft` variant around it
+    pub fn into_binary_unwrapped(self) -> StdResult<Binary> {
+        to_binary(&self)
+    }
+
     /// creates a cosmos_msg sending this struct to the named contract
     pub fn into_cosmos_msg<T: Into<String>, C>(self, contract_addr: T) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.into_cosmos_msg_with_funds(contract_addr, vec![])
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract,
+    /// forwarding the given funds along with it
+    pub fn into_cosmos_msg_with_funds<T: Into<String>, C>(
+        self,
+        contract_addr: T,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg<C>>
     where
         C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
     {
         // msg is the serialized binary version of struct/impl Cw721ReceiveMsg (self)
-        let msg = self.into_binary()?; 
+        let msg = self.into_binary()?;
         let execute = WasmMsg::Execute {
-            // attaches the contract address to the message 
+            // attaches the contract address to the message
             // attaching wasm message to the the cosmos message
-            contract_addr: contract_addr.into(), 
+            contract_addr: contract_addr.into(),
             // the serialized binary version of struct/impl Cw721ReceiveMsg (self)
-            msg, 
-            funds: vec![], // optionally attaches funds
+            msg,
+            funds, // optionally attaches funds
+        };
+        Ok(execute.into())
+    }
+
+    /// creates a cosmos_msg sending the bare struct (see into_binary_unwrapped) to the named
+    /// contract, for receivers that don't use the ReceiveNft variant
+    pub fn into_cosmos_msg_raw<T: Into<String>, C>(
+        self,
+        contract_addr: T,
+    ) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: self.into_binary_unwrapped()?,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// Cw721ReceiveMsg plus the owner the token was taken from, which differs from `sender` when
+/// an approved spender or operator sends it. Only sent to receivers that ask for it (SendNft's
+/// `with_owner`), under the `ReceiveNftWithOwner()` variant, so standard receivers are unaffected
+#[cw_serde]
+pub struct Cw721ReceiveWithOwnerMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+    pub previous_owner: String,
+}
+
+impl Cw721ReceiveWithOwnerMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = ReceiverExecuteMsg::ReceiveNftWithOwner(self);
+        to_binary(&msg)
+    }
+
+    /// serializes the bare message, without the `receive_nft_with_owner` variant around it
+    pub fn into_binary_unwrapped(self) -> StdResult<Binary> {
+        to_binary(&self)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>, C>(self, contract_addr: T) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: self.into_binary()?,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+
+    /// creates a cosmos_msg sending the bare struct, without the `receive_nft_with_owner`
+    /// variant around it
+    pub fn into_cosmos_msg_raw<T: Into<String>, C>(
+        self,
+        contract_addr: T,
+    ) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: self.into_binary_unwrapped()?,
+            funds: vec![],
         };
         Ok(execute.into())
     }
 }
 
-/// This is just a helper to properly serialize the above message.
-/// The actual receiver should include this variant in the larger ExecuteMsg enum
+/// This is just a helper to properly serialize the above messages.
+/// The actual receiver should include these variants in the larger ExecuteMsg enum
 #[cw_serde]
 enum ReceiverExecuteMsg {
     ReceiveNft(Cw721ReceiveMsg),
+    ReceiveNftWithOwner(Cw721ReceiveWithOwnerMsg),
 }